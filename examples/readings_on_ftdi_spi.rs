@@ -0,0 +1,29 @@
+//! Streams readings from all three sensors through an FT232H breakout, with
+//! SCK on D0, SDO on D1, SDI on D2, the LSM6DSL's chip select on D3 and the
+//! LIS3MDL's on D4.
+
+use ftdi_embedded_hal::libftd2xx::{Ft232h, Ftdi};
+use ftdi_embedded_hal::FtHal;
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+    let device: Ft232h = Ftdi::new()?.try_into()?;
+    let hal = FtHal::init_freq(device, 3_000_000)?;
+    let mut lsm6dsl = berryimu::spi_eh::Lsm6dsl::new(hal.spi_device(3)?)?;
+    let mut magnetometer = berryimu::spi_eh::Magnetometer::new(hal.spi_device(4)?)?;
+
+    loop {
+        let (acc, gyr) = lsm6dsl.read_accel_gyro()?;
+        let mag = magnetometer.read()?;
+
+        println!(
+            "acc: {} {} {}, gyr: {} {} {}, mag: {} {} {}",
+            acc.x, acc.y, acc.z, gyr.x, gyr.y, gyr.z, mag.x, mag.y, mag.z
+        );
+
+        // Sleep for 25ms
+        thread::sleep(Duration::from_millis(25));
+    }
+}
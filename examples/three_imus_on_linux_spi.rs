@@ -0,0 +1,49 @@
+//! Reads three BerryIMUs round-robin on a single SPI bus, with each board's
+//! LSM6DSL chip select wired to a GPIO that's been exported through sysfs.
+
+use spidev::{SpiModeFlags, Spidev, SpidevOptions};
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+const CHIP_SELECT_GPIOS: [u32; 3] = [5, 6, 13];
+
+// Chip selects are active low
+fn chip_select(gpio: u32) -> io::Result<impl FnMut(bool) -> io::Result<()>> {
+    let mut value = OpenOptions::new()
+        .write(true)
+        .open(format!("/sys/class/gpio/gpio{gpio}/value"))?;
+    value.write_all(b"1")?;
+    Ok(move |selected: bool| value.write_all(if selected { b"0" } else { b"1" }))
+}
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+    let mut imus = Vec::new();
+    for gpio in CHIP_SELECT_GPIOS {
+        let mut dev = Spidev::open("/dev/spidev0.0")?;
+        let options = SpidevOptions::new()
+            .bits_per_word(8)
+            .max_speed_hz(10_000_000)
+            .mode(SpiModeFlags::SPI_MODE_0 | SpiModeFlags::SPI_NO_CS)
+            .build();
+        dev.configure(&options)?;
+        imus.push(berryimu::spi::Lsm6dsl::new_with_chip_select(
+            dev,
+            chip_select(gpio)?,
+            berryimu::AccelConfig::default(),
+            berryimu::GyroConfig::default(),
+        )?);
+    }
+
+    loop {
+        for (i, imu) in imus.iter_mut().enumerate() {
+            let (acc, gyr) = imu.read_accel_gyro()?;
+            println!("{i}: acc {acc:?}, gyr {gyr:?}");
+        }
+
+        // Sleep for 25ms
+        thread::sleep(Duration::from_millis(25));
+    }
+}
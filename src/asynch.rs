@@ -0,0 +1,272 @@
+//! Async readers, generic over the bus they're accessed through. These share
+//! their register map, configuration and decoding with the blocking readers.
+//! See the `i2c` and `spi` submodules for constructors.
+
+pub mod i2c;
+pub mod spi;
+
+/// Async register-level access to one of the BerryIMUv3's chips. This is the
+/// async counterpart to `RegisterAccess`.
+#[allow(async_fn_in_trait)]
+pub trait AsyncRegisterAccess {
+    /// The error type of the underlying device.
+    type Error;
+
+    /// Reads a single register.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    async fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<Self::Error>>;
+
+    /// Writes a single register.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    /// * `value`: The value to write.
+    async fn write_register(&mut self, reg: u8, value: u8)
+        -> Result<(), crate::Error<Self::Error>>;
+
+    /// Reads consecutive registers.
+    ///
+    /// # Arguments
+    /// * `reg`: The address of the first register.
+    /// * `buf`: The buffer to read into, which determines how many registers
+    ///   are read.
+    async fn read_registers(
+        &mut self,
+        reg: u8,
+        buf: &mut [u8],
+    ) -> Result<(), crate::Error<Self::Error>>;
+}
+
+async fn init<B: AsyncRegisterAccess>(
+    bus: &mut B,
+    chip: crate::Chip,
+) -> Result<(), crate::Error<B::Error>> {
+    let who_am_i_response = bus.read_register(chip.who_am_i_register()).await?;
+    crate::bus::check_who_am_i(chip, who_am_i_response)
+}
+
+async fn identify_lsm6dsl<B: AsyncRegisterAccess>(
+    bus: &mut B,
+) -> Result<crate::lsm6dsl::Variant, crate::Error<B::Error>> {
+    let chip = crate::Chip::Lsm6dsl;
+    let who_am_i_response = bus.read_register(chip.who_am_i_register()).await?;
+    crate::lsm6dsl::Variant::from_who_am_i(who_am_i_response)
+}
+
+// Fails if the other sensor of the LSM6DSL, whose ODR is in
+// `other_odr_reg`, is already configured with a different endianness
+async fn check_endianness<B: AsyncRegisterAccess>(
+    bus: &mut B,
+    other_odr_reg: u8,
+    endianness: crate::Endianness,
+) -> Result<(), crate::Error<B::Error>> {
+    if !crate::lsm6dsl::is_powered(bus.read_register(other_odr_reg).await?) {
+        return Ok(());
+    }
+    let ctrl3_c = bus.read_register(crate::LSM6DSL_CTRL3_C).await?;
+    crate::lsm6dsl::check_shared_endianness(ctrl3_c, endianness)
+        .map_err(crate::Error::InvalidConfig)
+}
+
+async fn write_all<B: AsyncRegisterAccess>(
+    bus: &mut B,
+    writes: &[(u8, u8)],
+) -> Result<(), crate::Error<B::Error>> {
+    for &(reg, value) in writes {
+        bus.write_register(reg, value).await?;
+    }
+    Ok(())
+}
+
+// Changes only the masked bits of each register, e.g. of registers that are
+// shared between sensors.
+async fn modify_all<B: AsyncRegisterAccess>(
+    bus: &mut B,
+    modifies: impl IntoIterator<Item = (u8, u8, u8)>,
+) -> Result<(), crate::Error<B::Error>> {
+    for (reg, mask, value) in modifies {
+        let current = bus.read_register(reg).await?;
+        bus.write_register(reg, current & !mask | value & mask)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn read_xyz<B: AsyncRegisterAccess>(
+    bus: &mut B,
+    reg: u8,
+    endianness: crate::Endianness,
+) -> Result<(i32, i32, i32), crate::Error<B::Error>> {
+    let mut block = [0; 6];
+    bus.read_registers(reg, &mut block).await?;
+    Ok(crate::decode_xyz(&block, endianness))
+}
+
+/// An async accelerometer reader.
+pub struct Accelerometer<B: AsyncRegisterAccess> {
+    bus: B,
+    config: crate::AccelConfig,
+    variant: crate::lsm6dsl::Variant,
+}
+
+impl<B: AsyncRegisterAccess> Accelerometer<B> {
+    /// Creates a new accelerometer reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    /// * `config`: The accelerometer configuration.
+    pub async fn from_bus(
+        mut bus: B,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<B::Error>> {
+        let variant = identify_lsm6dsl(&mut bus).await?;
+        check_endianness(&mut bus, crate::LSM6DSL_CTRL2_G, config.endianness).await?;
+        let writes = crate::lsm6dsl::accel_init_writes(&config, variant)
+            .map_err(crate::Error::InvalidConfig)?;
+        write_all(&mut bus, &writes).await?;
+        modify_all(&mut bus, crate::lsm6dsl::accel_shared_bits(&config)).await?;
+        Ok(Self {
+            bus,
+            config,
+            variant,
+        })
+    }
+
+    /// The chip that was found.
+    pub fn variant(&self) -> crate::lsm6dsl::Variant {
+        self.variant
+    }
+
+    /// Read the raw accelerometer values.
+    pub async fn read(&mut self) -> Result<crate::AccelReading, crate::Error<B::Error>> {
+        let endianness = self.config.endianness;
+        Ok(
+            read_xyz(&mut self.bus, crate::LSM6DSL_OUTX_L_XL, endianness)
+                .await?
+                .into(),
+        )
+    }
+
+    /// Read the accelerometer values, in g.
+    pub async fn read_scaled(&mut self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        let endianness = self.config.endianness;
+        let xyz = read_xyz(&mut self.bus, crate::LSM6DSL_OUTX_L_XL, endianness).await?;
+        let sensitivity = self.config.full_scale.sensitivity();
+        Ok(crate::scale_xyz(xyz, sensitivity))
+    }
+}
+
+/// An async gyroscope reader.
+pub struct Gyroscope<B: AsyncRegisterAccess> {
+    bus: B,
+    config: crate::GyroConfig,
+    variant: crate::lsm6dsl::Variant,
+}
+
+impl<B: AsyncRegisterAccess> Gyroscope<B> {
+    /// Creates a new gyroscope reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    /// * `config`: The gyroscope configuration.
+    pub async fn from_bus(
+        mut bus: B,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<B::Error>> {
+        let variant = identify_lsm6dsl(&mut bus).await?;
+        check_endianness(&mut bus, crate::LSM6DSL_CTRL1_XL, config.endianness).await?;
+        let writes = crate::lsm6dsl::gyro_init_writes(&config, variant)
+            .map_err(crate::Error::InvalidConfig)?;
+        write_all(&mut bus, &writes).await?;
+        modify_all(&mut bus, crate::lsm6dsl::gyro_shared_bits(&config, variant)).await?;
+        Ok(Self {
+            bus,
+            config,
+            variant,
+        })
+    }
+
+    /// The chip that was found.
+    pub fn variant(&self) -> crate::lsm6dsl::Variant {
+        self.variant
+    }
+
+    /// Read the raw gyroscope values.
+    pub async fn read(&mut self) -> Result<crate::GyroReading, crate::Error<B::Error>> {
+        let endianness = self.config.endianness;
+        Ok(read_xyz(&mut self.bus, crate::LSM6DSL_OUTX_L_G, endianness)
+            .await?
+            .into())
+    }
+
+    /// Read the gyroscope values, in degrees per second.
+    pub async fn read_scaled(&mut self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        let endianness = self.config.endianness;
+        let xyz = read_xyz(&mut self.bus, crate::LSM6DSL_OUTX_L_G, endianness).await?;
+        let sensitivity = self.config.full_scale.degrees_per_lsb();
+        Ok(crate::scale_xyz(xyz, sensitivity))
+    }
+}
+
+/// An async magnetometer reader.
+pub struct Magnetometer<B: AsyncRegisterAccess> {
+    bus: B,
+    config: crate::MagConfig,
+}
+
+impl<B: AsyncRegisterAccess> Magnetometer<B> {
+    /// Creates a new magnetometer reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    /// * `config`: The magnetometer configuration.
+    pub async fn from_bus(
+        mut bus: B,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<B::Error>> {
+        init(&mut bus, crate::Chip::Lis3mdl).await?;
+        let writes = crate::lis3mdl::init_writes(&config).map_err(crate::Error::InvalidConfig)?;
+        write_all(&mut bus, &writes).await?;
+        Ok(Self { bus, config })
+    }
+
+    /// Read the raw magnetometer values.
+    pub async fn read(&mut self) -> Result<crate::MagReading, crate::Error<B::Error>> {
+        Ok(read_xyz(
+            &mut self.bus,
+            crate::LIS3MDL_OUT_X_L,
+            crate::Endianness::Little,
+        )
+        .await?
+        .into())
+    }
+
+    /// Read the magnetometer values, in gauss.
+    pub async fn read_scaled(&mut self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        let xyz = read_xyz(
+            &mut self.bus,
+            crate::LIS3MDL_OUT_X_L,
+            crate::Endianness::Little,
+        )
+        .await?;
+        let sensitivity = self.config.full_scale.gauss_per_lsb();
+        Ok(crate::scale_xyz(xyz, sensitivity))
+    }
+}
+
+/// Runs a future to completion, for testing against mocks, which never
+/// leave a future pending.
+#[cfg(test)]
+fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    use core::task::{Context, Poll, Waker};
+
+    let mut future = core::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
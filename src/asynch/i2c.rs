@@ -0,0 +1,217 @@
+//! Async readers for any I2C bus implementing embedded-hal-async's `I2c`
+//! trait.
+
+use super::AsyncRegisterAccess;
+use crate::asynch;
+use embedded_hal_async::i2c::I2c;
+
+/// Async register access to a chip through an embedded-hal-async I2C bus.
+pub struct I2cInterface<I: I2c> {
+    i2c: I,
+    address: u8,
+}
+
+impl<I: I2c> AsyncRegisterAccess for I2cInterface<I> {
+    type Error = I::Error;
+
+    async fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<I::Error>> {
+        let mut buf = [0];
+        self.i2c
+            .write_read(self.address, &[reg], &mut buf)
+            .await
+            .map_err(crate::Error::read(reg))?;
+        Ok(buf[0])
+    }
+
+    async fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<I::Error>> {
+        self.i2c
+            .write(self.address, &[reg, value])
+            .await
+            .map_err(crate::Error::write(reg))
+    }
+
+    async fn read_registers(
+        &mut self,
+        reg: u8,
+        buf: &mut [u8],
+    ) -> Result<(), crate::Error<I::Error>> {
+        self.i2c
+            .write_read(self.address, &[reg], buf)
+            .await
+            .map_err(crate::Error::read(reg))
+    }
+}
+
+/// An async accelerometer reader.
+pub type Accelerometer<I> = asynch::Accelerometer<I2cInterface<I>>;
+
+impl<I: I2c> Accelerometer<I> {
+    /// Creates a new accelerometer reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub async fn new(i2c: I) -> Result<Self, crate::Error<I::Error>> {
+        Accelerometer::new_with_config(i2c, crate::AccelConfig::default()).await
+    }
+
+    /// Creates a new accelerometer reader from an I2C bus, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `config`: The accelerometer configuration.
+    pub async fn new_with_config(
+        i2c: I,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<I::Error>> {
+        let address = crate::Chip::Lsm6dsl.i2c_address() as u8;
+        Accelerometer::from_bus(I2cInterface { i2c, address }, config).await
+    }
+}
+
+/// An async gyroscope reader.
+pub type Gyroscope<I> = asynch::Gyroscope<I2cInterface<I>>;
+
+impl<I: I2c> Gyroscope<I> {
+    /// Creates a new gyroscope reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub async fn new(i2c: I) -> Result<Self, crate::Error<I::Error>> {
+        Gyroscope::new_with_config(i2c, crate::GyroConfig::default()).await
+    }
+
+    /// Creates a new gyroscope reader from an I2C bus, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `config`: The gyroscope configuration.
+    pub async fn new_with_config(
+        i2c: I,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<I::Error>> {
+        let address = crate::Chip::Lsm6dsl.i2c_address() as u8;
+        Gyroscope::from_bus(I2cInterface { i2c, address }, config).await
+    }
+}
+
+/// An async magnetometer reader.
+pub type Magnetometer<I> = asynch::Magnetometer<I2cInterface<I>>;
+
+impl<I: I2c> Magnetometer<I> {
+    /// Creates a new magnetometer reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub async fn new(i2c: I) -> Result<Self, crate::Error<I::Error>> {
+        Magnetometer::new_with_config(i2c, crate::MagConfig::default()).await
+    }
+
+    /// Creates a new magnetometer reader from an I2C bus, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `config`: The magnetometer configuration.
+    pub async fn new_with_config(
+        i2c: I,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<I::Error>> {
+        let address = crate::Chip::Lis3mdl.i2c_address() as u8;
+        Magnetometer::from_bus(I2cInterface { i2c, address }, config).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asynch::block_on;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    #[test]
+    fn accelerometer_new() {
+        let mut i2c = Mock::new(&[
+            // WHO_AM_I
+            Transaction::write_read(0x6A, vec![0x0F], vec![0x6A]),
+            // The gyroscope's ODR in CTRL2_G, which is powered down
+            Transaction::write_read(0x6A, vec![0x11], vec![0x00]),
+            // CTRL1_XL, CTRL8_XL
+            Transaction::write(0x6A, vec![0x10, 0x9F]),
+            Transaction::write(0x6A, vec![0x17, 0xC8]),
+            // BDU, IF_INC and BLE in CTRL3_C
+            Transaction::write_read(0x6A, vec![0x12], vec![0x04]),
+            Transaction::write(0x6A, vec![0x12, 0x44]),
+            // XL_HM_MODE in CTRL6_C, keeping the gyroscope's FTYPE
+            Transaction::write_read(0x6A, vec![0x15], vec![0x01]),
+            Transaction::write(0x6A, vec![0x15, 0x01]),
+            // OUTX_L_XL
+            Transaction::write_read(0x6A, vec![0x28], vec![1, 0, 2, 0, 0xFD, 0xFF]),
+        ]);
+        block_on(async {
+            let mut accel = Accelerometer::new(i2c.clone()).await.unwrap();
+            assert_eq!(accel.read().await.unwrap(), (1, 2, -3).into());
+        });
+        i2c.done();
+    }
+
+    #[test]
+    fn gyroscope_new() {
+        let mut i2c = Mock::new(&[
+            // WHO_AM_I
+            Transaction::write_read(0x6A, vec![0x0F], vec![0x6A]),
+            // The accelerometer's ODR in CTRL1_XL, which is powered down
+            Transaction::write_read(0x6A, vec![0x10], vec![0x00]),
+            // CTRL2_G, CTRL7_G
+            Transaction::write(0x6A, vec![0x11, 0x9C]),
+            Transaction::write(0x6A, vec![0x16, 0x00]),
+            // BLE in CTRL3_C
+            Transaction::write_read(0x6A, vec![0x12], vec![0x44]),
+            Transaction::write(0x6A, vec![0x12, 0x44]),
+            // LPF1_SEL_G in CTRL4_C, and FTYPE in CTRL6_C
+            Transaction::write_read(0x6A, vec![0x13], vec![0x00]),
+            Transaction::write(0x6A, vec![0x13, 0x00]),
+            Transaction::write_read(0x6A, vec![0x15], vec![0x00]),
+            Transaction::write(0x6A, vec![0x15, 0x00]),
+            // OUTX_L_G
+            Transaction::write_read(0x6A, vec![0x22], vec![1, 0, 2, 0, 0xFD, 0xFF]),
+        ]);
+        block_on(async {
+            let mut gyro = Gyroscope::new(i2c.clone()).await.unwrap();
+            assert_eq!(gyro.read().await.unwrap(), (1, 2, -3).into());
+        });
+        i2c.done();
+    }
+
+    #[test]
+    fn magnetometer_new() {
+        let mut i2c = Mock::new(&[
+            // WHO_AM_I
+            Transaction::write_read(0x1C, vec![0x0F], vec![0x3D]),
+            // CTRL_REG1 to CTRL_REG5
+            Transaction::write(0x1C, vec![0x20, 0xDC]),
+            Transaction::write(0x1C, vec![0x21, 0x20]),
+            Transaction::write(0x1C, vec![0x22, 0x00]),
+            Transaction::write(0x1C, vec![0x23, 0x08]),
+            Transaction::write(0x1C, vec![0x24, 0x40]),
+            // OUT_X_L
+            Transaction::write_read(0x1C, vec![0x28], vec![1, 0, 2, 0, 0xFD, 0xFF]),
+        ]);
+        block_on(async {
+            let mut mag = Magnetometer::new(i2c.clone()).await.unwrap();
+            assert_eq!(mag.read().await.unwrap(), (1, 2, -3).into());
+        });
+        i2c.done();
+    }
+
+    #[test]
+    fn wrong_who_am_i() {
+        let mut i2c = Mock::new(&[Transaction::write_read(0x1C, vec![0x0F], vec![0x00])]);
+        let result = block_on(Magnetometer::new(i2c.clone()));
+        assert!(matches!(
+            result,
+            Err(crate::Error::Init { actual: 0x00, .. })
+        ));
+        i2c.done();
+    }
+}
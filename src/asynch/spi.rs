@@ -0,0 +1,199 @@
+//! Async readers for any SPI device implementing embedded-hal-async's
+//! `SpiDevice` trait.
+
+use super::AsyncRegisterAccess;
+use crate::asynch;
+use embedded_hal_async::spi::{Operation, SpiDevice};
+
+/// Async register access to a chip through an embedded-hal-async SPI device.
+pub struct SpiInterface<S: SpiDevice> {
+    spi: S,
+    chip: crate::Chip,
+}
+
+impl<S: SpiDevice> AsyncRegisterAccess for SpiInterface<S> {
+    type Error = S::Error;
+
+    async fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<S::Error>> {
+        let mut buf = [0];
+        self.read_registers(reg, &mut buf).await?;
+        Ok(buf[0])
+    }
+
+    async fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<S::Error>> {
+        self.spi
+            .write(&[reg, value])
+            .await
+            .map_err(crate::Error::write(reg))
+    }
+
+    async fn read_registers(
+        &mut self,
+        reg: u8,
+        buf: &mut [u8],
+    ) -> Result<(), crate::Error<S::Error>> {
+        let command = [self.chip.spi_read_command(reg, buf.len())];
+        self.spi
+            .transaction(&mut [Operation::Write(&command), Operation::Read(buf)])
+            .await
+            .map_err(crate::Error::read(reg))
+    }
+}
+
+/// An async accelerometer reader.
+pub type Accelerometer<S> = asynch::Accelerometer<SpiInterface<S>>;
+
+impl<S: SpiDevice> Accelerometer<S> {
+    /// Creates a new accelerometer reader from a SPI device.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    pub async fn new(spi: S) -> Result<Self, crate::Error<S::Error>> {
+        Accelerometer::new_with_config(spi, crate::AccelConfig::default()).await
+    }
+
+    /// Creates a new accelerometer reader from a SPI device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    /// * `config`: The accelerometer configuration.
+    pub async fn new_with_config(
+        spi: S,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<S::Error>> {
+        let chip = crate::Chip::Lsm6dsl;
+        Accelerometer::from_bus(SpiInterface { spi, chip }, config).await
+    }
+}
+
+/// An async gyroscope reader.
+pub type Gyroscope<S> = asynch::Gyroscope<SpiInterface<S>>;
+
+impl<S: SpiDevice> Gyroscope<S> {
+    /// Creates a new gyroscope reader from a SPI device.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    pub async fn new(spi: S) -> Result<Self, crate::Error<S::Error>> {
+        Gyroscope::new_with_config(spi, crate::GyroConfig::default()).await
+    }
+
+    /// Creates a new gyroscope reader from a SPI device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    /// * `config`: The gyroscope configuration.
+    pub async fn new_with_config(
+        spi: S,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<S::Error>> {
+        let chip = crate::Chip::Lsm6dsl;
+        Gyroscope::from_bus(SpiInterface { spi, chip }, config).await
+    }
+}
+
+/// An async magnetometer reader.
+pub type Magnetometer<S> = asynch::Magnetometer<SpiInterface<S>>;
+
+impl<S: SpiDevice> Magnetometer<S> {
+    /// Creates a new magnetometer reader from a SPI device.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    pub async fn new(spi: S) -> Result<Self, crate::Error<S::Error>> {
+        Magnetometer::new_with_config(spi, crate::MagConfig::default()).await
+    }
+
+    /// Creates a new magnetometer reader from a SPI device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    /// * `config`: The magnetometer configuration.
+    pub async fn new_with_config(
+        spi: S,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<S::Error>> {
+        let chip = crate::Chip::Lis3mdl;
+        Magnetometer::from_bus(SpiInterface { spi, chip }, config).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asynch::block_on;
+    use embedded_hal_mock::eh1::spi::{Mock, Transaction};
+
+    fn read(command: u8, response: &[u8]) -> [Transaction<u8>; 4] {
+        [
+            Transaction::transaction_start(),
+            Transaction::write_vec(vec![command]),
+            Transaction::read_vec(response.to_vec()),
+            Transaction::transaction_end(),
+        ]
+    }
+
+    fn write(reg: u8, value: u8) -> [Transaction<u8>; 3] {
+        [
+            Transaction::transaction_start(),
+            Transaction::write_vec(vec![reg, value]),
+            Transaction::transaction_end(),
+        ]
+    }
+
+    #[test]
+    fn accelerometer_new() {
+        let mut spi = Mock::new(
+            &[
+                // WHO_AM_I
+                &read(0x8F, &[0x6A])[..],
+                // The gyroscope's ODR in CTRL2_G, which is powered down
+                &read(0x91, &[0x00]),
+                // CTRL1_XL, CTRL8_XL
+                &write(0x10, 0x9F),
+                &write(0x17, 0xC8),
+                // BDU, IF_INC and BLE in CTRL3_C
+                &read(0x92, &[0x04]),
+                &write(0x12, 0x44),
+                // XL_HM_MODE in CTRL6_C, keeping the gyroscope's FTYPE
+                &read(0x95, &[0x01]),
+                &write(0x15, 0x01),
+                // OUTX_L_XL
+                &read(0xA8, &[1, 0, 2, 0, 0xFD, 0xFF]),
+            ]
+            .concat(),
+        );
+        block_on(async {
+            let mut accel = Accelerometer::new(spi.clone()).await.unwrap();
+            assert_eq!(accel.read().await.unwrap(), (1, 2, -3).into());
+        });
+        spi.done();
+    }
+
+    #[test]
+    fn magnetometer_new() {
+        let mut spi = Mock::new(
+            &[
+                // WHO_AM_I
+                &read(0x8F, &[0x3D])[..],
+                // CTRL_REG1 to CTRL_REG5
+                &write(0x20, 0xDC),
+                &write(0x21, 0x20),
+                &write(0x22, 0x00),
+                &write(0x23, 0x08),
+                &write(0x24, 0x40),
+                // OUT_X_L, with the MS bit set to auto-increment
+                &read(0xE8, &[1, 0, 2, 0, 0xFD, 0xFF]),
+            ]
+            .concat(),
+        );
+        block_on(async {
+            let mut mag = Magnetometer::new(spi.clone()).await.unwrap();
+            assert_eq!(mag.read().await.unwrap(), (1, 2, -3).into());
+        });
+        spi.done();
+    }
+}
@@ -0,0 +1,219 @@
+//! A reader for the BMP280 barometer carried by the BerryIMUv2, generic over
+//! the bus it's accessed through. This uses fixed settings, matching
+//! ozzmaker's reference code. See `i2c::Barometer` for constructors.
+
+use crate::bus::{self, RegisterAccess};
+
+/// The factory calibration coefficients, which are unique to each chip.
+#[derive(Clone, Copy, Debug)]
+struct Calibration {
+    t1: f64,
+    t2: f64,
+    t3: f64,
+    p1: f64,
+    p2: f64,
+    p3: f64,
+    p4: f64,
+    p5: f64,
+    p6: f64,
+    p7: f64,
+    p8: f64,
+    p9: f64,
+}
+
+impl Calibration {
+    fn decode(block: &[u8; 24]) -> Self {
+        let unsigned = |i: usize| u16::from_le_bytes([block[i], block[i + 1]]) as f64;
+        let signed = |i: usize| i16::from_le_bytes([block[i], block[i + 1]]) as f64;
+        Self {
+            t1: unsigned(0),
+            t2: signed(2),
+            t3: signed(4),
+            p1: unsigned(6),
+            p2: signed(8),
+            p3: signed(10),
+            p4: signed(12),
+            p5: signed(14),
+            p6: signed(16),
+            p7: signed(18),
+            p8: signed(20),
+            p9: signed(22),
+        }
+    }
+
+    // The floating point compensation formulas from section 8.1 of the
+    // datasheet. Returns the temperature in degrees celsius, and the "fine"
+    // temperature that pressure compensation depends on.
+    fn temperature(&self, adc_t: i32) -> (f64, f64) {
+        let adc_t = adc_t as f64;
+        let var1 = (adc_t / 16384.0 - self.t1 / 1024.0) * self.t2;
+        let var2 = adc_t / 131072.0 - self.t1 / 8192.0;
+        let var2 = var2 * var2 * self.t3;
+        let t_fine = var1 + var2;
+        (t_fine / 5120.0, t_fine)
+    }
+
+    // Returns the pressure in pascals.
+    fn pressure(&self, adc_p: i32, t_fine: f64) -> f64 {
+        let mut var1 = t_fine / 2.0 - 64000.0;
+        let mut var2 = var1 * var1 * self.p6 / 32768.0;
+        var2 += var1 * self.p5 * 2.0;
+        var2 = var2 / 4.0 + self.p4 * 65536.0;
+        var1 = (self.p3 * var1 * var1 / 524288.0 + self.p2 * var1) / 524288.0;
+        var1 = (1.0 + var1 / 32768.0) * self.p1;
+        if var1 == 0.0 {
+            // Avoids dividing by zero, which only happens with a zeroed
+            // calibration
+            return 0.0;
+        }
+        let mut p = 1048576.0 - adc_p as f64;
+        p = (p - var2 / 4096.0) * 6250.0 / var1;
+        var1 = self.p9 * p * p / 2147483648.0;
+        var2 = p * self.p8 / 32768.0;
+        p + (var1 + var2 + self.p7) / 16.0
+    }
+}
+
+// Combines the 20-bit big-endian readings of the pressure and temperature
+fn decode_adc(block: &[u8]) -> i32 {
+    (block[0] as i32) << 12 | (block[1] as i32) << 4 | (block[2] as i32) >> 4
+}
+
+/// A barometer reader.
+pub struct Barometer<B: RegisterAccess> {
+    bus: B,
+    calibration: Calibration,
+}
+
+impl<B: RegisterAccess> Barometer<B> {
+    /// Creates a new barometer reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    pub fn from_bus(mut bus: B) -> Result<Self, crate::Error<B::Error>> {
+        bus::init(&mut bus, crate::Chip::Bmp280)?;
+        let mut block = [0; 24];
+        bus.read_registers(crate::BMP280_CALIB00, &mut block)?;
+        let calibration = Calibration::decode(&block);
+        bus::write_all(
+            &mut bus,
+            &[
+                // 1x temperature & pressure oversampling, normal mode
+                (crate::BMP280_CTRL_MEAS, 0b00100111),
+                (crate::BMP280_CONFIG, 0b10100000), // 1000ms standby, filter off
+            ],
+        )?;
+        Ok(Self { bus, calibration })
+    }
+
+    // Reads the temperature and pressure in a single transaction, since
+    // compensating the pressure depends on the temperature.
+    fn read_compensated(&mut self) -> Result<(f64, f64), crate::Error<B::Error>> {
+        let mut block = [0; 6];
+        self.bus
+            .read_registers(crate::BMP280_PRESS_MSB, &mut block)?;
+        let (temperature, t_fine) = self.calibration.temperature(decode_adc(&block[3..]));
+        let pressure = self.calibration.pressure(decode_adc(&block[..3]), t_fine);
+        Ok((temperature, pressure))
+    }
+
+    /// Read the pressure, in pascals.
+    pub fn read_pressure_pa(&mut self) -> Result<f32, crate::Error<B::Error>> {
+        let (_, pressure) = self.read_compensated()?;
+        Ok(pressure as f32)
+    }
+
+    /// Read the temperature, in degrees celsius.
+    pub fn read_temperature_c(&mut self) -> Result<f32, crate::Error<B::Error>> {
+        let (temperature, _) = self.read_compensated()?;
+        Ok(temperature as f32)
+    }
+
+    /// Read the altitude, in meters, estimated from the pressure with the
+    /// international barometric formula.
+    ///
+    /// # Arguments
+    /// * `sea_level_pa`: The current pressure at sea level, in pascals, e.g.
+    ///   from a local weather report. The standard atmosphere's is 101325.
+    #[cfg(feature = "std")]
+    pub fn read_altitude_m(&mut self, sea_level_pa: f32) -> Result<f32, crate::Error<B::Error>> {
+        let (_, pressure) = self.read_compensated()?;
+        let altitude = 44330.0 * (1.0 - (pressure / sea_level_pa as f64).powf(1.0 / 5.255));
+        Ok(altitude as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeBus;
+
+    // The calibration of the datasheet's worked example
+    const COEFFICIENTS: [i32; 12] = [
+        27504, 26435, -1000, 36477, -10685, 3024, 2855, 140, -7, 15500, -14600, 6000,
+    ];
+
+    fn calibration_block() -> [u8; 24] {
+        let mut block = [0; 24];
+        for (bytes, coefficient) in block.chunks_mut(2).zip(COEFFICIENTS) {
+            // dig_T1 and dig_P1 are unsigned, but fit in either
+            bytes.copy_from_slice(&(coefficient as u16).to_le_bytes());
+        }
+        block
+    }
+
+    #[test]
+    fn decode_calibration() {
+        let calibration = Calibration::decode(&calibration_block());
+        let decoded = [
+            calibration.t1,
+            calibration.t2,
+            calibration.t3,
+            calibration.p1,
+            calibration.p2,
+            calibration.p3,
+            calibration.p4,
+            calibration.p5,
+            calibration.p6,
+            calibration.p7,
+            calibration.p8,
+            calibration.p9,
+        ];
+        assert_eq!(decoded, COEFFICIENTS.map(f64::from));
+    }
+
+    #[test]
+    fn decode_adc_value() {
+        // The low nibble of the last byte isn't part of the reading
+        assert_eq!(decode_adc(&[0x65, 0x5A, 0xCF]), 415148);
+        assert_eq!(decode_adc(&[0xFF, 0xFF, 0xF0]), 0xFFFFF);
+    }
+
+    #[test]
+    fn compensate() {
+        let calibration = Calibration::decode(&calibration_block());
+        let (temperature, t_fine) = calibration.temperature(519888);
+        assert!((temperature - 25.08).abs() < 0.01, "{}", temperature);
+        assert!((t_fine - 128422.0).abs() < 1.0, "{}", t_fine);
+        let pressure = calibration.pressure(415148, t_fine);
+        assert!((pressure - 100653.27).abs() < 0.1, "{}", pressure);
+    }
+
+    #[test]
+    fn read_compensated() {
+        let bus = FakeBus::new();
+        bus.set(crate::Chip::Bmp280.who_am_i_register(), 0x58);
+        for (i, &value) in calibration_block().iter().enumerate() {
+            bus.set(crate::BMP280_CALIB00 + i as u8, value);
+        }
+        let mut barometer = Barometer::from_bus(bus.clone()).unwrap();
+        // The pressure, and then the temperature
+        for (i, value) in [0x65, 0x5A, 0xC0, 0x7E, 0xED, 0x00].into_iter().enumerate() {
+            bus.set(crate::BMP280_PRESS_MSB + i as u8, value);
+        }
+        let pressure = barometer.read_pressure_pa().unwrap();
+        assert!((pressure - 100653.27).abs() < 0.1, "{}", pressure);
+        let temperature = barometer.read_temperature_c().unwrap();
+        assert!((temperature - 25.08).abs() < 0.01, "{}", temperature);
+    }
+}
@@ -0,0 +1,268 @@
+/// Register-level access to one of the BerryIMUv3's chips. This is what the
+/// readers are built on; each backend, e.g. `i2c` or `spi`, provides an
+/// implementation for its device type.
+pub trait RegisterAccess {
+    /// The error type of the underlying device.
+    type Error;
+
+    /// Reads a single register.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<Self::Error>>;
+
+    /// Writes a single register.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    /// * `value`: The value to write.
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<Self::Error>>;
+
+    /// Reads consecutive registers.
+    ///
+    /// # Arguments
+    /// * `reg`: The address of the first register.
+    /// * `buf`: The buffer to read into, which determines how many registers
+    ///   are read.
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), crate::Error<Self::Error>>;
+
+    /// Whether configuration writes should be read back to check that they
+    /// took effect, e.g. on a marginal bus. Defaults to `false`.
+    fn verify_writes(&self) -> bool {
+        false
+    }
+}
+
+pub(crate) fn init<B: RegisterAccess>(
+    bus: &mut B,
+    chip: crate::Chip,
+) -> Result<(), crate::Error<B::Error>> {
+    let who_am_i_response = bus.read_register(chip.who_am_i_register())?;
+    check_who_am_i(chip, who_am_i_response)
+}
+
+pub(crate) fn check_who_am_i<E>(chip: crate::Chip, response: u8) -> Result<(), crate::Error<E>> {
+    if chip.who_am_i().contains(&response) {
+        Ok(())
+    } else {
+        Err(crate::Error::Init {
+            expected: chip.who_am_i(),
+            actual: response,
+        })
+    }
+}
+
+pub(crate) fn write_all<B: RegisterAccess>(
+    bus: &mut B,
+    writes: &[(u8, u8)],
+) -> Result<(), crate::Error<B::Error>> {
+    for &(reg, value) in writes {
+        write_config(bus, reg, value)?;
+    }
+    Ok(())
+}
+
+/// Writes a configuration register. If the bus verifies writes, the
+/// register's read back, and a write that didn't take effect is retried
+/// once before failing with `Error::WriteMismatch`.
+pub(crate) fn write_config<B: RegisterAccess>(
+    bus: &mut B,
+    reg: u8,
+    value: u8,
+) -> Result<(), crate::Error<B::Error>> {
+    bus.write_register(reg, value)?;
+    if !bus.verify_writes() {
+        return Ok(());
+    }
+    let mut actual = bus.read_register(reg)?;
+    if actual != value {
+        bus.write_register(reg, value)?;
+        actual = bus.read_register(reg)?;
+    }
+    if actual == value {
+        Ok(())
+    } else {
+        Err(crate::Error::WriteMismatch {
+            reg,
+            expected: value,
+            actual,
+        })
+    }
+}
+
+/// Read-modify-writes a register, replacing only the bits set in `mask`.
+pub(crate) fn modify<B: RegisterAccess>(
+    bus: &mut B,
+    reg: u8,
+    mask: u8,
+    value: u8,
+) -> Result<(), crate::Error<B::Error>> {
+    let current = bus.read_register(reg)?;
+    write_config(bus, reg, current & !mask | value & mask)
+}
+
+/// Sets bits that clear themselves, e.g. to start a reboot. Unlike `modify`,
+/// this isn't verified, since the bits may clear before they're read back.
+pub(crate) fn set_self_clearing<B: RegisterAccess>(
+    bus: &mut B,
+    reg: u8,
+    mask: u8,
+    value: u8,
+) -> Result<(), crate::Error<B::Error>> {
+    let current = bus.read_register(reg)?;
+    bus.write_register(reg, current & !mask | value & mask)
+}
+
+/// How long to wait for a new sample before giving up, which is longer than
+/// the sample period at the slowest output data rate that's waited on.
+const SAMPLE_TIMEOUT: core::time::Duration = core::time::Duration::from_secs(1);
+
+/// Waits for the bits in `mask` to clear themselves, e.g. once a reset
+/// completes. With `std`, this gives up with `Error::Timeout` after 100ms.
+pub(crate) fn wait_until_clear<B: RegisterAccess>(
+    bus: &mut B,
+    reg: u8,
+    mask: u8,
+) -> Result<(), crate::Error<B::Error>> {
+    let timeout = core::time::Duration::from_millis(100);
+    wait_for(bus, reg, |value| value & mask == 0, timeout)
+}
+
+/// Waits for any of the bits in `mask` to be set, e.g. once a new sample is
+/// ready. With `std`, this gives up with `Error::Timeout` after a second.
+pub(crate) fn wait_until_set<B: RegisterAccess>(
+    bus: &mut B,
+    reg: u8,
+    mask: u8,
+) -> Result<(), crate::Error<B::Error>> {
+    wait_for(bus, reg, |value| value & mask != 0, SAMPLE_TIMEOUT)
+}
+
+#[cfg_attr(not(feature = "std"), allow(unused_variables))]
+fn wait_for<B: RegisterAccess>(
+    bus: &mut B,
+    reg: u8,
+    done: impl Fn(u8) -> bool,
+    timeout: core::time::Duration,
+) -> Result<(), crate::Error<B::Error>> {
+    #[cfg(feature = "std")]
+    let start = std::time::Instant::now();
+    while !done(bus.read_register(reg)?) {
+        #[cfg(feature = "std")]
+        {
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Err(crate::Error::Timeout { reg, elapsed });
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn read_xyz<B: RegisterAccess>(
+    bus: &mut B,
+    reg: u8,
+) -> Result<(i32, i32, i32), crate::Error<B::Error>> {
+    read_xyz_ordered(bus, reg, crate::Endianness::Little)
+}
+
+pub(crate) fn read_xyz_ordered<B: RegisterAccess>(
+    bus: &mut B,
+    reg: u8,
+    endianness: crate::Endianness,
+) -> Result<(i32, i32, i32), crate::Error<B::Error>> {
+    let mut block = [0; 6];
+    bus.read_registers(reg, &mut block)?;
+    Ok(crate::decode_xyz(&block, endianness))
+}
+
+/// Averages `count` samples, once each is signaled by `ready_mask` in the
+/// status register, after discarding the first `discard` samples to let the
+/// output settle. With `std`, this fails with `Error::Timeout` if a sample
+/// doesn't arrive.
+pub(crate) fn average_xyz<B: RegisterAccess>(
+    bus: &mut B,
+    status_reg: u8,
+    ready_mask: u8,
+    reg: u8,
+    endianness: crate::Endianness,
+    discard: u16,
+    count: i32,
+) -> Result<(i32, i32, i32), crate::Error<B::Error>> {
+    let mut sum = (0, 0, 0);
+    for i in 0..discard + count as u16 {
+        wait_until_set(bus, status_reg, ready_mask)?;
+        let (x, y, z) = read_xyz_ordered(bus, reg, endianness)?;
+        if i >= discard {
+            sum = (sum.0 + x, sum.1 + y, sum.2 + z);
+        }
+    }
+    Ok((sum.0 / count, sum.1 / count, sum.2 / count))
+}
+
+/// The absolute per-axis difference between two readings.
+pub(crate) fn delta_xyz(a: (i32, i32, i32), b: (i32, i32, i32)) -> (i32, i32, i32) {
+    ((a.0 - b.0).abs(), (a.1 - b.1).abs(), (a.2 - b.2).abs())
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn read_block<B: RegisterAccess>(
+    bus: &mut B,
+    start: u8,
+    count: u8,
+) -> Result<Vec<u8>, crate::Error<B::Error>> {
+    let mut block = vec![0; count as usize];
+    bus.read_registers(start, &mut block)?;
+    Ok(block)
+}
+
+/// Runs a transaction against a device, giving up with `Error::Timeout` if
+/// it doesn't complete within the timeout. A transaction that times out keeps
+/// running on a helper thread, holding the device's lock, so subsequent
+/// transactions time out too until it completes.
+#[cfg(any(
+    all(feature = "i2c", unix),
+    all(feature = "spi", any(target_os = "linux", target_os = "android"))
+))]
+pub(crate) fn with_timeout<D, T, E, F>(
+    dev: &std::sync::Arc<std::sync::Mutex<D>>,
+    reg: u8,
+    timeout: Option<std::time::Duration>,
+    f: F,
+) -> Result<T, crate::Error<E>>
+where
+    D: Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+    F: FnOnce(&mut D) -> Result<T, crate::Error<E>> + Send + 'static,
+{
+    use std::sync::{mpsc, PoisonError};
+    use std::time::Instant;
+    use std::{panic, thread};
+
+    let Some(timeout) = timeout else {
+        let mut dev = dev.lock().unwrap_or_else(PoisonError::into_inner);
+        return f(&mut dev);
+    };
+
+    let start = Instant::now();
+    let dev = dev.clone();
+    let (tx, rx) = mpsc::channel();
+    let thread = thread::spawn(move || {
+        let mut dev = dev.lock().unwrap_or_else(PoisonError::into_inner);
+        // The caller may have stopped waiting
+        let _ = tx.send(f(&mut dev));
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(crate::Error::Timeout {
+            reg,
+            elapsed: start.elapsed(),
+        }),
+        // The transaction panicked, so propagate it
+        Err(mpsc::RecvTimeoutError::Disconnected) => match thread.join() {
+            Err(payload) => panic::resume_unwind(payload),
+            Ok(()) => unreachable!("the transaction completed without sending a result"),
+        },
+    }
+}
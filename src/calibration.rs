@@ -0,0 +1,490 @@
+//! Calibration of the sensors' systematic errors.
+
+use crate::bus::RegisterAccess;
+use crate::lis3mdl::Magnetometer;
+use crate::GyroSensor;
+
+/// The gyroscope's bias, i.e. the rate it reads while stationary, in raw
+/// units.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GyroBias {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl GyroBias {
+    /// Removes the bias from a raw gyroscope reading.
+    ///
+    /// # Arguments
+    /// * `reading`: The raw gyroscope values.
+    pub fn apply(&self, reading: crate::GyroReading) -> (f64, f64, f64) {
+        (
+            reading.x as f64 - self.x,
+            reading.y as f64 - self.y,
+            reading.z as f64 - self.z,
+        )
+    }
+}
+
+/// Measures the gyroscope's bias, as the average of readings taken while
+/// it's stationary. The bias drifts with temperature, so it's best measured
+/// shortly before use. Readings are taken back to back, so at low output
+/// data rates some may repeat the same sample.
+///
+/// # Arguments
+/// * `gyro`: The gyroscope reader, on any version of the board.
+/// * `samples`: How many readings to average.
+pub fn calibrate_gyro_bias<G: GyroSensor>(
+    gyro: &mut G,
+    samples: usize,
+) -> Result<GyroBias, crate::Error<G::Error>> {
+    if samples == 0 {
+        return Ok(GyroBias::default());
+    }
+    let mut sum = (0.0, 0.0, 0.0);
+    for _ in 0..samples {
+        let reading = gyro.read_gyro()?;
+        sum.0 += reading.x as f64;
+        sum.1 += reading.y as f64;
+        sum.2 += reading.z as f64;
+    }
+    let count = samples as f64;
+    Ok(GyroBias {
+        x: sum.0 / count,
+        y: sum.1 / count,
+        z: sum.2 / count,
+    })
+}
+
+/// The accelerometer's per-axis offset and scale, which convert raw
+/// readings to g.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccelCalibration {
+    /// The raw reading of each axis at 0 g.
+    pub offset_x: f64,
+    pub offset_y: f64,
+    pub offset_z: f64,
+    /// The raw change in each axis per g, in LSB/g.
+    pub scale_x: f64,
+    pub scale_y: f64,
+    pub scale_z: f64,
+}
+
+impl AccelCalibration {
+    /// Converts a raw accelerometer reading to g.
+    ///
+    /// # Arguments
+    /// * `reading`: The raw accelerometer values.
+    pub fn apply(&self, reading: crate::AccelReading) -> (f64, f64, f64) {
+        (
+            (reading.x as f64 - self.offset_x) / self.scale_x,
+            (reading.y as f64 - self.offset_y) / self.scale_y,
+            (reading.z as f64 - self.offset_z) / self.scale_z,
+        )
+    }
+}
+
+/// Computes the accelerometer's calibration from readings taken at rest in
+/// six orientations, so that each axis in turn reads +1 g and then -1 g.
+/// Each reading is best averaged over a few seconds, with the sensor held
+/// still against a level surface.
+///
+/// # Arguments
+/// * `readings`: The raw readings with, in order, the X axis pointing up,
+///   the X axis pointing down, then likewise the Y and Z axes.
+pub fn calibrate_accel_6point(readings: [(i32, i32, i32); 6]) -> AccelCalibration {
+    let [x_up, x_down, y_up, y_down, z_up, z_down] = readings;
+    let axis = |up: i32, down: i32| {
+        let (up, down) = (up as f64, down as f64);
+        ((up + down) / 2.0, (up - down) / 2.0)
+    };
+    let (offset_x, scale_x) = axis(x_up.0, x_down.0);
+    let (offset_y, scale_y) = axis(y_up.1, y_down.1);
+    let (offset_z, scale_z) = axis(z_up.2, z_down.2);
+    AccelCalibration {
+        offset_x,
+        offset_y,
+        offset_z,
+        scale_x,
+        scale_y,
+        scale_z,
+    }
+}
+
+/// The magnetometer's hard-iron offset, i.e. the constant bias that nearby
+/// magnetized materials add to every reading, in raw units.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HardIronCalibration {
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub offset_z: i32,
+}
+
+impl HardIronCalibration {
+    /// Removes the offset from a raw magnetometer reading.
+    ///
+    /// # Arguments
+    /// * `reading`: The raw magnetometer values.
+    pub fn apply(&self, reading: crate::MagReading) -> crate::MagReading {
+        crate::MagReading {
+            x: reading.x - self.offset_x,
+            y: reading.y - self.offset_y,
+            z: reading.z - self.offset_z,
+        }
+    }
+}
+
+/// Measures the magnetometer's hard-iron offset, as the center of the range
+/// each axis covers. The sensor should be rotated through every orientation
+/// while this runs, e.g. in a figure of eight, and the offset should be
+/// remeasured whenever whatever it's mounted in changes. This fails with
+/// `Error::PoweredDown` if the magnetometer is powered down, e.g. after
+/// `read_single`, and with `std`, with `Error::Timeout` if a sample takes
+/// longer than a second.
+///
+/// # Arguments
+/// * `mag`: The magnetometer reader.
+/// * `samples`: How many samples to collect. At the default 80 Hz, 1600
+///   samples take 20 seconds.
+pub fn calibrate_hard_iron<B: RegisterAccess>(
+    mag: &mut Magnetometer<B>,
+    samples: usize,
+) -> Result<HardIronCalibration, crate::Error<B::Error>> {
+    if samples == 0 {
+        return Ok(HardIronCalibration::default());
+    }
+    let mut min = (i32::MAX, i32::MAX, i32::MAX);
+    let mut max = (i32::MIN, i32::MIN, i32::MIN);
+    for _ in 0..samples {
+        mag.wait_for_sample()?;
+        let reading = mag.read()?;
+        min = (
+            min.0.min(reading.x),
+            min.1.min(reading.y),
+            min.2.min(reading.z),
+        );
+        max = (
+            max.0.max(reading.x),
+            max.1.max(reading.y),
+            max.2.max(reading.z),
+        );
+    }
+    Ok(HardIronCalibration {
+        offset_x: (max.0 + min.0) / 2,
+        offset_y: (max.1 + min.1) / 2,
+        offset_z: (max.2 + min.2) / 2,
+    })
+}
+
+/// The magnetometer's soft-iron correction, which undoes the distortion
+/// that nearby magnetically soft materials cause, squashing the sphere of
+/// readings taken in every orientation into an ellipsoid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SoftIronCalibration {
+    /// The correction matrix, in row-major order.
+    pub matrix: [[f64; 3]; 3],
+}
+
+impl Default for SoftIronCalibration {
+    /// No correction
+    fn default() -> Self {
+        Self {
+            matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+}
+
+impl SoftIronCalibration {
+    /// Corrects a magnetometer reading, which should already have had its
+    /// hard-iron offset removed with `HardIronCalibration::apply`.
+    ///
+    /// # Arguments
+    /// * `reading`: The magnetometer values, in raw units.
+    pub fn apply(&self, reading: crate::MagReading) -> (f64, f64, f64) {
+        let v = [reading.x as f64, reading.y as f64, reading.z as f64];
+        let row = |i: usize| {
+            let m: &[f64; 3] = &self.matrix[i];
+            m[0] * v[0] + m[1] * v[1] + m[2] * v[2]
+        };
+        (row(0), row(1), row(2))
+    }
+}
+
+/// Computes the soft-iron correction by a least-squares fit of an ellipsoid
+/// to magnetometer readings, taken while the sensor is rotated through every
+/// orientation. The correction maps the ellipsoid onto a sphere of the same
+/// volume, so corrected readings keep roughly the same magnitude. The fit
+/// also accounts for any hard-iron offset in the readings.
+///
+/// Returns `None` if the readings don't determine an ellipsoid, e.g. because
+/// there are fewer than 9, or they don't cover enough orientations.
+///
+/// # Arguments
+/// * `samples`: The raw magnetometer readings.
+#[cfg(feature = "std")]
+pub fn fit_ellipsoid(samples: &[crate::MagReading]) -> Option<SoftIronCalibration> {
+    // The readings are scaled to around 1 to keep the normal equations well
+    // conditioned, which doesn't change the fitted correction
+    let scale = samples
+        .iter()
+        .flat_map(|r| [r.x, r.y, r.z])
+        .map(|v| (v as f64).abs())
+        .fold(0.0, f64::max);
+    if samples.len() < 9 || scale == 0.0 {
+        return None;
+    }
+
+    // Fits the quadric
+    // ax^2 + by^2 + cz^2 + 2dyz + 2exz + 2fxy + 2gx + 2hy + 2iz = 1
+    let mut normal = [[0.0; 10]; 9];
+    for reading in samples {
+        let (x, y, z) = (
+            reading.x as f64 / scale,
+            reading.y as f64 / scale,
+            reading.z as f64 / scale,
+        );
+        let row = [
+            x * x,
+            y * y,
+            z * z,
+            2.0 * y * z,
+            2.0 * x * z,
+            2.0 * x * y,
+            2.0 * x,
+            2.0 * y,
+            2.0 * z,
+        ];
+        for i in 0..9 {
+            for j in 0..9 {
+                normal[i][j] += row[i] * row[j];
+            }
+            normal[i][9] += row[i];
+        }
+    }
+    let p = solve(normal)?;
+    let a = [[p[0], p[5], p[4]], [p[5], p[1], p[3]], [p[4], p[3], p[2]]];
+    let b = [p[6], p[7], p[8]];
+
+    // The center solves a * center = -b, and shifting the quadric there
+    // leaves (x - center)' a (x - center) = 1 + center' a center
+    let center = solve([
+        [a[0][0], a[0][1], a[0][2], -b[0]],
+        [a[1][0], a[1][1], a[1][2], -b[1]],
+        [a[2][0], a[2][1], a[2][2], -b[2]],
+    ])?;
+    let k = 1.0 - (0..3).map(|i| b[i] * center[i]).sum::<f64>();
+
+    // The correction is the square root of a / k, which maps the ellipsoid
+    // onto the unit sphere, scaled back up by the ellipsoid's mean radius
+    let (eigenvalues, eigenvectors) = symmetric_eigen(a);
+    let eigenvalues = eigenvalues.map(|e| e / k);
+    if eigenvalues.iter().any(|&e| e <= 0.0 || !e.is_finite()) {
+        return None;
+    }
+    let mean_radius = eigenvalues
+        .iter()
+        .map(|e| e.sqrt().recip())
+        .product::<f64>()
+        .cbrt();
+    let mut matrix = [[0.0; 3]; 3];
+    for (i, row) in matrix.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3)
+                .map(|n| eigenvectors[i][n] * eigenvalues[n].sqrt() * eigenvectors[j][n])
+                .sum::<f64>()
+                * mean_radius;
+        }
+    }
+    Some(SoftIronCalibration { matrix })
+}
+
+// Solves a system of linear equations, given as an augmented matrix, by
+// Gaussian elimination with partial pivoting. Returns `None` if it's
+// singular.
+#[cfg(feature = "std")]
+fn solve<const N: usize, const M: usize>(mut m: [[f64; M]; N]) -> Option<[f64; N]> {
+    for col in 0..N {
+        let pivot = (col..N).max_by(|&i, &j| m[i][col].abs().total_cmp(&m[j][col].abs()))?;
+        if m[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        m.swap(col, pivot);
+        let pivot_row = m[col];
+        for row in &mut m[col + 1..] {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * pivot;
+            }
+        }
+    }
+    let mut x = [0.0; N];
+    for row in (0..N).rev() {
+        let sum = (row + 1..N).map(|k| m[row][k] * x[k]).sum::<f64>();
+        x[row] = (m[row][N] - sum) / m[row][row];
+    }
+    Some(x)
+}
+
+// Computes the eigenvalues and eigenvectors, as columns, of a symmetric
+// 3x3 matrix, by Jacobi rotations
+#[cfg(feature = "std")]
+fn symmetric_eigen(mut a: [[f64; 3]; 3]) -> ([f64; 3], [[f64; 3]; 3]) {
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    for _ in 0..50 {
+        let off_diagonal = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+        if off_diagonal < 1e-30 {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+            // The rotation that zeroes a[p][q]
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let t = if theta == 0.0 { 1.0 } else { t };
+            let c = (t * t + 1.0).sqrt().recip();
+            let s = t * c;
+            for row in a.iter_mut() {
+                let (ap, aq) = (row[p], row[q]);
+                row[p] = c * ap - s * aq;
+                row[q] = s * ap + c * aq;
+            }
+            let (ap, aq) = (a[p], a[q]);
+            a[p] = core::array::from_fn(|k| c * ap[k] - s * aq[k]);
+            a[q] = core::array::from_fn(|k| s * ap[k] + c * aq[k]);
+            for row in v.iter_mut() {
+                let (vp, vq) = (row[p], row[q]);
+                row[p] = c * vp - s * vq;
+                row[q] = s * vp + c * vq;
+            }
+        }
+    }
+    ([a[0][0], a[1][1], a[2][2]], v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeBus;
+    use crate::MagConfig;
+
+    #[test]
+    fn hard_iron_powered_down() {
+        let bus = FakeBus::lis3mdl();
+        let mut mag = Magnetometer::from_bus(bus.clone(), MagConfig::default()).unwrap();
+        mag.set_enabled(false).unwrap();
+        // ZYXDA is never set
+        assert!(matches!(
+            calibrate_hard_iron(&mut mag, 10),
+            Err(crate::Error::PoweredDown)
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hard_iron_times_out() {
+        let bus = FakeBus::lis3mdl();
+        let mut mag = Magnetometer::from_bus(bus.clone(), MagConfig::default()).unwrap();
+        assert!(matches!(
+            calibrate_hard_iron(&mut mag, 10),
+            Err(crate::Error::Timeout {
+                reg: crate::LIS3MDL_STATUS_REG,
+                ..
+            })
+        ));
+    }
+
+    // Positions on an ellipsoid with the given radii, rotated by `rotation`
+    // and then offset by `center`, covering every orientation
+    #[cfg(feature = "std")]
+    fn ellipsoid(
+        radii: [f64; 3],
+        rotation: [[f64; 3]; 3],
+        center: [f64; 3],
+    ) -> Vec<crate::MagReading> {
+        let mut samples = Vec::new();
+        for i in 1..20 {
+            let polar = i as f64 / 20.0 * core::f64::consts::PI;
+            for j in 0..40 {
+                let azimuth = j as f64 / 40.0 * 2.0 * core::f64::consts::PI;
+                let unit = [
+                    polar.sin() * azimuth.cos(),
+                    polar.sin() * azimuth.sin(),
+                    polar.cos(),
+                ];
+                let p: [f64; 3] = core::array::from_fn(|k| {
+                    (0..3).map(|n| rotation[k][n] * radii[n] * unit[n]).sum()
+                });
+                samples.push(crate::MagReading {
+                    x: (p[0] + center[0]).round() as i32,
+                    y: (p[1] + center[1]).round() as i32,
+                    z: (p[2] + center[2]).round() as i32,
+                });
+            }
+        }
+        samples
+    }
+
+    // Checks that the corrected positions, relative to the center, all lie
+    // within 1% of the ellipsoid's mean radius
+    #[cfg(feature = "std")]
+    fn assert_corrected_to_sphere(radii: [f64; 3], rotation: [[f64; 3]; 3]) {
+        let center = [500.0, -300.0, 200.0];
+        let samples = ellipsoid(radii, rotation, center);
+        let calibration = fit_ellipsoid(&samples).unwrap();
+        let mean_radius = radii.iter().product::<f64>().cbrt();
+        for reading in samples {
+            let centered = crate::MagReading {
+                x: reading.x - center[0] as i32,
+                y: reading.y - center[1] as i32,
+                z: reading.z - center[2] as i32,
+            };
+            let (x, y, z) = calibration.apply(centered);
+            let norm = (x * x + y * y + z * z).sqrt();
+            assert!(
+                (norm / mean_radius - 1.0).abs() < 0.01,
+                "{} vs {}",
+                norm,
+                mean_radius
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn fit_axis_aligned_ellipsoid() {
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        assert_corrected_to_sphere([3000.0, 2000.0, 1000.0], identity);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn fit_rotated_ellipsoid() {
+        // 30 degrees about z, and then 45 degrees about x
+        let (s30, c30) = 30f64.to_radians().sin_cos();
+        let (s45, c45) = 45f64.to_radians().sin_cos();
+        let about_z = [[c30, -s30, 0.0], [s30, c30, 0.0], [0.0, 0.0, 1.0]];
+        let about_x = [[1.0, 0.0, 0.0], [0.0, c45, -s45], [0.0, s45, c45]];
+        let rotation: [[f64; 3]; 3] = core::array::from_fn(|i| {
+            core::array::from_fn(|j| (0..3).map(|n| about_x[i][n] * about_z[n][j]).sum())
+        });
+        assert_corrected_to_sphere([3000.0, 2000.0, 1000.0], rotation);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn fit_degenerate_samples() {
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let samples = ellipsoid([3000.0, 2000.0, 1000.0], identity, [0.0; 3]);
+        assert_eq!(fit_ellipsoid(&samples[..8]), None);
+        // A circle, rather than every orientation
+        let coplanar: Vec<_> = samples
+            .iter()
+            .map(|reading| crate::MagReading { z: 0, ..*reading })
+            .collect();
+        assert_eq!(fit_ellipsoid(&coplanar), None);
+        assert_eq!(fit_ellipsoid(&[]), None);
+    }
+}
@@ -0,0 +1,1485 @@
+/// Accelerometer output data rate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AccelOdr {
+    PowerDown = 0b0000,
+    /// 1.6 Hz. Only available in low-power mode.
+    Hz1_6 = 0b1011,
+    Hz12_5 = 0b0001,
+    Hz26 = 0b0010,
+    Hz52 = 0b0011,
+    Hz104 = 0b0100,
+    Hz208 = 0b0101,
+    Hz416 = 0b0110,
+    Hz833 = 0b0111,
+    Hz1660 = 0b1000,
+    Hz3330 = 0b1001,
+    Hz6660 = 0b1010,
+}
+
+impl AccelOdr {
+    /// Decodes the ODR_XL bits of `CTRL1_XL`. In high-performance mode, the
+    /// 1.6 Hz bits select 12.5 Hz instead.
+    fn from_bits(bits: u8, low_power: bool) -> Option<Self> {
+        match bits {
+            0b0000 => Some(AccelOdr::PowerDown),
+            0b1011 if low_power => Some(AccelOdr::Hz1_6),
+            0b0001 | 0b1011 => Some(AccelOdr::Hz12_5),
+            0b0010 => Some(AccelOdr::Hz26),
+            0b0011 => Some(AccelOdr::Hz52),
+            0b0100 => Some(AccelOdr::Hz104),
+            0b0101 => Some(AccelOdr::Hz208),
+            0b0110 => Some(AccelOdr::Hz416),
+            0b0111 => Some(AccelOdr::Hz833),
+            0b1000 => Some(AccelOdr::Hz1660),
+            0b1001 => Some(AccelOdr::Hz3330),
+            0b1010 => Some(AccelOdr::Hz6660),
+            _ => None,
+        }
+    }
+}
+
+/// Accelerometer full-scale range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AccelFullScale {
+    /// +/- 2g
+    G2 = 0b00,
+    /// +/- 4g
+    G4 = 0b10,
+    /// +/- 8g
+    G8 = 0b11,
+    /// +/- 16g
+    G16 = 0b01,
+}
+
+impl AccelFullScale {
+    /// The conversion factor from raw readings to g, in g/LSB.
+    pub fn sensitivity(self) -> f32 {
+        match self {
+            AccelFullScale::G2 => 0.000061,
+            AccelFullScale::G4 => 0.000122,
+            AccelFullScale::G8 => 0.000244,
+            AccelFullScale::G16 => 0.000488,
+        }
+    }
+
+    /// Decodes the FS_XL bits of `CTRL1_XL`.
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => AccelFullScale::G2,
+            0b10 => AccelFullScale::G4,
+            0b11 => AccelFullScale::G8,
+            _ => AccelFullScale::G16,
+        }
+    }
+}
+
+/// Accelerometer power mode. The low-power and normal modes are each only
+/// available at particular output data rates, and draw less current than
+/// high-performance mode at the cost of more noise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerMode {
+    HighPerformance,
+    /// Available at 104 and 208 Hz.
+    Normal,
+    /// Available at up to 52 Hz.
+    LowPower,
+}
+
+/// The bandwidth of the accelerometer's digital low-pass filter, relative to
+/// the output data rate. The LSM6DSO's closest bandwidths are used on it,
+/// i.e. ODR/10 for `Odr9` and ODR/45 for `Odr50`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LowPassBandwidth {
+    Odr9,
+    Odr50,
+    Odr100,
+    Odr400,
+}
+
+/// The cutoff of the accelerometer's digital high-pass filter, relative to
+/// the output data rate. The LSM6DSO's ODR/10 cutoff is used for `Odr9` on
+/// it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HighPassCutoff {
+    /// The slope filter, which subtracts the previous sample.
+    Odr4,
+    Odr9,
+    Odr100,
+    Odr400,
+}
+
+/// The accelerometer's digital filtering. Narrower filters add more group
+/// delay, and take longer to settle after being changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccelFilter {
+    /// Neither the low-pass nor high-pass filter is applied.
+    Bypass,
+    LowPass(LowPassBandwidth),
+    /// Removes gravity, and other slowly changing acceleration.
+    HighPass(HighPassCutoff),
+}
+
+impl AccelFilter {
+    /// The approximate number of samples the filter takes to settle after
+    /// it's configured.
+    pub fn settling_samples(self) -> u16 {
+        match self {
+            AccelFilter::Bypass => 0,
+            AccelFilter::HighPass(HighPassCutoff::Odr4) => 2,
+            AccelFilter::LowPass(LowPassBandwidth::Odr9)
+            | AccelFilter::HighPass(HighPassCutoff::Odr9) => 12,
+            AccelFilter::LowPass(LowPassBandwidth::Odr50) => 80,
+            AccelFilter::LowPass(LowPassBandwidth::Odr100)
+            | AccelFilter::HighPass(HighPassCutoff::Odr100) => 160,
+            AccelFilter::LowPass(LowPassBandwidth::Odr400)
+            | AccelFilter::HighPass(HighPassCutoff::Odr400) => 640,
+        }
+    }
+}
+
+/// The byte order of the LSM6DSL's output registers, which is set by the
+/// BLE bit of `CTRL3_C`, and shared by the accelerometer and gyroscope.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// The BLE bit of `CTRL3_C`.
+    pub(crate) fn ctrl3_c(self) -> u8 {
+        ((self == Endianness::Big) as u8) << 1
+    }
+
+    /// Decodes the BLE bit of `CTRL3_C`.
+    pub(crate) fn from_ctrl3_c(ctrl3_c: u8) -> Self {
+        if ctrl3_c & 0b10 != 0 {
+            Endianness::Big
+        } else {
+            Endianness::Little
+        }
+    }
+
+    /// Combines the two bytes of a reading, in register order.
+    pub(crate) fn decode(self, bytes: [u8; 2]) -> i16 {
+        match self {
+            Endianness::Little => i16::from_le_bytes(bytes),
+            Endianness::Big => i16::from_be_bytes(bytes),
+        }
+    }
+}
+
+/// Accelerometer configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccelConfig {
+    pub odr: AccelOdr,
+    pub full_scale: AccelFullScale,
+    /// Whether block data update is enabled, so that the output registers
+    /// aren't updated until both the low and high bytes have been read.
+    pub bdu: bool,
+    pub power_mode: PowerMode,
+    pub filter: AccelFilter,
+    /// The byte order of the output registers, which has to match the
+    /// gyroscope's once it's powered, since they share a bit. Readers fail with
+    /// `Error::InvalidConfig` otherwise.
+    pub endianness: Endianness,
+    /// Whether the accelerometer is powered down when its reader is dropped, e.g.
+    /// so it stops drawing current once the program exits.
+    pub power_down_on_drop: bool,
+}
+
+impl AccelConfig {
+    /// Creates a builder, starting from the default configuration.
+    pub fn builder() -> AccelBuilder {
+        AccelBuilder::default()
+    }
+
+    /// Checks that this configuration can be applied.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        match (self.power_mode, self.odr) {
+            (_, AccelOdr::PowerDown) => Ok(()),
+            (
+                PowerMode::LowPower,
+                AccelOdr::Hz1_6 | AccelOdr::Hz12_5 | AccelOdr::Hz26 | AccelOdr::Hz52,
+            ) => Ok(()),
+            (PowerMode::LowPower, _) => {
+                Err("low-power mode requires an accelerometer ODR of at most 52 Hz")
+            }
+            (PowerMode::Normal, AccelOdr::Hz104 | AccelOdr::Hz208) => Ok(()),
+            (PowerMode::Normal, _) => {
+                Err("normal mode requires an accelerometer ODR of 104 or 208 Hz")
+            }
+            (PowerMode::HighPerformance, AccelOdr::Hz1_6) => {
+                Err("a 1.6 Hz accelerometer ODR requires low-power mode")
+            }
+            (PowerMode::HighPerformance, _) => Ok(()),
+        }
+    }
+
+    /// The value of the `CTRL1_XL` register for this configuration.
+    pub(crate) fn ctrl1_xl(&self, variant: crate::lsm6dsl::Variant) -> u8 {
+        let filter = match (variant, self.filter) {
+            // Selects the 400hz analog anti-aliasing filter on the
+            // LSM6DSL, or the 50hz one on the LSM6DS3
+            (crate::lsm6dsl::Variant::Lsm6dsl | crate::lsm6dsl::Variant::Lsm6ds3, _) => 0b11,
+            // The LSM6DSO has no analog filter selection, and instead
+            // enables the low-pass filter here
+            (crate::lsm6dsl::Variant::Lsm6dso, AccelFilter::LowPass(_)) => 0b10,
+            (crate::lsm6dsl::Variant::Lsm6dso, _) => 0b00,
+        };
+        (self.odr as u8) << 4 | (self.full_scale as u8) << 2 | filter
+    }
+
+    /// The value of the `CTRL8_XL` register for this configuration.
+    pub(crate) fn ctrl8_xl(&self, variant: crate::lsm6dsl::Variant) -> u8 {
+        match variant {
+            crate::lsm6dsl::Variant::Lsm6dsl | crate::lsm6dsl::Variant::Lsm6ds3 => {
+                match self.filter {
+                    AccelFilter::Bypass => 0,
+                    // LPF2_XL_EN, with the ODR/4 composite filter input
+                    AccelFilter::LowPass(bandwidth) => {
+                        let hpcf = match bandwidth {
+                            LowPassBandwidth::Odr50 => 0b00,
+                            LowPassBandwidth::Odr100 => 0b01,
+                            LowPassBandwidth::Odr9 => 0b10,
+                            LowPassBandwidth::Odr400 => 0b11,
+                        };
+                        0b1000_0000 | hpcf << 5 | 0b1000
+                    }
+                    // HP_SLOPE_XL_EN
+                    AccelFilter::HighPass(cutoff) => {
+                        let hpcf = match cutoff {
+                            HighPassCutoff::Odr4 => 0b00,
+                            HighPassCutoff::Odr100 => 0b01,
+                            HighPassCutoff::Odr9 => 0b10,
+                            HighPassCutoff::Odr400 => 0b11,
+                        };
+                        hpcf << 5 | 0b100
+                    }
+                }
+            }
+            crate::lsm6dsl::Variant::Lsm6dso => match self.filter {
+                AccelFilter::Bypass => 0,
+                AccelFilter::LowPass(bandwidth) => {
+                    let hpcf = match bandwidth {
+                        LowPassBandwidth::Odr9 => 0b001,
+                        LowPassBandwidth::Odr50 => 0b011,
+                        LowPassBandwidth::Odr100 => 0b100,
+                        LowPassBandwidth::Odr400 => 0b110,
+                    };
+                    hpcf << 5
+                }
+                // HP_SLOPE_XL_EN
+                AccelFilter::HighPass(cutoff) => {
+                    let hpcf = match cutoff {
+                        HighPassCutoff::Odr4 => 0b000,
+                        HighPassCutoff::Odr9 => 0b001,
+                        HighPassCutoff::Odr100 => 0b100,
+                        HighPassCutoff::Odr400 => 0b110,
+                    };
+                    hpcf << 5 | 0b100
+                }
+            },
+        }
+    }
+
+    /// The value of the `CTRL3_C` register for this configuration.
+    pub(crate) fn ctrl3_c(&self) -> u8 {
+        // Always increment during multi byte read
+        (self.bdu as u8) << 6 | 0b100 | self.endianness.ctrl3_c()
+    }
+
+    /// The value of the `CTRL6_C` register for this configuration.
+    pub(crate) fn ctrl6_c(&self) -> u8 {
+        // XL_HM_MODE disables high-performance mode, leaving the ODR to
+        // select between low-power and normal mode
+        ((self.power_mode != PowerMode::HighPerformance) as u8) << 4
+    }
+
+    /// Decodes the configuration from the values of its registers.
+    /// `power_down_on_drop` isn't stored on the chip, so it's passed in.
+    pub(crate) fn decode<E>(
+        variant: crate::lsm6dsl::Variant,
+        [ctrl1_xl, ctrl3_c, ctrl6_c, ctrl8_xl]: [u8; 4],
+        power_down_on_drop: bool,
+    ) -> Result<Self, crate::Error<E>> {
+        let unknown = |reg, value| crate::Error::UnknownRegisterValue { reg, value };
+        let xl_hm_mode = ctrl6_c & 0b1_0000 != 0;
+        let odr = AccelOdr::from_bits(ctrl1_xl >> 4, xl_hm_mode)
+            .ok_or_else(|| unknown(crate::LSM6DSL_CTRL1_XL, ctrl1_xl))?;
+        let power_mode = match (xl_hm_mode, odr) {
+            (false, _) => PowerMode::HighPerformance,
+            (true, AccelOdr::Hz104 | AccelOdr::Hz208) => PowerMode::Normal,
+            // The chip runs in high-performance mode above 208 Hz regardless
+            (true, AccelOdr::Hz416 | AccelOdr::Hz833)
+            | (true, AccelOdr::Hz1660 | AccelOdr::Hz3330 | AccelOdr::Hz6660) => {
+                PowerMode::HighPerformance
+            }
+            (true, _) => PowerMode::LowPower,
+        };
+        let filter = match variant {
+            crate::lsm6dsl::Variant::Lsm6dsl | crate::lsm6dsl::Variant::Lsm6ds3 => {
+                let hpcf = (ctrl8_xl >> 5) & 0b11;
+                match ctrl8_xl & !0b0110_0000 {
+                    0 if hpcf == 0 => AccelFilter::Bypass,
+                    0b1000_1000 => AccelFilter::LowPass(match hpcf {
+                        0b00 => LowPassBandwidth::Odr50,
+                        0b01 => LowPassBandwidth::Odr100,
+                        0b10 => LowPassBandwidth::Odr9,
+                        _ => LowPassBandwidth::Odr400,
+                    }),
+                    0b100 => AccelFilter::HighPass(match hpcf {
+                        0b00 => HighPassCutoff::Odr4,
+                        0b01 => HighPassCutoff::Odr100,
+                        0b10 => HighPassCutoff::Odr9,
+                        _ => HighPassCutoff::Odr400,
+                    }),
+                    _ => return Err(unknown(crate::LSM6DSL_CTRL8_XL, ctrl8_xl)),
+                }
+            }
+            crate::lsm6dsl::Variant::Lsm6dso => {
+                let hpcf = ctrl8_xl >> 5;
+                let filter = match (ctrl1_xl & 0b10 != 0, ctrl8_xl & 0b100 != 0, hpcf) {
+                    (false, false, 0) => Some(AccelFilter::Bypass),
+                    (true, false, 0b001) => Some(AccelFilter::LowPass(LowPassBandwidth::Odr9)),
+                    (true, false, 0b011) => Some(AccelFilter::LowPass(LowPassBandwidth::Odr50)),
+                    (true, false, 0b100) => Some(AccelFilter::LowPass(LowPassBandwidth::Odr100)),
+                    (true, false, 0b110) => Some(AccelFilter::LowPass(LowPassBandwidth::Odr400)),
+                    (false, true, 0b000) => Some(AccelFilter::HighPass(HighPassCutoff::Odr4)),
+                    (false, true, 0b001) => Some(AccelFilter::HighPass(HighPassCutoff::Odr9)),
+                    (false, true, 0b100) => Some(AccelFilter::HighPass(HighPassCutoff::Odr100)),
+                    (false, true, 0b110) => Some(AccelFilter::HighPass(HighPassCutoff::Odr400)),
+                    _ => None,
+                };
+                filter.ok_or_else(|| unknown(crate::LSM6DSL_CTRL8_XL, ctrl8_xl))?
+            }
+        };
+        Ok(Self {
+            odr,
+            full_scale: AccelFullScale::from_bits(ctrl1_xl >> 2),
+            bdu: ctrl3_c & 0b100_0000 != 0,
+            power_mode,
+            filter,
+            endianness: Endianness::from_ctrl3_c(ctrl3_c),
+            power_down_on_drop,
+        })
+    }
+}
+
+impl Default for AccelConfig {
+    /// ODR 3.33 kHz, +/- 8g, block data update enabled, high performance,
+    /// ODR/9 low-pass filter, little-endian
+    fn default() -> Self {
+        Self {
+            odr: AccelOdr::Hz3330,
+            full_scale: AccelFullScale::G8,
+            bdu: true,
+            power_mode: PowerMode::HighPerformance,
+            filter: AccelFilter::LowPass(LowPassBandwidth::Odr9),
+            endianness: Endianness::Little,
+            power_down_on_drop: false,
+        }
+    }
+}
+
+/// A builder for `AccelConfig`. Settings that aren't specified keep their
+/// defaults. Invalid combinations are rejected with `Error::InvalidConfig`
+/// when the configuration is applied.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AccelBuilder(AccelConfig);
+
+impl AccelBuilder {
+    /// Sets the output data rate.
+    pub fn odr(mut self, odr: AccelOdr) -> Self {
+        self.0.odr = odr;
+        self
+    }
+
+    /// Sets the full-scale range.
+    pub fn full_scale(mut self, full_scale: AccelFullScale) -> Self {
+        self.0.full_scale = full_scale;
+        self
+    }
+
+    /// Sets whether block data update is enabled.
+    pub fn bdu(mut self, bdu: bool) -> Self {
+        self.0.bdu = bdu;
+        self
+    }
+
+    /// Sets the power mode.
+    pub fn power_mode(mut self, power_mode: PowerMode) -> Self {
+        self.0.power_mode = power_mode;
+        self
+    }
+
+    /// Sets the digital filtering.
+    pub fn filter(mut self, filter: AccelFilter) -> Self {
+        self.0.filter = filter;
+        self
+    }
+
+    /// Sets the byte order of the output registers.
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.0.endianness = endianness;
+        self
+    }
+
+    /// Sets whether the sensor is powered down when its reader is dropped.
+    pub fn power_down_on_drop(mut self, power_down_on_drop: bool) -> Self {
+        self.0.power_down_on_drop = power_down_on_drop;
+        self
+    }
+
+    /// Builds the configuration.
+    pub fn build(self) -> AccelConfig {
+        self.0
+    }
+}
+
+/// Gyroscope output data rate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GyroOdr {
+    PowerDown = 0b0000,
+    Hz12_5 = 0b0001,
+    Hz26 = 0b0010,
+    Hz52 = 0b0011,
+    Hz104 = 0b0100,
+    Hz208 = 0b0101,
+    Hz416 = 0b0110,
+    Hz833 = 0b0111,
+    Hz1660 = 0b1000,
+    Hz3330 = 0b1001,
+    Hz6660 = 0b1010,
+}
+
+impl GyroOdr {
+    /// Decodes the ODR_G bits of `CTRL2_G`.
+    fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0b0000 => Some(GyroOdr::PowerDown),
+            0b0001 => Some(GyroOdr::Hz12_5),
+            0b0010 => Some(GyroOdr::Hz26),
+            0b0011 => Some(GyroOdr::Hz52),
+            0b0100 => Some(GyroOdr::Hz104),
+            0b0101 => Some(GyroOdr::Hz208),
+            0b0110 => Some(GyroOdr::Hz416),
+            0b0111 => Some(GyroOdr::Hz833),
+            0b1000 => Some(GyroOdr::Hz1660),
+            0b1001 => Some(GyroOdr::Hz3330),
+            0b1010 => Some(GyroOdr::Hz6660),
+            _ => None,
+        }
+    }
+}
+
+/// Gyroscope full-scale range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GyroFullScale {
+    /// +/- 125 dps
+    Dps125 = 0b001,
+    /// +/- 250 dps
+    Dps250 = 0b000,
+    /// +/- 500 dps
+    Dps500 = 0b010,
+    /// +/- 1000 dps
+    Dps1000 = 0b100,
+    /// +/- 2000 dps
+    Dps2000 = 0b110,
+}
+
+impl GyroFullScale {
+    /// The conversion factor from raw readings to degrees per second, in
+    /// dps/LSB.
+    pub fn degrees_per_lsb(self) -> f32 {
+        match self {
+            GyroFullScale::Dps125 => 0.004375,
+            GyroFullScale::Dps250 => 0.00875,
+            GyroFullScale::Dps500 => 0.0175,
+            GyroFullScale::Dps1000 => 0.035,
+            GyroFullScale::Dps2000 => 0.070,
+        }
+    }
+
+    /// Decodes the FS_G and FS_125 bits of `CTRL2_G`.
+    fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0b001 => Some(GyroFullScale::Dps125),
+            0b000 => Some(GyroFullScale::Dps250),
+            0b010 => Some(GyroFullScale::Dps500),
+            0b100 => Some(GyroFullScale::Dps1000),
+            0b110 => Some(GyroFullScale::Dps2000),
+            _ => None,
+        }
+    }
+}
+
+/// The cutoff of the gyroscope's high-pass filter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GyroHighPass {
+    /// 16 mHz
+    Hz0_016 = 0b00,
+    /// 65 mHz
+    Hz0_065 = 0b01,
+    /// 260 mHz
+    Hz0_26 = 0b10,
+    /// 1.04 Hz
+    Hz1_04 = 0b11,
+}
+
+impl GyroHighPass {
+    /// Decodes the HPM_G bits of `CTRL7_G`.
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => GyroHighPass::Hz0_016,
+            0b01 => GyroHighPass::Hz0_065,
+            0b10 => GyroHighPass::Hz0_26,
+            _ => GyroHighPass::Hz1_04,
+        }
+    }
+}
+
+/// The bandwidth of the gyroscope's digital low-pass filter, LPF1, which is
+/// set by the FTYPE bits of `CTRL6_C`. The bandwidth itself also depends on
+/// the output data rate; see the datasheet for the exact values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GyroLpf1Bandwidth {
+    Widest = 0b11,
+    /// The power-on default.
+    Wide = 0b00,
+    Narrow = 0b01,
+    Narrowest = 0b10,
+}
+
+impl GyroLpf1Bandwidth {
+    /// Decodes the lower two FTYPE bits of `CTRL6_C`.
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b11 => GyroLpf1Bandwidth::Widest,
+            0b00 => GyroLpf1Bandwidth::Wide,
+            0b01 => GyroLpf1Bandwidth::Narrow,
+            _ => GyroLpf1Bandwidth::Narrowest,
+        }
+    }
+}
+
+/// Gyroscope power mode. The low-power and normal modes are each only
+/// available at particular output data rates, and draw less current than
+/// high-performance mode at the cost of more noise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GyroPowerMode {
+    HighPerformance,
+    /// Available at 104 and 208 Hz.
+    Normal,
+    /// Available at up to 52 Hz.
+    LowPower,
+}
+
+/// Gyroscope configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GyroConfig {
+    pub odr: GyroOdr,
+    pub full_scale: GyroFullScale,
+    pub power_mode: GyroPowerMode,
+    /// The high-pass filter's cutoff, or `None` to disable it.
+    pub high_pass: Option<GyroHighPass>,
+    /// The low-pass filter's bandwidth, or `None` to disable it. The LSM6DS3
+    /// has no low-pass filter, so it rejects anything else.
+    pub low_pass: Option<GyroLpf1Bandwidth>,
+    /// The byte order of the output registers, which has to match the
+    /// accelerometer's once it's powered, since they share a bit. Readers fail with
+    /// `Error::InvalidConfig` otherwise.
+    pub endianness: Endianness,
+    /// Whether the gyroscope is powered down when its reader is dropped, e.g.
+    /// so it stops drawing current once the program exits.
+    pub power_down_on_drop: bool,
+}
+
+impl GyroConfig {
+    /// Creates a builder, starting from the default configuration.
+    pub fn builder() -> GyroBuilder {
+        GyroBuilder::default()
+    }
+
+    /// Checks that this configuration can be applied.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        match (self.power_mode, self.odr) {
+            (_, GyroOdr::PowerDown) | (GyroPowerMode::HighPerformance, _) => Ok(()),
+            (GyroPowerMode::LowPower, GyroOdr::Hz12_5 | GyroOdr::Hz26 | GyroOdr::Hz52) => Ok(()),
+            (GyroPowerMode::LowPower, _) => {
+                Err("low-power mode requires a gyroscope ODR of at most 52 Hz")
+            }
+            (GyroPowerMode::Normal, GyroOdr::Hz104 | GyroOdr::Hz208) => Ok(()),
+            (GyroPowerMode::Normal, _) => {
+                Err("normal mode requires a gyroscope ODR of 104 or 208 Hz")
+            }
+        }
+    }
+
+    /// The value of the `CTRL2_G` register for this configuration.
+    pub(crate) fn ctrl2_g(&self) -> u8 {
+        // The full-scale discriminant covers both FS_G and FS_125
+        (self.odr as u8) << 4 | (self.full_scale as u8) << 1
+    }
+
+    /// The value of the `CTRL7_G` register for this configuration.
+    pub(crate) fn ctrl7_g(&self) -> u8 {
+        // G_HM_MODE disables high-performance mode, leaving the ODR to
+        // select between low-power and normal mode
+        let g_hm_mode = ((self.power_mode != GyroPowerMode::HighPerformance) as u8) << 7;
+        // HP_EN_G and HPM_G
+        let high_pass = match self.high_pass {
+            Some(cutoff) => 0b0100_0000 | (cutoff as u8) << 4,
+            None => 0,
+        };
+        g_hm_mode | high_pass
+    }
+
+    /// The gyroscope's LPF1_SEL_G bit of `CTRL4_C`, which is otherwise the
+    /// accelerometer's.
+    pub(crate) fn ctrl4_c(&self) -> u8 {
+        (self.low_pass.is_some() as u8) << 1
+    }
+
+    /// The gyroscope's FTYPE bits of `CTRL6_C`, which is otherwise the
+    /// accelerometer's. They only take effect with LPF1_SEL_G set.
+    pub(crate) fn ctrl6_c(&self) -> u8 {
+        self.low_pass.map_or(0, |bandwidth| bandwidth as u8)
+    }
+
+    /// Decodes the configuration from the values of its registers.
+    /// `power_down_on_drop` isn't stored on the chip, so it's passed in.
+    pub(crate) fn decode<E>(
+        variant: crate::lsm6dsl::Variant,
+        [ctrl2_g, ctrl3_c, ctrl4_c, ctrl6_c, ctrl7_g]: [u8; 5],
+        power_down_on_drop: bool,
+    ) -> Result<Self, crate::Error<E>> {
+        let unknown = || crate::Error::UnknownRegisterValue {
+            reg: crate::LSM6DSL_CTRL2_G,
+            value: ctrl2_g,
+        };
+        let odr = GyroOdr::from_bits(ctrl2_g >> 4).ok_or_else(unknown)?;
+        let full_scale = GyroFullScale::from_bits((ctrl2_g >> 1) & 0b111).ok_or_else(unknown)?;
+        let power_mode = match (ctrl7_g & 0b1000_0000 != 0, odr) {
+            (false, _) => GyroPowerMode::HighPerformance,
+            (true, GyroOdr::Hz104 | GyroOdr::Hz208) => GyroPowerMode::Normal,
+            // The chip runs in high-performance mode above 208 Hz regardless
+            (true, GyroOdr::Hz416 | GyroOdr::Hz833)
+            | (true, GyroOdr::Hz1660 | GyroOdr::Hz3330 | GyroOdr::Hz6660) => {
+                GyroPowerMode::HighPerformance
+            }
+            (true, _) => GyroPowerMode::LowPower,
+        };
+        let high_pass = (ctrl7_g & 0b0100_0000 != 0).then(|| GyroHighPass::from_bits(ctrl7_g >> 4));
+        let low_pass = match variant {
+            // The LSM6DS3 has no LPF1_SEL_G or FTYPE
+            crate::lsm6dsl::Variant::Lsm6ds3 => None,
+            _ if ctrl4_c & 0b10 == 0 => None,
+            // The LSM6DSO's FTYPE has a third bit, which is only ever cleared
+            crate::lsm6dsl::Variant::Lsm6dso if ctrl6_c & 0b100 != 0 => {
+                return Err(crate::Error::UnknownRegisterValue {
+                    reg: crate::LSM6DSL_CTRL6_C,
+                    value: ctrl6_c,
+                })
+            }
+            _ => Some(GyroLpf1Bandwidth::from_bits(ctrl6_c)),
+        };
+        Ok(Self {
+            odr,
+            full_scale,
+            power_mode,
+            high_pass,
+            low_pass,
+            endianness: Endianness::from_ctrl3_c(ctrl3_c),
+            power_down_on_drop,
+        })
+    }
+}
+
+impl Default for GyroConfig {
+    /// ODR 3.33 kHz, 2000 dps, high performance, little-endian
+    fn default() -> Self {
+        Self {
+            odr: GyroOdr::Hz3330,
+            full_scale: GyroFullScale::Dps2000,
+            power_mode: GyroPowerMode::HighPerformance,
+            high_pass: None,
+            low_pass: None,
+            endianness: Endianness::Little,
+            power_down_on_drop: false,
+        }
+    }
+}
+
+/// A builder for `GyroConfig`. Settings that aren't specified keep their
+/// defaults. Invalid combinations are rejected with `Error::InvalidConfig`
+/// when the configuration is applied.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GyroBuilder(GyroConfig);
+
+impl GyroBuilder {
+    /// Sets the output data rate.
+    pub fn odr(mut self, odr: GyroOdr) -> Self {
+        self.0.odr = odr;
+        self
+    }
+
+    /// Sets the full-scale range.
+    pub fn full_scale(mut self, full_scale: GyroFullScale) -> Self {
+        self.0.full_scale = full_scale;
+        self
+    }
+
+    /// Sets the power mode.
+    pub fn power_mode(mut self, power_mode: GyroPowerMode) -> Self {
+        self.0.power_mode = power_mode;
+        self
+    }
+
+    /// Enables the high-pass filter.
+    pub fn high_pass(mut self, cutoff: GyroHighPass) -> Self {
+        self.0.high_pass = Some(cutoff);
+        self
+    }
+
+    /// Enables the low-pass filter.
+    pub fn low_pass(mut self, bandwidth: GyroLpf1Bandwidth) -> Self {
+        self.0.low_pass = Some(bandwidth);
+        self
+    }
+
+    /// Sets the byte order of the output registers.
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.0.endianness = endianness;
+        self
+    }
+
+    /// Sets whether the sensor is powered down when its reader is dropped.
+    pub fn power_down_on_drop(mut self, power_down_on_drop: bool) -> Self {
+        self.0.power_down_on_drop = power_down_on_drop;
+        self
+    }
+
+    /// Builds the configuration.
+    pub fn build(self) -> GyroConfig {
+        self.0
+    }
+}
+
+/// Magnetometer output data rate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MagOdr {
+    Hz0_625,
+    Hz1_25,
+    Hz2_5,
+    Hz5,
+    Hz10,
+    Hz20,
+    Hz40,
+    Hz80,
+    /// FAST_ODR in ultra-high-performance mode.
+    Hz155,
+    /// FAST_ODR in high-performance mode.
+    Hz300,
+    /// FAST_ODR in medium-performance mode.
+    Hz560,
+    /// FAST_ODR in low-power mode.
+    Hz1000,
+}
+
+impl MagOdr {
+    /// The DO and FAST_ODR bits of `CTRL_REG1`, alongside the performance
+    /// mode required by FAST_ODR rates.
+    fn bits(self) -> (u8, bool, Option<MagPerformance>) {
+        match self {
+            MagOdr::Hz0_625 => (0b000, false, None),
+            MagOdr::Hz1_25 => (0b001, false, None),
+            MagOdr::Hz2_5 => (0b010, false, None),
+            MagOdr::Hz5 => (0b011, false, None),
+            MagOdr::Hz10 => (0b100, false, None),
+            MagOdr::Hz20 => (0b101, false, None),
+            MagOdr::Hz40 => (0b110, false, None),
+            MagOdr::Hz80 => (0b111, false, None),
+            MagOdr::Hz155 => (0b000, true, Some(MagPerformance::UltraHigh)),
+            MagOdr::Hz300 => (0b000, true, Some(MagPerformance::High)),
+            MagOdr::Hz560 => (0b000, true, Some(MagPerformance::Medium)),
+            MagOdr::Hz1000 => (0b000, true, Some(MagPerformance::LowPower)),
+        }
+    }
+
+    /// Decodes the DO and FAST_ODR bits of `CTRL_REG1`, alongside the
+    /// performance mode that selects between FAST_ODR rates.
+    fn from_bits(odr: u8, fast_odr: bool, performance: MagPerformance) -> Self {
+        match (fast_odr, performance, odr & 0b111) {
+            (true, MagPerformance::UltraHigh, _) => MagOdr::Hz155,
+            (true, MagPerformance::High, _) => MagOdr::Hz300,
+            (true, MagPerformance::Medium, _) => MagOdr::Hz560,
+            (true, MagPerformance::LowPower, _) => MagOdr::Hz1000,
+            (false, _, 0b000) => MagOdr::Hz0_625,
+            (false, _, 0b001) => MagOdr::Hz1_25,
+            (false, _, 0b010) => MagOdr::Hz2_5,
+            (false, _, 0b011) => MagOdr::Hz5,
+            (false, _, 0b100) => MagOdr::Hz10,
+            (false, _, 0b101) => MagOdr::Hz20,
+            (false, _, 0b110) => MagOdr::Hz40,
+            (false, _, _) => MagOdr::Hz80,
+        }
+    }
+}
+
+/// Magnetometer X and Y axis operating mode, which trades noise against
+/// current draw.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MagPerformance {
+    LowPower = 0b00,
+    Medium = 0b01,
+    High = 0b10,
+    UltraHigh = 0b11,
+}
+
+impl MagPerformance {
+    /// Decodes the two bits of an operating mode.
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => MagPerformance::LowPower,
+            0b01 => MagPerformance::Medium,
+            0b10 => MagPerformance::High,
+            _ => MagPerformance::UltraHigh,
+        }
+    }
+}
+
+/// Magnetometer operating mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MagMode {
+    /// Samples are measured continuously, at the output data rate.
+    Continuous = 0b00,
+    /// A single sample is measured, after which the magnetometer powers
+    /// down.
+    Single = 0b01,
+    PowerDown = 0b11,
+}
+
+/// Magnetometer full-scale range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MagFullScale {
+    /// +/- 4 gauss
+    Gauss4 = 0b00,
+    /// +/- 8 gauss
+    Gauss8 = 0b01,
+    /// +/- 12 gauss
+    Gauss12 = 0b10,
+    /// +/- 16 gauss
+    Gauss16 = 0b11,
+}
+
+impl MagFullScale {
+    /// The conversion factor from raw readings to gauss, in gauss/LSB.
+    pub fn gauss_per_lsb(self) -> f32 {
+        match self {
+            MagFullScale::Gauss4 => 1.0 / 6842.0,
+            MagFullScale::Gauss8 => 1.0 / 3421.0,
+            MagFullScale::Gauss12 => 1.0 / 2281.0,
+            MagFullScale::Gauss16 => 1.0 / 1711.0,
+        }
+    }
+
+    /// Decodes the FS bits of `CTRL_REG2`.
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => MagFullScale::Gauss4,
+            0b01 => MagFullScale::Gauss8,
+            0b10 => MagFullScale::Gauss12,
+            _ => MagFullScale::Gauss16,
+        }
+    }
+}
+
+/// Magnetometer configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MagConfig {
+    pub odr: MagOdr,
+    pub full_scale: MagFullScale,
+    /// The operating mode of the X and Y axes. FAST_ODR rates each require
+    /// a particular mode.
+    pub performance: MagPerformance,
+    /// The operating mode of the Z axis, which normally matches the X and Y
+    /// axes'.
+    pub z_performance: MagPerformance,
+    /// The operating mode it starts in. Reads fail with `Error::PoweredDown`
+    /// unless it's continuous, but single conversions can still be taken.
+    pub mode: MagMode,
+    /// Whether block data update is enabled, so that the output registers
+    /// aren't updated until both the low and high bytes have been read.
+    pub bdu: bool,
+    /// Whether the magnetometer is powered down when its reader is dropped, e.g.
+    /// so it stops drawing current once the program exits.
+    pub power_down_on_drop: bool,
+}
+
+impl MagConfig {
+    /// Creates a builder, starting from the default configuration.
+    pub fn builder() -> MagBuilder {
+        MagBuilder::default()
+    }
+
+    /// Checks that this configuration can be applied.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        match self.odr.bits() {
+            (_, _, Some(performance)) if performance != self.performance => {
+                Err("the magnetometer ODR requires a different performance mode")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The value of the `CTRL_REG1` register for this configuration.
+    pub(crate) fn ctrl_reg1(&self) -> u8 {
+        let (odr, fast_odr, _) = self.odr.bits();
+        // Temp sensor enabled, self test disabled
+        0b1000_0000 | (self.performance as u8) << 5 | odr << 2 | (fast_odr as u8) << 1
+    }
+
+    /// The value of the `CTRL_REG2` register for this configuration.
+    pub(crate) fn ctrl_reg2(&self) -> u8 {
+        (self.full_scale as u8) << 5
+    }
+
+    /// The value of the `CTRL_REG3` register for this configuration.
+    pub(crate) fn ctrl_reg3(&self) -> u8 {
+        // The MD bits, with low-power mode and 3-wire SPI disabled
+        self.mode as u8
+    }
+
+    /// The value of the `CTRL_REG4` register for this configuration.
+    pub(crate) fn ctrl_reg4(&self) -> u8 {
+        // Little endian output
+        (self.z_performance as u8) << 2
+    }
+
+    /// The value of the `CTRL_REG5` register for this configuration.
+    pub(crate) fn ctrl_reg5(&self) -> u8 {
+        (self.bdu as u8) << 6
+    }
+
+    /// Decodes the configuration from the values of its registers.
+    /// `power_down_on_drop` isn't stored on the chip, so it's passed in.
+    pub(crate) fn decode<E>(
+        [ctrl_reg1, ctrl_reg2, ctrl_reg3, ctrl_reg4, ctrl_reg5]: [u8; 5],
+        power_down_on_drop: bool,
+    ) -> Result<Self, crate::Error<E>> {
+        // The bits that have to be cleared, because they're reserved or no
+        // configuration sets them: ST, the reserved bits of CTRL_REG2, LP,
+        // BLE and FAST_READ. REBOOT and SOFT_RST clear themselves, and SIM
+        // is the bus's concern.
+        for (reg, value, mask) in [
+            (crate::LIS3MDL_CTRL_REG1, ctrl_reg1, 0b0000_0001),
+            (crate::LIS3MDL_CTRL_REG2, ctrl_reg2, 0b1001_0011),
+            (crate::LIS3MDL_CTRL_REG3, ctrl_reg3, 0b1111_1000),
+            (crate::LIS3MDL_CTRL_REG4, ctrl_reg4, 0b1111_0011),
+            (crate::LIS3MDL_CTRL_REG5, ctrl_reg5, 0b1011_1111),
+        ] {
+            if value & mask != 0 {
+                return Err(crate::Error::UnknownRegisterValue { reg, value });
+            }
+        }
+        let performance = MagPerformance::from_bits(ctrl_reg1 >> 5);
+        let mode = match ctrl_reg3 & 0b11 {
+            0b00 => MagMode::Continuous,
+            0b01 => MagMode::Single,
+            _ => MagMode::PowerDown,
+        };
+        Ok(Self {
+            odr: MagOdr::from_bits(ctrl_reg1 >> 2, ctrl_reg1 & 0b10 != 0, performance),
+            full_scale: MagFullScale::from_bits(ctrl_reg2 >> 5),
+            performance,
+            z_performance: MagPerformance::from_bits(ctrl_reg4 >> 2),
+            mode,
+            bdu: ctrl_reg5 & 0b100_0000 != 0,
+            power_down_on_drop,
+        })
+    }
+}
+
+impl Default for MagConfig {
+    /// ODR 80 Hz, +/- 8 gauss, high performance, continuous conversion,
+    /// block data update enabled
+    fn default() -> Self {
+        Self {
+            odr: MagOdr::Hz80,
+            full_scale: MagFullScale::Gauss8,
+            performance: MagPerformance::High,
+            z_performance: MagPerformance::High,
+            mode: MagMode::Continuous,
+            bdu: true,
+            power_down_on_drop: false,
+        }
+    }
+}
+
+/// A builder for `MagConfig`. Settings that aren't specified keep their
+/// defaults. Invalid combinations are rejected with `Error::InvalidConfig`
+/// when the configuration is applied.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MagBuilder(MagConfig);
+
+impl MagBuilder {
+    /// Sets the output data rate. FAST_ODR rates also set the performance
+    /// mode they require.
+    pub fn odr(mut self, odr: MagOdr) -> Self {
+        self.0.odr = odr;
+        if let (_, _, Some(performance)) = odr.bits() {
+            self = self.performance(performance);
+        }
+        self
+    }
+
+    /// Sets the operating mode of every axis.
+    pub fn performance(mut self, performance: MagPerformance) -> Self {
+        self.0.performance = performance;
+        self.0.z_performance = performance;
+        self
+    }
+
+    /// Sets the operating mode of the Z axis alone, e.g. to trade its noise
+    /// against current draw separately from the X and Y axes.
+    pub fn z_axis_performance(mut self, performance: MagPerformance) -> Self {
+        self.0.z_performance = performance;
+        self
+    }
+
+    /// Sets the full-scale range.
+    pub fn full_scale(mut self, full_scale: MagFullScale) -> Self {
+        self.0.full_scale = full_scale;
+        self
+    }
+
+    /// Sets the operating mode the magnetometer starts in.
+    pub fn mode(mut self, mode: MagMode) -> Self {
+        self.0.mode = mode;
+        self
+    }
+
+    /// Sets whether block data update is enabled.
+    pub fn bdu(mut self, bdu: bool) -> Self {
+        self.0.bdu = bdu;
+        self
+    }
+
+    /// Sets whether the sensor is powered down when its reader is dropped.
+    pub fn power_down_on_drop(mut self, power_down_on_drop: bool) -> Self {
+        self.0.power_down_on_drop = power_down_on_drop;
+        self
+    }
+
+    /// Builds the configuration.
+    pub fn build(self) -> MagConfig {
+        self.0
+    }
+}
+
+/// A preset combination of the accelerometer, gyroscope and magnetometer
+/// configurations, trading noise and bandwidth against current draw. The
+/// other settings keep their defaults.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// Accelerometer at 26 Hz and gyroscope at 52 Hz, both in low-power
+    /// mode, and magnetometer at 10 Hz in low-power mode.
+    LowPower,
+    /// Accelerometer and gyroscope at 104 Hz in normal mode, and
+    /// magnetometer at 20 Hz in medium-performance mode.
+    Balanced,
+    /// The default configurations, i.e. accelerometer and gyroscope at 3.33
+    /// kHz in high-performance mode, and magnetometer at 80 Hz in
+    /// high-performance mode.
+    HighPerformance,
+}
+
+impl Profile {
+    /// The accelerometer configuration for this profile.
+    pub fn accel_config(self) -> AccelConfig {
+        match self {
+            Profile::LowPower => AccelConfig::builder()
+                .odr(AccelOdr::Hz26)
+                .power_mode(PowerMode::LowPower)
+                .build(),
+            Profile::Balanced => AccelConfig::builder()
+                .odr(AccelOdr::Hz104)
+                .power_mode(PowerMode::Normal)
+                .build(),
+            Profile::HighPerformance => AccelConfig::default(),
+        }
+    }
+
+    /// The gyroscope configuration for this profile.
+    pub fn gyro_config(self) -> GyroConfig {
+        match self {
+            Profile::LowPower => GyroConfig::builder()
+                .odr(GyroOdr::Hz52)
+                .power_mode(GyroPowerMode::LowPower)
+                .build(),
+            Profile::Balanced => GyroConfig::builder()
+                .odr(GyroOdr::Hz104)
+                .power_mode(GyroPowerMode::Normal)
+                .build(),
+            Profile::HighPerformance => GyroConfig::default(),
+        }
+    }
+
+    /// The magnetometer configuration for this profile.
+    pub fn mag_config(self) -> MagConfig {
+        match self {
+            Profile::LowPower => MagConfig::builder()
+                .odr(MagOdr::Hz10)
+                .performance(MagPerformance::LowPower)
+                .build(),
+            Profile::Balanced => MagConfig::builder()
+                .odr(MagOdr::Hz20)
+                .performance(MagPerformance::Medium)
+                .build(),
+            Profile::HighPerformance => MagConfig::default(),
+        }
+    }
+}
+
+/// FIFO mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FifoMode {
+    /// The FIFO is disabled and emptied.
+    Bypass = 0b000,
+    /// Samples are stored until the FIFO is full, then storing stops.
+    Fifo = 0b001,
+    /// Continuous mode until a trigger event, then FIFO mode.
+    ContinuousToFifo = 0b011,
+    /// Bypass mode until a trigger event, then continuous mode.
+    BypassToContinuous = 0b100,
+    /// Samples are stored continuously, overwriting the oldest once the
+    /// FIFO is full.
+    Continuous = 0b110,
+}
+
+/// An interrupt pin of the LSM6DSL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntPin {
+    Int1,
+    Int2,
+}
+
+/// How a sensor's samples are decimated as they're stored in the FIFO.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FifoDecimation {
+    /// The sensor's samples aren't stored.
+    Disabled = 0b000,
+    /// Every sample is stored.
+    None = 0b001,
+    X2 = 0b010,
+    X3 = 0b011,
+    X4 = 0b100,
+    X8 = 0b101,
+    X16 = 0b110,
+    X32 = 0b111,
+}
+
+/// FIFO configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FifoConfig {
+    /// The FIFO threshold, in 16-bit words, i.e. a third of an axis triple.
+    /// At most 2047.
+    pub watermark: u16,
+    pub accel_decimation: FifoDecimation,
+    pub gyro_decimation: FifoDecimation,
+    /// The rate samples are stored at, which shouldn't exceed the sensors'
+    /// own.
+    pub odr: AccelOdr,
+    pub mode: FifoMode,
+}
+
+impl FifoConfig {
+    /// Checks that this configuration can be applied.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        if self.watermark > 0x07FF {
+            return Err("the FIFO watermark must be at most 2047");
+        }
+        if self.odr == AccelOdr::Hz1_6 {
+            return Err("a 1.6 Hz FIFO ODR isn't supported");
+        }
+        Ok(())
+    }
+
+    /// The value of the `FIFO_CTRL3` register for this configuration.
+    pub(crate) fn fifo_ctrl3(&self) -> u8 {
+        (self.gyro_decimation as u8) << 3 | self.accel_decimation as u8
+    }
+
+    /// The value of the `FIFO_CTRL5` register for this configuration.
+    pub(crate) fn fifo_ctrl5(&self) -> u8 {
+        (self.odr as u8) << 3 | self.mode as u8
+    }
+}
+
+impl Default for FifoConfig {
+    /// Bypass mode, accelerometer samples only, stored at 3.33 kHz
+    fn default() -> Self {
+        Self {
+            watermark: 0,
+            accel_decimation: FifoDecimation::None,
+            gyro_decimation: FifoDecimation::Disabled,
+            odr: AccelOdr::Hz3330,
+            mode: FifoMode::Bypass,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsm6dsl::Variant;
+
+    // The values of CTRL1_XL, CTRL3_C, CTRL6_C and CTRL8_XL, in the order
+    // `AccelConfig::decode` takes them
+    fn accel_registers(config: &AccelConfig, variant: Variant) -> [u8; 4] {
+        [
+            config.ctrl1_xl(variant),
+            config.ctrl3_c(),
+            config.ctrl6_c(),
+            config.ctrl8_xl(variant),
+        ]
+    }
+
+    // The values of CTRL2_G, CTRL3_C, CTRL4_C, CTRL6_C and CTRL7_G, in the
+    // order `GyroConfig::decode` takes them
+    fn gyro_registers(config: &GyroConfig) -> [u8; 5] {
+        [
+            config.ctrl2_g(),
+            config.endianness.ctrl3_c(),
+            config.ctrl4_c(),
+            config.ctrl6_c(),
+            config.ctrl7_g(),
+        ]
+    }
+
+    // The values of CTRL_REG1 to CTRL_REG5
+    fn mag_registers(config: &MagConfig) -> [u8; 5] {
+        [
+            config.ctrl_reg1(),
+            config.ctrl_reg2(),
+            config.ctrl_reg3(),
+            config.ctrl_reg4(),
+            config.ctrl_reg5(),
+        ]
+    }
+
+    #[test]
+    fn accel_default_bytes() {
+        let config = AccelConfig::default();
+        assert_eq!(
+            accel_registers(&config, Variant::Lsm6dsl),
+            [0x9F, 0x44, 0x00, 0xC8]
+        );
+        assert_eq!(
+            accel_registers(&config, Variant::Lsm6dso),
+            [0x9E, 0x44, 0x00, 0x20]
+        );
+    }
+
+    #[test]
+    fn accel_bytes_per_variant() {
+        let config = AccelConfig::builder()
+            .odr(AccelOdr::Hz52)
+            .full_scale(AccelFullScale::G4)
+            .power_mode(PowerMode::LowPower)
+            .filter(AccelFilter::HighPass(HighPassCutoff::Odr9))
+            .endianness(Endianness::Big)
+            .bdu(false)
+            .build();
+        assert_eq!(
+            accel_registers(&config, Variant::Lsm6dsl),
+            [0x3B, 0x06, 0x10, 0x44]
+        );
+        assert_eq!(
+            accel_registers(&config, Variant::Lsm6dso),
+            [0x38, 0x06, 0x10, 0x24]
+        );
+
+        let config = AccelConfig::builder()
+            .odr(AccelOdr::Hz104)
+            .power_mode(PowerMode::Normal)
+            .filter(AccelFilter::LowPass(LowPassBandwidth::Odr100))
+            .build();
+        assert_eq!(
+            accel_registers(&config, Variant::Lsm6dsl),
+            [0x4F, 0x44, 0x10, 0xA8]
+        );
+        assert_eq!(
+            accel_registers(&config, Variant::Lsm6dso),
+            [0x4E, 0x44, 0x10, 0x80]
+        );
+    }
+
+    #[test]
+    fn accel_bytes_round_trip() {
+        for variant in [Variant::Lsm6dsl, Variant::Lsm6dso, Variant::Lsm6ds3] {
+            for filter in [
+                AccelFilter::Bypass,
+                AccelFilter::LowPass(LowPassBandwidth::Odr9),
+                AccelFilter::LowPass(LowPassBandwidth::Odr50),
+                AccelFilter::LowPass(LowPassBandwidth::Odr100),
+                AccelFilter::LowPass(LowPassBandwidth::Odr400),
+                AccelFilter::HighPass(HighPassCutoff::Odr4),
+                AccelFilter::HighPass(HighPassCutoff::Odr9),
+                AccelFilter::HighPass(HighPassCutoff::Odr100),
+                AccelFilter::HighPass(HighPassCutoff::Odr400),
+            ] {
+                let config = AccelConfig::builder()
+                    .odr(AccelOdr::Hz1_6)
+                    .full_scale(AccelFullScale::G8)
+                    .power_mode(PowerMode::LowPower)
+                    .filter(filter)
+                    .build();
+                let bytes = accel_registers(&config, variant);
+                let decoded = AccelConfig::decode::<()>(variant, bytes, false).unwrap();
+                assert_eq!(decoded, config, "{:?} {:?}", variant, filter);
+            }
+        }
+    }
+
+    #[test]
+    fn gyro_bytes() {
+        let config = GyroConfig::default();
+        assert_eq!(gyro_registers(&config), [0x9C, 0, 0, 0, 0]);
+
+        let config = GyroConfig::builder()
+            .odr(GyroOdr::Hz104)
+            .full_scale(GyroFullScale::Dps500)
+            .power_mode(GyroPowerMode::Normal)
+            .high_pass(GyroHighPass::Hz0_065)
+            .low_pass(GyroLpf1Bandwidth::Narrow)
+            .endianness(Endianness::Big)
+            .build();
+        let bytes = gyro_registers(&config);
+        // G_HM_MODE, HP_EN_G and HPM_G in CTRL7_G, and LPF1_SEL_G with
+        // FTYPE
+        assert_eq!(bytes, [0x44, 0b10, 0b10, 0b01, 0xD0]);
+        let decoded = GyroConfig::decode::<()>(Variant::Lsm6dsl, bytes, false).unwrap();
+        assert_eq!(decoded, config);
+    }
+
+    #[test]
+    fn gyro_125_dps() {
+        // FS_125 overrides FS_G
+        let config = GyroConfig::builder()
+            .full_scale(GyroFullScale::Dps125)
+            .build();
+        assert_eq!(config.ctrl2_g(), 0x92);
+    }
+
+    #[test]
+    fn mag_bytes() {
+        let config = MagConfig::default();
+        assert_eq!(mag_registers(&config), [0xDC, 0x20, 0x00, 0x08, 0x40]);
+
+        // FAST_ODR at 155 Hz, which selects ultra-high-performance mode
+        let config = MagConfig::builder()
+            .odr(MagOdr::Hz155)
+            .z_axis_performance(MagPerformance::LowPower)
+            .full_scale(MagFullScale::Gauss16)
+            .mode(MagMode::Single)
+            .bdu(false)
+            .build();
+        let bytes = mag_registers(&config);
+        assert_eq!(bytes, [0xE2, 0x60, 0x01, 0x00, 0x00]);
+        assert_eq!(MagConfig::decode::<()>(bytes, false).unwrap(), config);
+    }
+
+    #[test]
+    fn fifo_bytes() {
+        let config = FifoConfig {
+            watermark: 100,
+            accel_decimation: FifoDecimation::X2,
+            gyro_decimation: FifoDecimation::None,
+            odr: AccelOdr::Hz104,
+            mode: FifoMode::Continuous,
+        };
+        assert_eq!(config.fifo_ctrl3(), 0x0A);
+        assert_eq!(config.fifo_ctrl5(), 0x26);
+    }
+
+    #[test]
+    fn profile_bytes() {
+        for (profile, accel, gyro, mag) in [
+            (
+                Profile::LowPower,
+                [0x2F, 0x44, 0x10, 0xC8],
+                [0x3C, 0, 0, 0, 0x80],
+                [0x90, 0x20, 0x00, 0x00, 0x40],
+            ),
+            (
+                Profile::Balanced,
+                [0x4F, 0x44, 0x10, 0xC8],
+                [0x4C, 0, 0, 0, 0x80],
+                [0xB4, 0x20, 0x00, 0x04, 0x40],
+            ),
+            (
+                Profile::HighPerformance,
+                [0x9F, 0x44, 0x00, 0xC8],
+                [0x9C, 0, 0, 0, 0],
+                [0xDC, 0x20, 0x00, 0x08, 0x40],
+            ),
+        ] {
+            let accel_config = profile.accel_config();
+            let gyro_config = profile.gyro_config();
+            let mag_config = profile.mag_config();
+            assert_eq!(accel_config.validate(), Ok(()), "{:?}", profile);
+            assert_eq!(gyro_config.validate(), Ok(()), "{:?}", profile);
+            assert_eq!(mag_config.validate(), Ok(()), "{:?}", profile);
+            let accel_bytes = accel_registers(&accel_config, Variant::Lsm6dsl);
+            assert_eq!(accel_bytes, accel, "{:?}", profile);
+            assert_eq!(gyro_registers(&gyro_config), gyro, "{:?}", profile);
+            assert_eq!(mag_registers(&mag_config), mag, "{:?}", profile);
+        }
+    }
+
+    #[test]
+    fn mag_decode_rejects_unknown_bits() {
+        let bytes = mag_registers(&MagConfig::default());
+        for (i, bit) in [
+            // ST, a reserved bit, LP, BLE and FAST_READ
+            (0, 0b0000_0001),
+            (1, 0b0000_0001),
+            (2, 0b0010_0000),
+            (3, 0b0000_0010),
+            (4, 0b1000_0000),
+        ] {
+            let mut bytes = bytes;
+            bytes[i] |= bit;
+            assert!(
+                matches!(
+                    MagConfig::decode::<()>(bytes, false),
+                    Err(crate::Error::UnknownRegisterValue { reg, value })
+                        if reg == crate::LIS3MDL_CTRL_REG1 + i as u8 && value == bytes[i]
+                ),
+                "{:?}",
+                bytes
+            );
+        }
+        // Both power-down patterns
+        for md in [0b10, 0b11] {
+            let mut bytes = bytes;
+            bytes[2] = md;
+            let config = MagConfig::decode::<()>(bytes, false).unwrap();
+            assert_eq!(config.mode, MagMode::PowerDown);
+        }
+    }
+}
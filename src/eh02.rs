@@ -0,0 +1,167 @@
+//! Readers for any I2C bus implementing embedded-hal 0.2's blocking
+//! `WriteRead` and `Write` traits, for platforms whose HALs haven't moved to
+//! embedded-hal 1.0.
+
+use crate::bus::RegisterAccess;
+use crate::lis3mdl;
+use crate::lsm6dsl;
+use embedded_hal_02::blocking::i2c::{Write, WriteRead};
+
+/// An embedded-hal 0.2 I2C bus, supporting both writes and combined
+/// write-reads with the same error type.
+pub trait I2c: WriteRead + Write<Error = <Self as WriteRead>::Error> {}
+
+impl<I: WriteRead + Write<Error = <I as WriteRead>::Error>> I2c for I {}
+
+/// Register access to a chip through an embedded-hal 0.2 I2C bus.
+pub struct I2cInterface<I: I2c> {
+    i2c: I,
+    address: u8,
+}
+
+impl<I: I2c> RegisterAccess for I2cInterface<I> {
+    type Error = <I as WriteRead>::Error;
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<<I as WriteRead>::Error>> {
+        let mut buf = [0];
+        self.i2c
+            .write_read(self.address, &[reg], &mut buf)
+            .map_err(crate::Error::read(reg))?;
+        Ok(buf[0])
+    }
+
+    fn write_register(
+        &mut self,
+        reg: u8,
+        value: u8,
+    ) -> Result<(), crate::Error<<I as WriteRead>::Error>> {
+        self.i2c
+            .write(self.address, &[reg, value])
+            .map_err(crate::Error::write(reg))
+    }
+
+    fn read_registers(
+        &mut self,
+        reg: u8,
+        buf: &mut [u8],
+    ) -> Result<(), crate::Error<<I as WriteRead>::Error>> {
+        self.i2c
+            .write_read(self.address, &[reg], buf)
+            .map_err(crate::Error::read(reg))
+    }
+}
+
+/// An accelerometer reader.
+pub type Accelerometer<I> = lsm6dsl::Accelerometer<I2cInterface<I>>;
+
+impl<I: I2c> Accelerometer<I> {
+    /// Creates a new accelerometer reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub fn new(i2c: I) -> Result<Self, crate::Error<<I as WriteRead>::Error>> {
+        Accelerometer::new_with_config(i2c, crate::AccelConfig::default())
+    }
+
+    /// Creates a new accelerometer reader from an I2C bus, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `config`: The accelerometer configuration.
+    pub fn new_with_config(
+        i2c: I,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<<I as WriteRead>::Error>> {
+        let address = crate::Chip::Lsm6dsl.i2c_address() as u8;
+        Accelerometer::from_bus(I2cInterface { i2c, address }, config)
+    }
+}
+
+/// A gyroscope reader.
+pub type Gyroscope<I> = lsm6dsl::Gyroscope<I2cInterface<I>>;
+
+impl<I: I2c> Gyroscope<I> {
+    /// Creates a new gyroscope reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub fn new(i2c: I) -> Result<Self, crate::Error<<I as WriteRead>::Error>> {
+        Gyroscope::new_with_config(i2c, crate::GyroConfig::default())
+    }
+
+    /// Creates a new gyroscope reader from an I2C bus, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `config`: The gyroscope configuration.
+    pub fn new_with_config(
+        i2c: I,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<<I as WriteRead>::Error>> {
+        let address = crate::Chip::Lsm6dsl.i2c_address() as u8;
+        Gyroscope::from_bus(I2cInterface { i2c, address }, config)
+    }
+}
+
+/// A reader for both the accelerometer and gyroscope, which share the
+/// LSM6DSL chip.
+pub type Lsm6dsl<I> = lsm6dsl::Lsm6dsl<I2cInterface<I>>;
+
+impl<I: I2c> Lsm6dsl<I> {
+    /// Creates a new LSM6DSL reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub fn new(i2c: I) -> Result<Self, crate::Error<<I as WriteRead>::Error>> {
+        Lsm6dsl::new_with_config(
+            i2c,
+            crate::AccelConfig::default(),
+            crate::GyroConfig::default(),
+        )
+    }
+
+    /// Creates a new LSM6DSL reader from an I2C bus, with the given
+    /// accelerometer and gyroscope configurations.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    pub fn new_with_config(
+        i2c: I,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<<I as WriteRead>::Error>> {
+        let address = crate::Chip::Lsm6dsl.i2c_address() as u8;
+        Lsm6dsl::from_bus(I2cInterface { i2c, address }, accel_config, gyro_config)
+    }
+}
+
+/// A magnetometer reader.
+pub type Magnetometer<I> = lis3mdl::Magnetometer<I2cInterface<I>>;
+
+impl<I: I2c> Magnetometer<I> {
+    /// Creates a new magnetometer reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub fn new(i2c: I) -> Result<Self, crate::Error<<I as WriteRead>::Error>> {
+        Magnetometer::new_with_config(i2c, crate::MagConfig::default())
+    }
+
+    /// Creates a new magnetometer reader from an I2C bus, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `config`: The magnetometer configuration.
+    pub fn new_with_config(
+        i2c: I,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<<I as WriteRead>::Error>> {
+        let address = crate::Chip::Lis3mdl.i2c_address() as u8;
+        Magnetometer::from_bus(I2cInterface { i2c, address }, config)
+    }
+}
@@ -0,0 +1,230 @@
+//! Readers for any I2C bus implementing embedded-hal 1.0's `I2c` trait, for
+//! platforms that don't provide `i2cdev`.
+
+use crate::bus::RegisterAccess;
+use crate::lis3mdl;
+use crate::lsm6dsl;
+use embedded_hal::i2c::I2c;
+
+/// Register access to a chip through an embedded-hal I2C bus.
+pub struct I2cInterface<I: I2c> {
+    i2c: I,
+    address: u8,
+}
+
+impl<I: I2c> RegisterAccess for I2cInterface<I> {
+    type Error = I::Error;
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<I::Error>> {
+        let mut buf = [0];
+        self.i2c
+            .write_read(self.address, &[reg], &mut buf)
+            .map_err(crate::Error::read(reg))?;
+        Ok(buf[0])
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<I::Error>> {
+        self.i2c
+            .write(self.address, &[reg, value])
+            .map_err(crate::Error::write(reg))
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), crate::Error<I::Error>> {
+        self.i2c
+            .write_read(self.address, &[reg], buf)
+            .map_err(crate::Error::read(reg))
+    }
+}
+
+/// An accelerometer reader.
+pub type Accelerometer<I> = lsm6dsl::Accelerometer<I2cInterface<I>>;
+
+impl<I: I2c> Accelerometer<I> {
+    /// Creates a new accelerometer reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub fn new(i2c: I) -> Result<Self, crate::Error<I::Error>> {
+        Accelerometer::new_with_config(i2c, crate::AccelConfig::default())
+    }
+
+    /// Creates a new accelerometer reader from an I2C bus, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `config`: The accelerometer configuration.
+    pub fn new_with_config(
+        i2c: I,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<I::Error>> {
+        let address = crate::Chip::Lsm6dsl.i2c_address() as u8;
+        Accelerometer::from_bus(I2cInterface { i2c, address }, config)
+    }
+}
+
+/// A gyroscope reader.
+pub type Gyroscope<I> = lsm6dsl::Gyroscope<I2cInterface<I>>;
+
+impl<I: I2c> Gyroscope<I> {
+    /// Creates a new gyroscope reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub fn new(i2c: I) -> Result<Self, crate::Error<I::Error>> {
+        Gyroscope::new_with_config(i2c, crate::GyroConfig::default())
+    }
+
+    /// Creates a new gyroscope reader from an I2C bus, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `config`: The gyroscope configuration.
+    pub fn new_with_config(
+        i2c: I,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<I::Error>> {
+        let address = crate::Chip::Lsm6dsl.i2c_address() as u8;
+        Gyroscope::from_bus(I2cInterface { i2c, address }, config)
+    }
+}
+
+/// A reader for both the accelerometer and gyroscope, which share the
+/// LSM6DSL chip.
+pub type Lsm6dsl<I> = lsm6dsl::Lsm6dsl<I2cInterface<I>>;
+
+impl<I: I2c> Lsm6dsl<I> {
+    /// Creates a new LSM6DSL reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub fn new(i2c: I) -> Result<Self, crate::Error<I::Error>> {
+        Lsm6dsl::new_with_config(
+            i2c,
+            crate::AccelConfig::default(),
+            crate::GyroConfig::default(),
+        )
+    }
+
+    /// Creates a new LSM6DSL reader from an I2C bus, with the given
+    /// accelerometer and gyroscope configurations.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    pub fn new_with_config(
+        i2c: I,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<I::Error>> {
+        let address = crate::Chip::Lsm6dsl.i2c_address() as u8;
+        Lsm6dsl::from_bus(I2cInterface { i2c, address }, accel_config, gyro_config)
+    }
+}
+
+/// A magnetometer reader.
+pub type Magnetometer<I> = lis3mdl::Magnetometer<I2cInterface<I>>;
+
+impl<I: I2c> Magnetometer<I> {
+    /// Creates a new magnetometer reader from an I2C bus.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    pub fn new(i2c: I) -> Result<Self, crate::Error<I::Error>> {
+        Magnetometer::new_with_config(i2c, crate::MagConfig::default())
+    }
+
+    /// Creates a new magnetometer reader from an I2C bus, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C bus.
+    /// * `config`: The magnetometer configuration.
+    pub fn new_with_config(
+        i2c: I,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<I::Error>> {
+        let address = crate::Chip::Lis3mdl.i2c_address() as u8;
+        Magnetometer::from_bus(I2cInterface { i2c, address }, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    #[test]
+    fn accelerometer_new() {
+        let mut i2c = Mock::new(&[
+            // WHO_AM_I
+            Transaction::write_read(0x6A, vec![0x0F], vec![0x6A]),
+            // The gyroscope's ODR in CTRL2_G, which is powered down
+            Transaction::write_read(0x6A, vec![0x11], vec![0x00]),
+            // CTRL1_XL, CTRL8_XL
+            Transaction::write(0x6A, vec![0x10, 0x9F]),
+            Transaction::write(0x6A, vec![0x17, 0xC8]),
+            // BDU, IF_INC and BLE in CTRL3_C
+            Transaction::write_read(0x6A, vec![0x12], vec![0x04]),
+            Transaction::write(0x6A, vec![0x12, 0x44]),
+            // XL_HM_MODE in CTRL6_C, keeping the gyroscope's FTYPE
+            Transaction::write_read(0x6A, vec![0x15], vec![0x01]),
+            Transaction::write(0x6A, vec![0x15, 0x01]),
+            // OUTX_L_XL
+            Transaction::write_read(0x6A, vec![0x28], vec![1, 0, 2, 0, 0xFD, 0xFF]),
+        ]);
+        let mut accel = Accelerometer::new(i2c.clone()).unwrap();
+        assert_eq!(accel.read().unwrap(), (1, 2, -3).into());
+        drop(accel);
+        i2c.done();
+    }
+
+    #[test]
+    fn gyroscope_new() {
+        let mut i2c = Mock::new(&[
+            // WHO_AM_I
+            Transaction::write_read(0x6A, vec![0x0F], vec![0x6A]),
+            // The accelerometer's ODR in CTRL1_XL, which is powered down
+            Transaction::write_read(0x6A, vec![0x10], vec![0x00]),
+            // CTRL2_G, CTRL7_G
+            Transaction::write(0x6A, vec![0x11, 0x9C]),
+            Transaction::write(0x6A, vec![0x16, 0x00]),
+            // BLE in CTRL3_C
+            Transaction::write_read(0x6A, vec![0x12], vec![0x44]),
+            Transaction::write(0x6A, vec![0x12, 0x44]),
+            // LPF1_SEL_G in CTRL4_C, and FTYPE in CTRL6_C
+            Transaction::write_read(0x6A, vec![0x13], vec![0x00]),
+            Transaction::write(0x6A, vec![0x13, 0x00]),
+            Transaction::write_read(0x6A, vec![0x15], vec![0x00]),
+            Transaction::write(0x6A, vec![0x15, 0x00]),
+            // OUTX_L_G
+            Transaction::write_read(0x6A, vec![0x22], vec![1, 0, 2, 0, 0xFD, 0xFF]),
+        ]);
+        let mut gyro = Gyroscope::new(i2c.clone()).unwrap();
+        assert_eq!(gyro.read().unwrap(), (1, 2, -3).into());
+        drop(gyro);
+        i2c.done();
+    }
+
+    #[test]
+    fn magnetometer_new() {
+        let mut i2c = Mock::new(&[
+            // WHO_AM_I
+            Transaction::write_read(0x1C, vec![0x0F], vec![0x3D]),
+            // CTRL_REG1 to CTRL_REG5
+            Transaction::write(0x1C, vec![0x20, 0xDC]),
+            Transaction::write(0x1C, vec![0x21, 0x20]),
+            Transaction::write(0x1C, vec![0x22, 0x00]),
+            Transaction::write(0x1C, vec![0x23, 0x08]),
+            Transaction::write(0x1C, vec![0x24, 0x40]),
+            // OUT_X_L
+            Transaction::write_read(0x1C, vec![0x28], vec![1, 0, 2, 0, 0xFD, 0xFF]),
+        ]);
+        let mut mag = Magnetometer::new(i2c.clone()).unwrap();
+        assert_eq!(mag.read().unwrap(), (1, 2, -3).into());
+        drop(mag);
+        i2c.done();
+    }
+}
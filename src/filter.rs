@@ -0,0 +1,173 @@
+//! Filters for raw readings, e.g. sensor fusion for estimating orientation.
+//!
+//! `ComplementaryFilter` combines the accelerometer and gyroscope into tilt
+//! angles. The gyroscope is accurate over short periods but drifts, while
+//! the accelerometer doesn't drift but is disturbed by any movement, so the
+//! filter blends the integrated gyroscope rate with the accelerometer's
+//! angle. The blend is set by `alpha`: values closer to 1.0 trust the
+//! gyroscope more, giving smoother angles that are slower to correct drift.
+//! 0.4, as in ozzmaker's reference code, is a reasonable starting point.
+//!
+//! `Madgwick` implements Sebastian Madgwick's gradient descent filter, which
+//! estimates the full orientation as a quaternion. It corrects the
+//! integrated gyroscope rate towards the accelerometer's gravity vector, and
+//! optionally the magnetometer's field for yaw, by a step set by `beta`.
+//! Larger values converge faster but pass through more accelerometer and
+//! magnetometer noise. The original paper suggests around 0.041.
+//!
+//! `Mahony` implements Robert Mahony's filter, which instead corrects the
+//! gyroscope rate by proportional-integral feedback of the error between the
+//! measured and estimated gravity, and optionally magnetic field. `kp` sets
+//! how quickly it converges, and `ki` removes the gyroscope's bias. It
+//! converges faster than `Madgwick` when the sensors are quiet. The
+//! published implementation uses a `kp` of 0.5, and a `ki` of 0.0.
+//!
+//! `MovingAverage` averages a sensor's last few readings, to reduce noise
+//! where responsiveness matters less, e.g. in a static orientation display.
+//! It keeps its readings on the stack, so unlike the fusion filters, it's
+//! available without `std`.
+
+use crate::{AccelSensor, GyroSensor, MagSensor};
+
+#[cfg(feature = "std")]
+mod fusion;
+
+#[cfg(feature = "std")]
+pub use fusion::*;
+
+/// Averages the last `N` readings of a sensor, for each axis. It wraps the
+/// reader for a sensor, and implements whichever of `AccelSensor`,
+/// `GyroSensor` and `MagSensor` the reader does, averaging each separately,
+/// e.g. for a reader of both the accelerometer and gyroscope. Until `N`
+/// readings have been taken, the average is of those taken so far.
+#[derive(Clone, Debug)]
+pub struct MovingAverage<S, const N: usize> {
+    sensor: S,
+    accel: Window<N>,
+    gyro: Window<N>,
+    mag: Window<N>,
+}
+
+impl<S, const N: usize> MovingAverage<S, N> {
+    /// Creates a new moving average of a sensor's readings.
+    ///
+    /// # Arguments
+    /// * `sensor`: The sensor's reader.
+    ///
+    /// # Panics
+    /// If `N` is zero.
+    pub fn new(sensor: S) -> Self {
+        assert!(N > 0, "a moving average needs at least one reading");
+        Self {
+            sensor,
+            accel: Window::new(),
+            gyro: Window::new(),
+            mag: Window::new(),
+        }
+    }
+
+    /// The sensor's reader, e.g. to change its configuration.
+    pub fn sensor(&mut self) -> &mut S {
+        &mut self.sensor
+    }
+
+    /// Returns the sensor's reader.
+    pub fn into_inner(self) -> S {
+        self.sensor
+    }
+
+    /// Discards the readings taken so far, e.g. after the sensor's
+    /// configuration changes.
+    pub fn reset(&mut self) {
+        self.accel = Window::new();
+        self.gyro = Window::new();
+        self.mag = Window::new();
+    }
+}
+
+// The last `N` readings of one sensor
+#[derive(Clone, Debug)]
+struct Window<const N: usize> {
+    readings: [[i32; 3]; N],
+    len: usize,
+    next: usize,
+}
+
+impl<const N: usize> Window<N> {
+    fn new() -> Self {
+        Self {
+            readings: [[0; 3]; N],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    // Replaces the oldest reading, and returns the new average, truncated
+    // towards zero
+    fn push(&mut self, reading: [i32; 3]) -> [i32; 3] {
+        self.readings[self.next] = reading;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+        let readings = &self.readings[..self.len];
+        core::array::from_fn(|i| {
+            let sum: i64 = readings.iter().map(|reading| reading[i] as i64).sum();
+            (sum / self.len as i64) as i32
+        })
+    }
+}
+
+impl<S: AccelSensor, const N: usize> AccelSensor for MovingAverage<S, N> {
+    type Error = S::Error;
+
+    fn read_accel(&mut self) -> Result<crate::AccelReading, crate::Error<S::Error>> {
+        let reading = self.sensor.read_accel()?;
+        let [x, y, z] = self.accel.push([reading.x, reading.y, reading.z]);
+        Ok(crate::AccelReading { x, y, z })
+    }
+}
+
+impl<S: GyroSensor, const N: usize> GyroSensor for MovingAverage<S, N> {
+    type Error = S::Error;
+
+    fn read_gyro(&mut self) -> Result<crate::GyroReading, crate::Error<S::Error>> {
+        let reading = self.sensor.read_gyro()?;
+        let [x, y, z] = self.gyro.push([reading.x, reading.y, reading.z]);
+        Ok(crate::GyroReading { x, y, z })
+    }
+}
+
+impl<S: MagSensor, const N: usize> MagSensor for MovingAverage<S, N> {
+    type Error = S::Error;
+
+    fn read_mag(&mut self) -> Result<crate::MagReading, crate::Error<S::Error>> {
+        let reading = self.sensor.read_mag()?;
+        let [x, y, z] = self.mag.push([reading.x, reading.y, reading.z]);
+        Ok(crate::MagReading { x, y, z })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsm6dsl::Lsm6dsl;
+    use crate::testing::FakeBus;
+    use crate::{AccelConfig, GyroConfig};
+
+    #[test]
+    fn averages_each_sensor_separately() {
+        let bus = FakeBus::lsm6dsl();
+        let imu =
+            Lsm6dsl::from_bus(bus.clone(), AccelConfig::default(), GyroConfig::default()).unwrap();
+        let mut average = MovingAverage::<_, 4>::new(imu);
+        // OUTX_L_XL and OUTX_L_G
+        bus.set(0x28, 10);
+        bus.set(0x22, 100);
+        assert_eq!(average.read_accel().unwrap().x, 10);
+        assert_eq!(average.read_gyro().unwrap().x, 100);
+        bus.set(0x28, 20);
+        assert_eq!(average.read_accel().unwrap().x, 15);
+        assert_eq!(average.read_gyro().unwrap().x, 100);
+        average.reset();
+        assert_eq!(average.read_accel().unwrap().x, 20);
+    }
+}
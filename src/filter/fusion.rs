@@ -0,0 +1,756 @@
+//! The sensor fusion filters, which need `std` for their floating point
+//! math.
+
+use core::f64::consts::PI;
+
+/// A complementary filter, which estimates the angles around the X and Y
+/// axes, in degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComplementaryFilter {
+    alpha: f64,
+    degrees_per_lsb: f64,
+    angle_x: f64,
+    angle_y: f64,
+}
+
+impl ComplementaryFilter {
+    /// Creates a new complementary filter, with both angles starting at
+    /// zero.
+    ///
+    /// # Arguments
+    /// * `alpha`: How much to trust the gyroscope over the accelerometer,
+    ///   from 0.0 to 1.0.
+    /// * `gyro_full_scale`: The gyroscope's full-scale range, which the raw
+    ///   readings are scaled by.
+    pub fn new(alpha: f64, gyro_full_scale: crate::GyroFullScale) -> Self {
+        Self {
+            alpha,
+            degrees_per_lsb: gyro_full_scale.degrees_per_lsb().into(),
+            angle_x: 0.0,
+            angle_y: 0.0,
+        }
+    }
+
+    /// Changes the gyroscope full-scale range that raw readings are scaled
+    /// by, e.g. after changing it on the gyroscope.
+    ///
+    /// # Arguments
+    /// * `gyro_full_scale`: The gyroscope's full-scale range.
+    pub fn set_gyro_full_scale(&mut self, gyro_full_scale: crate::GyroFullScale) {
+        self.degrees_per_lsb = gyro_full_scale.degrees_per_lsb().into();
+    }
+
+    /// Updates the estimate with a new sample, returning the angles around
+    /// the X and Y axes, in degrees.
+    ///
+    /// # Arguments
+    /// * `accel`: The raw accelerometer values.
+    /// * `gyro`: The raw gyroscope values.
+    /// * `dt_secs`: The time since the previous sample, in seconds.
+    pub fn update(
+        &mut self,
+        accel: &crate::AccelReading,
+        gyro: &crate::GyroReading,
+        dt_secs: f64,
+    ) -> (f64, f64) {
+        let (acc_x, acc_y, acc_z) = (accel.x as f64, accel.y as f64, accel.z as f64);
+
+        // Convert gyro raw to degrees per second
+        let rate_x = gyro.x as f64 * self.degrees_per_lsb;
+        let rate_y = gyro.y as f64 * self.degrees_per_lsb;
+
+        // Convert accelerometer values to degrees
+        let acc_angle_x = 180.0 * acc_y.atan2(acc_z) / PI;
+        let mut acc_angle_y = 180.0 * (acc_z.atan2(acc_x) + PI) / PI;
+        // Convert the values to -180 and +180
+        if acc_angle_y > 90.0 {
+            acc_angle_y -= 270.0;
+        } else {
+            acc_angle_y += 90.0;
+        }
+
+        let alpha = self.alpha;
+        self.angle_x = alpha * (self.angle_x + rate_x * dt_secs) + (1.0 - alpha) * acc_angle_x;
+        self.angle_y = alpha * (self.angle_y + rate_y * dt_secs) + (1.0 - alpha) * acc_angle_y;
+        (self.angle_x, self.angle_y)
+    }
+
+    /// The current angles around the X and Y axes, in degrees.
+    pub fn angles(&self) -> (f64, f64) {
+        (self.angle_x, self.angle_y)
+    }
+}
+
+/// An orientation, as a unit quaternion in `[w, x, y, z]` order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quaternion(pub [f64; 4]);
+
+impl Quaternion {
+    /// The orientation as roll, pitch and yaw.
+    pub fn to_euler_angles(&self) -> EulerAngles {
+        let [w, x, y, z] = self.0;
+        let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+        // Clamped, since rounding can push it just past +/- 1 at +/- 90
+        // degrees of pitch
+        let pitch = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+        let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+        EulerAngles { roll, pitch, yaw }
+    }
+
+    /// The orientation as a rotation matrix, in row-major order, which
+    /// rotates vectors from the sensor frame to the earth frame.
+    pub fn to_rotation_matrix(&self) -> [[f64; 3]; 3] {
+        let [w, x, y, z] = self.0;
+        [
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+            ],
+        ]
+    }
+
+    /// The heading, i.e. the yaw, in degrees from 0 up to 360.
+    pub fn to_heading_degrees(&self) -> f64 {
+        self.to_euler_angles().yaw.to_degrees().rem_euclid(360.0)
+    }
+
+    /// This quaternion scaled to unit length, e.g. to remove accumulated
+    /// rounding errors.
+    pub fn normalize(&self) -> Self {
+        let norm = self.0.iter().map(|q| q * q).sum::<f64>().sqrt();
+        Quaternion(self.0.map(|q| q / norm))
+    }
+
+    /// The conjugate, which for a unit quaternion is the inverse rotation.
+    pub fn conjugate(&self) -> Self {
+        let [w, x, y, z] = self.0;
+        Quaternion([w, -x, -y, -z])
+    }
+
+    /// Spherically interpolates between this orientation and another, along
+    /// the shortest path.
+    ///
+    /// # Arguments
+    /// * `other`: The orientation to interpolate towards.
+    /// * `t`: How far to interpolate, from 0.0, i.e. this orientation, to
+    ///   1.0, i.e. `other`.
+    pub fn slerp(&self, other: &Quaternion, t: f64) -> Quaternion {
+        let mut dot: f64 = self.0.iter().zip(other.0).map(|(a, b)| a * b).sum();
+        // `other` and its negation are the same orientation, and one of
+        // them is the shorter path
+        let mut other = other.0;
+        if dot < 0.0 {
+            other = other.map(|q| -q);
+            dot = -dot;
+        }
+        let (a, b) = if dot > 0.9995 {
+            // Nearly parallel, where linear interpolation is accurate and
+            // avoids dividing by a tiny sine
+            (1.0 - t, t)
+        } else {
+            let theta = dot.acos();
+            let sin_theta = theta.sin();
+            (
+                ((1.0 - t) * theta).sin() / sin_theta,
+                (t * theta).sin() / sin_theta,
+            )
+        };
+        let mut q = self.0;
+        for (q, other) in q.iter_mut().zip(other) {
+            *q = a * *q + b * other;
+        }
+        Quaternion(q).normalize()
+    }
+}
+
+impl Default for Quaternion {
+    /// No rotation
+    fn default() -> Self {
+        Quaternion([1.0, 0.0, 0.0, 0.0])
+    }
+}
+
+/// An orientation as rotations around the X, Y and Z axes, in radians.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EulerAngles {
+    pub roll: f64,
+    pub pitch: f64,
+    pub yaw: f64,
+}
+
+impl EulerAngles {
+    /// The roll, pitch and yaw, in degrees.
+    pub fn to_degrees(&self) -> (f64, f64, f64) {
+        (
+            self.roll.to_degrees(),
+            self.pitch.to_degrees(),
+            self.yaw.to_degrees(),
+        )
+    }
+}
+
+/// A Madgwick filter, which estimates the orientation as a quaternion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Madgwick {
+    quaternion: [f64; 4],
+    beta: f64,
+    radians_per_lsb: f64,
+}
+
+impl Madgwick {
+    /// Creates a new Madgwick filter, starting with no rotation.
+    ///
+    /// # Arguments
+    /// * `beta`: The gain of the correction towards the accelerometer and
+    ///   magnetometer.
+    /// * `gyro_full_scale`: The gyroscope's full-scale range, which the raw
+    ///   readings are scaled by.
+    pub fn new(beta: f64, gyro_full_scale: crate::GyroFullScale) -> Self {
+        let degrees_per_lsb: f64 = gyro_full_scale.degrees_per_lsb().into();
+        Self {
+            quaternion: Quaternion::default().0,
+            beta,
+            radians_per_lsb: degrees_per_lsb.to_radians(),
+        }
+    }
+
+    /// Changes the gyroscope full-scale range that raw readings are scaled
+    /// by, e.g. after changing it on the gyroscope.
+    ///
+    /// # Arguments
+    /// * `gyro_full_scale`: The gyroscope's full-scale range.
+    pub fn set_gyro_full_scale(&mut self, gyro_full_scale: crate::GyroFullScale) {
+        let degrees_per_lsb: f64 = gyro_full_scale.degrees_per_lsb().into();
+        self.radians_per_lsb = degrees_per_lsb.to_radians();
+    }
+
+    /// The current orientation.
+    pub fn quaternion(&self) -> Quaternion {
+        Quaternion(self.quaternion)
+    }
+
+    /// Updates the estimate with a new sample from the accelerometer and
+    /// gyroscope, returning the new orientation. Yaw drifts, since there's
+    /// no reference to correct it by.
+    ///
+    /// # Arguments
+    /// * `accel`: The raw accelerometer values.
+    /// * `gyro`: The raw gyroscope values.
+    /// * `dt`: The time since the previous sample, in seconds.
+    pub fn update(
+        &mut self,
+        accel: &crate::AccelReading,
+        gyro: &crate::GyroReading,
+        dt: f64,
+    ) -> Quaternion {
+        let [q0, q1, q2, q3] = self.quaternion;
+        let (gx, gy, gz) = gyro_rates(gyro, self.radians_per_lsb);
+        let mut q_dot = rate_of_change(self.quaternion, gx, gy, gz);
+
+        // Skipped if the accelerometer reads zero, which can't be normalized
+        if let Some([ax, ay, az]) = normalize([accel.x as f64, accel.y as f64, accel.z as f64]) {
+            // The gradient of the objective function for gravity
+            let s = [
+                4.0 * q0 * q2 * q2 + 2.0 * q2 * ax + 4.0 * q0 * q1 * q1 - 2.0 * q1 * ay,
+                4.0 * q1 * q3 * q3 - 2.0 * q3 * ax + 4.0 * q0 * q0 * q1 - 2.0 * q0 * ay - 4.0 * q1
+                    + 8.0 * q1 * q1 * q1
+                    + 8.0 * q1 * q2 * q2
+                    + 4.0 * q1 * az,
+                4.0 * q0 * q0 * q2 + 2.0 * q0 * ax + 4.0 * q2 * q3 * q3 - 2.0 * q3 * ay - 4.0 * q2
+                    + 8.0 * q2 * q1 * q1
+                    + 8.0 * q2 * q2 * q2
+                    + 4.0 * q2 * az,
+                4.0 * q1 * q1 * q3 - 2.0 * q1 * ax + 4.0 * q2 * q2 * q3 - 2.0 * q2 * ay,
+            ];
+            self.apply_correction(&mut q_dot, s);
+        }
+        self.quaternion = integrate(self.quaternion, q_dot, dt);
+        Quaternion(self.quaternion)
+    }
+
+    /// Updates the estimate with a new sample from the accelerometer,
+    /// gyroscope and magnetometer, returning the new orientation. The
+    /// magnetometer corrects the yaw.
+    ///
+    /// # Arguments
+    /// * `accel`: The raw accelerometer values.
+    /// * `gyro`: The raw gyroscope values.
+    /// * `mag`: The raw magnetometer values.
+    /// * `dt`: The time since the previous sample, in seconds.
+    pub fn update_with_mag(
+        &mut self,
+        accel: &crate::AccelReading,
+        gyro: &crate::GyroReading,
+        mag: &crate::MagReading,
+        dt: f64,
+    ) -> Quaternion {
+        let accel_norm = normalize([accel.x as f64, accel.y as f64, accel.z as f64]);
+        let mag_norm = normalize([mag.x as f64, mag.y as f64, mag.z as f64]);
+        let ([ax, ay, az], [mx, my, mz]) = match (accel_norm, mag_norm) {
+            (Some(accel), Some(mag)) => (accel, mag),
+            // Without a magnetometer reading, this falls back to the
+            // accelerometer and gyroscope alone
+            _ => return self.update(accel, gyro, dt),
+        };
+        let [q0, q1, q2, q3] = self.quaternion;
+        let (gx, gy, gz) = gyro_rates(gyro, self.radians_per_lsb);
+        let mut q_dot = rate_of_change(self.quaternion, gx, gy, gz);
+
+        // The direction of the earth's magnetic field, in the earth frame
+        let hx = mx * (q0 * q0 + q1 * q1 - q2 * q2 - q3 * q3)
+            + 2.0 * my * (q1 * q2 - q0 * q3)
+            + 2.0 * mz * (q0 * q2 + q1 * q3);
+        let hy = 2.0 * mx * (q0 * q3 + q1 * q2)
+            + my * (q0 * q0 - q1 * q1 + q2 * q2 - q3 * q3)
+            + 2.0 * mz * (q2 * q3 - q0 * q1);
+        let bx = (hx * hx + hy * hy).sqrt();
+        let bz = 2.0 * mx * (q1 * q3 - q0 * q2)
+            + 2.0 * my * (q0 * q1 + q2 * q3)
+            + mz * (q0 * q0 - q1 * q1 - q2 * q2 + q3 * q3);
+
+        // The objective function's errors for gravity and the magnetic field
+        let f_gx = 2.0 * (q1 * q3 - q0 * q2) - ax;
+        let f_gy = 2.0 * (q0 * q1 + q2 * q3) - ay;
+        let f_gz = 1.0 - 2.0 * (q1 * q1 + q2 * q2) - az;
+        let f_bx = 2.0 * bx * (0.5 - q2 * q2 - q3 * q3) + 2.0 * bz * (q1 * q3 - q0 * q2) - mx;
+        let f_by = 2.0 * bx * (q1 * q2 - q0 * q3) + 2.0 * bz * (q0 * q1 + q2 * q3) - my;
+        let f_bz = 2.0 * bx * (q0 * q2 + q1 * q3) + 2.0 * bz * (0.5 - q1 * q1 - q2 * q2) - mz;
+
+        // And their gradient, i.e. the transposed Jacobian times the errors
+        let s = [
+            -2.0 * q2 * f_gx + 2.0 * q1 * f_gy - 2.0 * bz * q2 * f_bx
+                + 2.0 * (bz * q1 - bx * q3) * f_by
+                + 2.0 * bx * q2 * f_bz,
+            2.0 * q3 * f_gx + 2.0 * q0 * f_gy - 4.0 * q1 * f_gz
+                + 2.0 * bz * q3 * f_bx
+                + 2.0 * (bx * q2 + bz * q0) * f_by
+                + 2.0 * (bx * q3 - 2.0 * bz * q1) * f_bz,
+            -2.0 * q0 * f_gx + 2.0 * q3 * f_gy - 4.0 * q2 * f_gz
+                + 2.0 * (-2.0 * bx * q2 - bz * q0) * f_bx
+                + 2.0 * (bx * q1 + bz * q3) * f_by
+                + 2.0 * (bx * q0 - 2.0 * bz * q2) * f_bz,
+            2.0 * q1 * f_gx
+                + 2.0 * q2 * f_gy
+                + 2.0 * (-2.0 * bx * q3 + bz * q1) * f_bx
+                + 2.0 * (-bx * q0 + bz * q2) * f_by
+                + 2.0 * bx * q1 * f_bz,
+        ];
+        self.apply_correction(&mut q_dot, s);
+        self.quaternion = integrate(self.quaternion, q_dot, dt);
+        Quaternion(self.quaternion)
+    }
+
+    // Steps the rate of change against the normalized gradient
+    fn apply_correction(&self, q_dot: &mut [f64; 4], gradient: [f64; 4]) {
+        let norm = gradient.iter().map(|s| s * s).sum::<f64>().sqrt();
+        // The gradient's zero once the estimate matches exactly
+        if norm > 0.0 {
+            for (q, s) in q_dot.iter_mut().zip(gradient) {
+                *q -= self.beta * s / norm;
+            }
+        }
+    }
+}
+
+/// A Mahony filter, which estimates the orientation as a quaternion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mahony {
+    quaternion: [f64; 4],
+    integral_error: [f64; 3],
+    kp: f64,
+    ki: f64,
+    radians_per_lsb: f64,
+}
+
+impl Mahony {
+    /// Creates a new Mahony filter, starting with no rotation.
+    ///
+    /// # Arguments
+    /// * `kp`: The proportional gain of the feedback.
+    /// * `ki`: The integral gain of the feedback, or 0.0 to disable it.
+    /// * `gyro_full_scale`: The gyroscope's full-scale range, which the raw
+    ///   readings are scaled by.
+    pub fn new(kp: f64, ki: f64, gyro_full_scale: crate::GyroFullScale) -> Self {
+        let degrees_per_lsb: f64 = gyro_full_scale.degrees_per_lsb().into();
+        Self {
+            quaternion: Quaternion::default().0,
+            integral_error: [0.0; 3],
+            kp,
+            ki,
+            radians_per_lsb: degrees_per_lsb.to_radians(),
+        }
+    }
+
+    /// Changes the gyroscope full-scale range that raw readings are scaled
+    /// by, e.g. after changing it on the gyroscope.
+    ///
+    /// # Arguments
+    /// * `gyro_full_scale`: The gyroscope's full-scale range.
+    pub fn set_gyro_full_scale(&mut self, gyro_full_scale: crate::GyroFullScale) {
+        let degrees_per_lsb: f64 = gyro_full_scale.degrees_per_lsb().into();
+        self.radians_per_lsb = degrees_per_lsb.to_radians();
+    }
+
+    /// The current orientation.
+    pub fn quaternion(&self) -> Quaternion {
+        Quaternion(self.quaternion)
+    }
+
+    /// Resets the orientation to no rotation, and clears the integral
+    /// error.
+    pub fn reset(&mut self) {
+        self.quaternion = Quaternion::default().0;
+        self.integral_error = [0.0; 3];
+    }
+
+    /// Updates the estimate with a new sample from the accelerometer and
+    /// gyroscope, returning the new orientation. Yaw drifts, since there's
+    /// no reference to correct it by.
+    ///
+    /// # Arguments
+    /// * `accel`: The raw accelerometer values.
+    /// * `gyro`: The raw gyroscope values.
+    /// * `dt`: The time since the previous sample, in seconds.
+    pub fn update(
+        &mut self,
+        accel: &crate::AccelReading,
+        gyro: &crate::GyroReading,
+        dt: f64,
+    ) -> Quaternion {
+        // Skipped if the accelerometer reads zero, which can't be normalized
+        let error = normalize([accel.x as f64, accel.y as f64, accel.z as f64])
+            .map(|accel| cross(accel, self.estimated_gravity()));
+        self.integrate_with_feedback(gyro, error, dt)
+    }
+
+    /// Updates the estimate with a new sample from the accelerometer,
+    /// gyroscope and magnetometer, returning the new orientation. The
+    /// magnetometer corrects the yaw.
+    ///
+    /// # Arguments
+    /// * `accel`: The raw accelerometer values.
+    /// * `gyro`: The raw gyroscope values.
+    /// * `mag`: The raw magnetometer values.
+    /// * `dt`: The time since the previous sample, in seconds.
+    pub fn update_with_mag(
+        &mut self,
+        accel: &crate::AccelReading,
+        gyro: &crate::GyroReading,
+        mag: &crate::MagReading,
+        dt: f64,
+    ) -> Quaternion {
+        let accel_norm = normalize([accel.x as f64, accel.y as f64, accel.z as f64]);
+        let mag_norm = normalize([mag.x as f64, mag.y as f64, mag.z as f64]);
+        let (accel, [mx, my, mz]) = match (accel_norm, mag_norm) {
+            (Some(accel), Some(mag)) => (accel, mag),
+            // Without a magnetometer reading, this falls back to the
+            // accelerometer and gyroscope alone
+            _ => return self.update(accel, gyro, dt),
+        };
+        let [q0, q1, q2, q3] = self.quaternion;
+
+        // The direction of the earth's magnetic field, in the earth frame
+        let hx = 2.0
+            * (mx * (0.5 - q2 * q2 - q3 * q3)
+                + my * (q1 * q2 - q0 * q3)
+                + mz * (q1 * q3 + q0 * q2));
+        let hy = 2.0
+            * (mx * (q1 * q2 + q0 * q3)
+                + my * (0.5 - q1 * q1 - q3 * q3)
+                + mz * (q2 * q3 - q0 * q1));
+        let bx = (hx * hx + hy * hy).sqrt();
+        let bz = 2.0
+            * (mx * (q1 * q3 - q0 * q2)
+                + my * (q2 * q3 + q0 * q1)
+                + mz * (0.5 - q1 * q1 - q2 * q2));
+
+        // And its estimated direction, in the sensor frame
+        let w = [
+            2.0 * (bx * (0.5 - q2 * q2 - q3 * q3) + bz * (q1 * q3 - q0 * q2)),
+            2.0 * (bx * (q1 * q2 - q0 * q3) + bz * (q0 * q1 + q2 * q3)),
+            2.0 * (bx * (q0 * q2 + q1 * q3) + bz * (0.5 - q1 * q1 - q2 * q2)),
+        ];
+
+        let gravity_error = cross(accel, self.estimated_gravity());
+        let field_error = cross([mx, my, mz], w);
+        let error = [
+            gravity_error[0] + field_error[0],
+            gravity_error[1] + field_error[1],
+            gravity_error[2] + field_error[2],
+        ];
+        self.integrate_with_feedback(gyro, Some(error), dt)
+    }
+
+    // The direction of gravity, in the sensor frame
+    fn estimated_gravity(&self) -> [f64; 3] {
+        let [q0, q1, q2, q3] = self.quaternion;
+        [
+            2.0 * (q1 * q3 - q0 * q2),
+            2.0 * (q0 * q1 + q2 * q3),
+            q0 * q0 - q1 * q1 - q2 * q2 + q3 * q3,
+        ]
+    }
+
+    // Corrects the gyroscope rate by the feedback of the error, if any, and
+    // integrates it
+    fn integrate_with_feedback(
+        &mut self,
+        gyro: &crate::GyroReading,
+        error: Option<[f64; 3]>,
+        dt: f64,
+    ) -> Quaternion {
+        let (mut gx, mut gy, mut gz) = gyro_rates(gyro, self.radians_per_lsb);
+        if let Some([ex, ey, ez]) = error {
+            if self.ki > 0.0 {
+                self.integral_error[0] += self.ki * ex * dt;
+                self.integral_error[1] += self.ki * ey * dt;
+                self.integral_error[2] += self.ki * ez * dt;
+            } else {
+                // Prevents wind-up if the integral gain is later enabled
+                self.integral_error = [0.0; 3];
+            }
+            gx += self.kp * ex + self.integral_error[0];
+            gy += self.kp * ey + self.integral_error[1];
+            gz += self.kp * ez + self.integral_error[2];
+        }
+        let q_dot = rate_of_change(self.quaternion, gx, gy, gz);
+        self.quaternion = integrate(self.quaternion, q_dot, dt);
+        Quaternion(self.quaternion)
+    }
+}
+
+// Converts the raw gyroscope values to radians per second
+fn gyro_rates(gyro: &crate::GyroReading, radians_per_lsb: f64) -> (f64, f64, f64) {
+    (
+        gyro.x as f64 * radians_per_lsb,
+        gyro.y as f64 * radians_per_lsb,
+        gyro.z as f64 * radians_per_lsb,
+    )
+}
+
+// The rate of change of the quaternion from the gyroscope alone
+fn rate_of_change([q0, q1, q2, q3]: [f64; 4], gx: f64, gy: f64, gz: f64) -> [f64; 4] {
+    [
+        0.5 * (-q1 * gx - q2 * gy - q3 * gz),
+        0.5 * (q0 * gx + q2 * gz - q3 * gy),
+        0.5 * (q0 * gy - q1 * gz + q3 * gx),
+        0.5 * (q0 * gz + q1 * gy - q2 * gx),
+    ]
+}
+
+// Steps the quaternion by its rate of change, and renormalizes it
+fn integrate(mut q: [f64; 4], q_dot: [f64; 4], dt: f64) -> [f64; 4] {
+    for (q, q_dot) in q.iter_mut().zip(q_dot) {
+        *q += q_dot * dt;
+    }
+    let norm = q.iter().map(|q| q * q).sum::<f64>().sqrt();
+    for q in q.iter_mut() {
+        *q /= norm;
+    }
+    q
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(v: [f64; 3]) -> Option<[f64; 3]> {
+    let norm = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if norm == 0.0 {
+        None
+    } else {
+        Some([v[0] / norm, v[1] / norm, v[2] / norm])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A rotation by `degrees` around a unit axis
+    fn about(axis: [f64; 3], degrees: f64) -> Quaternion {
+        let (sin, cos) = (degrees.to_radians() / 2.0).sin_cos();
+        Quaternion([cos, axis[0] * sin, axis[1] * sin, axis[2] * sin])
+    }
+
+    // Within around a degree, where a quaternion and its negation are the
+    // same orientation
+    fn assert_same_orientation(a: Quaternion, b: Quaternion) {
+        let dot: f64 = a.0.iter().zip(b.0).map(|(a, b)| a * b).sum();
+        assert!(dot.abs() > 1.0 - 1e-4, "{:?} vs {:?}", a, b);
+    }
+
+    // Level, and facing magnetic north, where the field points down too
+    const LEVEL: crate::AccelReading = crate::AccelReading {
+        x: 0,
+        y: 0,
+        z: 1000,
+    };
+    const STILL: crate::GyroReading = crate::GyroReading { x: 0, y: 0, z: 0 };
+    const NORTH: crate::MagReading = crate::MagReading {
+        x: 300,
+        y: 0,
+        z: 400,
+    };
+
+    // Tilted 30 degrees, and turned 45 degrees from north
+    fn tilted() -> Quaternion {
+        let q = about([1.0, 0.0, 0.0], 30.0).0;
+        let [w, x, y, z] = about([0.0, 0.0, 1.0], 45.0).0;
+        let [qw, qx, qy, qz] = q;
+        Quaternion([
+            w * qw - x * qx - y * qy - z * qz,
+            w * qx + x * qw + y * qz - z * qy,
+            w * qy - x * qz + y * qw + z * qx,
+            w * qz + x * qy - y * qx + z * qw,
+        ])
+    }
+
+    #[test]
+    fn madgwick_converges() {
+        let mut filter = Madgwick::new(0.5, crate::GyroFullScale::Dps250);
+        filter.quaternion = tilted().0;
+        for _ in 0..2000 {
+            filter.update(&LEVEL, &STILL, 0.01);
+        }
+        // The fixed step leaves it within around beta * dt
+        let angles = filter.quaternion().to_euler_angles();
+        assert!(angles.roll.abs() < 0.02, "{:?}", angles);
+        assert!(angles.pitch.abs() < 0.02, "{:?}", angles);
+        // Without a magnetometer, only the tilt is corrected
+        assert!((angles.yaw - PI / 4.0).abs() < 0.02, "{:?}", angles);
+
+        filter.quaternion = tilted().0;
+        for _ in 0..2000 {
+            filter.update_with_mag(&LEVEL, &STILL, &NORTH, 0.01);
+        }
+        assert_same_orientation(filter.quaternion(), Quaternion::default());
+    }
+
+    #[test]
+    fn mahony_converges() {
+        let mut filter = Mahony::new(2.0, 0.0, crate::GyroFullScale::Dps250);
+        filter.quaternion = tilted().0;
+        for _ in 0..2000 {
+            filter.update(&LEVEL, &STILL, 0.01);
+        }
+        let angles = filter.quaternion().to_euler_angles();
+        assert!(angles.roll.abs() < 1e-3, "{:?}", angles);
+        assert!(angles.pitch.abs() < 1e-3, "{:?}", angles);
+        // Without a magnetometer, only the tilt is corrected
+        assert!((angles.yaw - PI / 4.0).abs() < 1e-3, "{:?}", angles);
+
+        filter.quaternion = tilted().0;
+        for _ in 0..2000 {
+            filter.update_with_mag(&LEVEL, &STILL, &NORTH, 0.01);
+        }
+        assert_same_orientation(filter.quaternion(), Quaternion::default());
+    }
+
+    #[test]
+    fn mahony_reset() {
+        let mut filter = Mahony::new(2.0, 0.5, crate::GyroFullScale::Dps250);
+        filter.quaternion = tilted().0;
+        for _ in 0..10 {
+            filter.update(&LEVEL, &STILL, 0.01);
+        }
+        assert_ne!(filter.integral_error, [0.0; 3]);
+        filter.reset();
+        assert_eq!(filter.integral_error, [0.0; 3]);
+        assert_eq!(filter.quaternion(), Quaternion::default());
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} vs {}", a, b);
+    }
+
+    #[test]
+    fn quaternion_euler_angles() {
+        let angles = about([1.0, 0.0, 0.0], 90.0).to_euler_angles();
+        assert_close(angles.roll, PI / 2.0);
+        assert_close(angles.pitch, 0.0);
+        assert_close(angles.yaw, 0.0);
+        let angles = about([0.0, 1.0, 0.0], 45.0).to_euler_angles();
+        assert_close(angles.roll, 0.0);
+        assert_close(angles.pitch, PI / 4.0);
+        assert_close(angles.yaw, 0.0);
+        // Where rounding can push the sine of the pitch past 1
+        let angles = about([0.0, 1.0, 0.0], 90.0).to_euler_angles();
+        assert_close(angles.pitch, PI / 2.0);
+        let angles = about([0.0, 0.0, 1.0], 90.0).to_euler_angles();
+        assert_close(angles.roll, 0.0);
+        assert_close(angles.pitch, 0.0);
+        assert_close(angles.yaw, PI / 2.0);
+    }
+
+    #[test]
+    fn quaternion_rotation_matrix() {
+        for (axis, expected) in [
+            // Y to Z, Z to X, and X to Y
+            (
+                [1.0, 0.0, 0.0],
+                [[1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]],
+            ),
+            (
+                [0.0, 1.0, 0.0],
+                [[0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [-1.0, 0.0, 0.0]],
+            ),
+            (
+                [0.0, 0.0, 1.0],
+                [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+            ),
+        ] {
+            let matrix = about(axis, 90.0).to_rotation_matrix();
+            for (row, expected) in matrix.iter().zip(expected) {
+                for (&value, expected) in row.iter().zip(expected) {
+                    assert_close(value, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quaternion_heading() {
+        assert_close(Quaternion::default().to_heading_degrees(), 0.0);
+        let z = [0.0, 0.0, 1.0];
+        assert_close(about(z, 90.0).to_heading_degrees(), 90.0);
+        // Negative yaws wrap around
+        assert_close(about(z, -90.0).to_heading_degrees(), 270.0);
+        assert_close(about(z, 180.0).to_heading_degrees(), 180.0);
+    }
+
+    #[test]
+    fn quaternion_slerp() {
+        let z = [0.0, 0.0, 1.0];
+        let start = Quaternion::default();
+        let end = about(z, 90.0);
+        assert_same_orientation(start.slerp(&end, 0.0), start);
+        assert_same_orientation(start.slerp(&end, 1.0), end);
+        assert_same_orientation(start.slerp(&end, 0.5), about(z, 45.0));
+        assert_same_orientation(start.slerp(&end, 0.25), about(z, 22.5));
+        // The negation is the same orientation, and takes the same, short
+        // path rather than the long way around
+        let negated = Quaternion(end.0.map(|q| -q));
+        assert_same_orientation(start.slerp(&negated, 0.5), about(z, 45.0));
+        // Nearly parallel orientations are interpolated linearly
+        let near = about(z, 1.0);
+        let halfway = start.slerp(&near, 0.5);
+        assert_close(halfway.to_euler_angles().yaw, 0.5f64.to_radians());
+        assert_close(halfway.0.iter().map(|q| q * q).sum::<f64>(), 1.0);
+    }
+}
@@ -1,35 +1,428 @@
+use crate::bus::{self, RegisterAccess};
+use crate::imu;
+use crate::lis3mdl;
+use crate::lsm6dsl;
 use i2cdev::core::*;
 #[cfg(any(target_os = "linux", target_os = "android"))]
 use i2cdev::linux::{LinuxI2CDevice, LinuxI2CError};
 use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
 
-fn init<D: I2CDevice>(
-    dev: &mut D,
-    who_am_i: u8,
-    expected_response: u8,
-) -> Result<(), crate::Error<D::Error>> {
-    let who_am_i_response = dev.smbus_read_byte_data(who_am_i)?;
-    if who_am_i_response == expected_response {
-        Ok(())
-    } else {
-        Err(crate::Error::Init)
+#[cfg(feature = "bmp280")]
+mod bmp280;
+pub mod lsm9ds0;
+pub mod lsm9ds1;
+
+#[cfg(feature = "bmp280")]
+pub use self::bmp280::Barometer;
+
+/// The slave addresses the LSM6DSL may answer at, in the order they're
+/// probed.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const LSM6DSL_ADDRESSES: [u16; 2] = [
+    crate::Lsm6dslAddress::Low as u16,
+    crate::Lsm6dslAddress::High as u16,
+];
+
+/// The slave addresses the LIS3MDL may answer at, in the order they're
+/// probed.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const LIS3MDL_ADDRESSES: [u16; 2] = [
+    crate::Lis3mdlAddress::Low as u16,
+    crate::Lis3mdlAddress::High as u16,
+];
+
+/// Opens a device at each of the given slave addresses in turn, creating a
+/// reader at the first one whose WHO_AM_I register identifies the chip.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn probe<T>(
+    addr: &Path,
+    chip: crate::Chip,
+    addresses: [u16; 2],
+    new: impl FnOnce(LinuxI2CDevice) -> Result<T, crate::Error<LinuxI2CError>>,
+) -> Result<T, crate::Error<LinuxI2CError>> {
+    let open = |address| Ok(LinuxI2CDevice::new(addr, address)?);
+    probe_with(chip, addresses, open, new)
+}
+
+/// Like `probe`, but opens each device with `open`. Fails with
+/// `Error::NotFound` holding the outcome of probing each address.
+#[cfg(any(test, target_os = "linux", target_os = "android"))]
+fn probe_with<D: I2CDevice, T>(
+    chip: crate::Chip,
+    addresses: [u16; 2],
+    mut open: impl FnMut(u16) -> Result<D, crate::Error<D::Error>>,
+    new: impl FnOnce(D) -> Result<T, crate::Error<D::Error>>,
+) -> Result<T, crate::Error<D::Error>> {
+    let mut probes = addresses.map(|address| crate::Probe {
+        address,
+        who_am_i: None,
+    });
+    for probe in &mut probes {
+        let mut dev = open(probe.address)?;
+        probe.who_am_i = dev.smbus_read_byte_data(chip.who_am_i_register()).ok();
+        if probe
+            .who_am_i
+            .is_some_and(|id| chip.who_am_i().contains(&id))
+        {
+            return new(dev);
+        }
     }
+    Err(crate::Error::NotFound(probes))
+}
+
+/// The slave addresses probed while detecting the board. The accelerometer
+/// & gyroscope sit at 0x6A or 0x6B on every version, while the magnetometer
+/// sits at 0x1C or 0x1E, or 0x1D or 0x1E alongside the BerryIMUv1's
+/// accelerometer.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const DETECT_ADDRESSES: [u16; 5] = [0x6A, 0x6B, 0x1C, 0x1D, 0x1E];
+
+/// The board detected on an I2C bus by `detect`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Detection {
+    /// The board version.
+    pub version: crate::BoardVersion,
+    /// The outcome of probing each address.
+    pub probes: [crate::Probe; 5],
 }
 
-fn read_block<D: I2CDevice>(
-    dev: &mut D,
-    command: u8,
-    size: u8,
-) -> Result<Vec<u8>, crate::Error<D::Error>> {
-    let block = dev.smbus_read_i2c_block_data(command, size)?;
-    if block.len() != size as usize {
-        return Err(crate::Error::Read);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Detection {
+    /// The addresses that responded.
+    pub fn responded(&self) -> impl Iterator<Item = u16> + '_ {
+        self.probes
+            .iter()
+            .filter(|probe| probe.who_am_i.is_some())
+            .map(|probe| probe.address)
+    }
+
+    fn found(
+        &self,
+        version: crate::BoardVersion,
+        addresses: &[u16],
+        chip: crate::Chip,
+    ) -> Option<u16> {
+        if self.version != version {
+            return None;
+        }
+        self.probes
+            .iter()
+            .find(|probe| {
+                addresses.contains(&probe.address)
+                    && probe
+                        .who_am_i
+                        .is_some_and(|id| chip.who_am_i().contains(&id))
+            })
+            .map(|probe| probe.address)
+    }
+
+    /// The LSM6DSL's slave address, for use with e.g.
+    /// `Accelerometer::new_from_address_with_slave`, or `None` if this isn't
+    /// a BerryIMUv3.
+    pub fn lsm6dsl_address(&self) -> Option<crate::Lsm6dslAddress> {
+        match self.found(
+            crate::BoardVersion::V3,
+            &LSM6DSL_ADDRESSES,
+            crate::Chip::Lsm6dsl,
+        )? {
+            0x6B => Some(crate::Lsm6dslAddress::High),
+            _ => Some(crate::Lsm6dslAddress::Low),
+        }
+    }
+
+    /// The LIS3MDL's slave address, for use with e.g.
+    /// `Magnetometer::new_from_address_with_slave`, or `None` if this isn't
+    /// a BerryIMUv3.
+    pub fn lis3mdl_address(&self) -> Option<crate::Lis3mdlAddress> {
+        match self.found(
+            crate::BoardVersion::V3,
+            &LIS3MDL_ADDRESSES,
+            crate::Chip::Lis3mdl,
+        )? {
+            0x1E => Some(crate::Lis3mdlAddress::High),
+            _ => Some(crate::Lis3mdlAddress::Low),
+        }
+    }
+
+    /// The slave address of the LSM9DS1's accelerometer & gyroscope, for use
+    /// with e.g. `lsm9ds1::Accelerometer::new`, or `None` if this isn't a
+    /// BerryIMUv2.
+    pub fn lsm9ds1_accel_gyro_address(&self) -> Option<u16> {
+        self.found(
+            crate::BoardVersion::V2,
+            &[0x6A, 0x6B],
+            crate::Chip::Lsm9ds1AccelGyro,
+        )
+    }
+
+    /// The slave address of the LSM9DS1's magnetometer, or `None` if this
+    /// isn't a BerryIMUv2.
+    pub fn lsm9ds1_magnetometer_address(&self) -> Option<u16> {
+        self.found(
+            crate::BoardVersion::V2,
+            &[0x1C, 0x1E],
+            crate::Chip::Lsm9ds1Mag,
+        )
+    }
+
+    /// The slave address of the LSM9DS0's gyroscope, for use with e.g.
+    /// `lsm9ds0::Gyroscope::new`, or `None` if this isn't a BerryIMUv1.
+    pub fn lsm9ds0_gyro_address(&self) -> Option<u16> {
+        self.found(
+            crate::BoardVersion::V1,
+            &[0x6A, 0x6B],
+            crate::Chip::Lsm9ds0Gyro,
+        )
+    }
+
+    /// The slave address of the LSM9DS0's accelerometer & magnetometer, or
+    /// `None` if this isn't a BerryIMUv1.
+    pub fn lsm9ds0_accel_mag_address(&self) -> Option<u16> {
+        self.found(
+            crate::BoardVersion::V1,
+            &[0x1D, 0x1E],
+            crate::Chip::Lsm9ds0AccelMag,
+        )
+    }
+
+    /// Creates new LSM9DS1 readers for the detected board.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address that was probed, e.g. `/dev/i2c-1`.
+    pub fn lsm9ds1<P: AsRef<Path>>(
+        &self,
+        addr: P,
+    ) -> Result<lsm9ds1::Readers<LinuxI2CDevice>, crate::Error<LinuxI2CError>> {
+        let (Some(accel_gyro_address), Some(magnetometer_address)) = (
+            self.lsm9ds1_accel_gyro_address(),
+            self.lsm9ds1_magnetometer_address(),
+        ) else {
+            return Err(crate::Error::InvalidConfig(
+                "only the BerryIMUv2 has an LSM9DS1",
+            ));
+        };
+        let addr = addr.as_ref();
+        Ok(lsm9ds1::Readers {
+            accelerometer: lsm9ds1::Accelerometer::new(LinuxI2CDevice::new(
+                addr,
+                accel_gyro_address,
+            )?)?,
+            gyroscope: lsm9ds1::Gyroscope::new(LinuxI2CDevice::new(addr, accel_gyro_address)?)?,
+            magnetometer: lsm9ds1::Magnetometer::new(LinuxI2CDevice::new(
+                addr,
+                magnetometer_address,
+            )?)?,
+        })
+    }
+
+    /// Creates new LSM9DS0 readers for the detected board.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address that was probed, e.g. `/dev/i2c-1`.
+    pub fn lsm9ds0<P: AsRef<Path>>(
+        &self,
+        addr: P,
+    ) -> Result<lsm9ds0::Readers<LinuxI2CDevice>, crate::Error<LinuxI2CError>> {
+        let (Some(gyro_address), Some(accel_mag_address)) = (
+            self.lsm9ds0_gyro_address(),
+            self.lsm9ds0_accel_mag_address(),
+        ) else {
+            return Err(crate::Error::InvalidConfig(
+                "only the BerryIMUv1 has an LSM9DS0",
+            ));
+        };
+        let addr = addr.as_ref();
+        Ok(lsm9ds0::Readers {
+            accelerometer: lsm9ds0::Accelerometer::new(LinuxI2CDevice::new(
+                addr,
+                accel_mag_address,
+            )?)?,
+            gyroscope: lsm9ds0::Gyroscope::new(LinuxI2CDevice::new(addr, gyro_address)?)?,
+            magnetometer: lsm9ds0::Magnetometer::new(LinuxI2CDevice::new(
+                addr,
+                accel_mag_address,
+            )?)?,
+        })
+    }
+
+    /// Creates a new IMU reader for the detected board. Only the BerryIMUv3
+    /// has one; use `lsm9ds1` or `lsm9ds0` for the older boards.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address that was probed, e.g. `/dev/i2c-1`.
+    pub fn imu<P: AsRef<Path>>(
+        &self,
+        addr: P,
+    ) -> Result<Imu<LinuxI2CDevice>, crate::Error<LinuxI2CError>> {
+        let (Some(lsm6dsl_address), Some(lis3mdl_address)) =
+            (self.lsm6dsl_address(), self.lis3mdl_address())
+        else {
+            return Err(crate::Error::InvalidConfig(
+                "only the BerryIMUv3 has an IMU reader",
+            ));
+        };
+        let addr = addr.as_ref();
+        Ok(Imu::from_readers(
+            Lsm6dsl::new_from_address_with_slave(addr, lsm6dsl_address)?,
+            Magnetometer::new_from_address_with_slave(addr, lis3mdl_address)?,
+        ))
+    }
+}
+
+/// Detects which version of the BerryIMU is on an I2C bus, by reading the
+/// WHO_AM_I register at each of the addresses its chips may answer at.
+///
+/// # Arguments
+/// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn detect<P: AsRef<Path>>(addr: P) -> Result<Detection, crate::Error<LinuxI2CError>> {
+    let mut probes = DETECT_ADDRESSES.map(|address| crate::Probe {
+        address,
+        who_am_i: None,
+    });
+    for probe in &mut probes {
+        let mut dev = LinuxI2CDevice::new(addr.as_ref(), probe.address)?;
+        // WHO_AM_I is at 0x0F on every chip
+        probe.who_am_i = dev.smbus_read_byte_data(0x0F).ok();
+    }
+
+    // Each version is told apart by the WHO_AM_I value of the chip at 0x6A
+    // or 0x6B: the LSM6DSL or a compatible chip, the LSM9DS1's accelerometer
+    // & gyroscope, or the LSM9DS0's gyroscope.
+    let version = probes[..2].iter().find_map(|probe| match probe.who_am_i? {
+        0x6A | 0x6C | 0x69 => Some(crate::BoardVersion::V3),
+        0x68 => Some(crate::BoardVersion::V2),
+        0xD4 => Some(crate::BoardVersion::V1),
+        _ => None,
+    });
+    match version {
+        Some(version) => Ok(Detection { version, probes }),
+        None => Err(crate::Error::UnknownBoard(probes)),
+    }
+}
+
+/// Register access to a chip through an I2C device.
+pub struct I2cInterface<D: I2CDevice> {
+    dev: Device<D>,
+    verify_writes: bool,
+}
+
+enum Device<D: I2CDevice> {
+    Owned(D),
+    // With a read timeout, each transaction runs on a helper thread
+    Timed(Box<dyn TimedDevice<D> + Send>),
+}
+
+/// A transaction that can be run on a helper thread. Each returns the bytes
+/// it read, if any.
+type Transaction<D> =
+    Box<dyn FnOnce(&mut D) -> Result<Vec<u8>, crate::Error<<D as I2CDevice>::Error>> + Send>;
+
+/// Runs transactions with a timeout. This hides the bounds needed to move a
+/// device to a helper thread, so that only constructors that take a timeout
+/// require them.
+trait TimedDevice<D: I2CDevice> {
+    fn run(&self, reg: u8, transaction: Transaction<D>) -> Result<Vec<u8>, crate::Error<D::Error>>;
+}
+
+struct Timeout<D> {
+    dev: Arc<Mutex<D>>,
+    timeout: Duration,
+}
+
+impl<D> TimedDevice<D> for Timeout<D>
+where
+    D: I2CDevice + Send + 'static,
+    D::Error: Send + 'static,
+{
+    fn run(&self, reg: u8, transaction: Transaction<D>) -> Result<Vec<u8>, crate::Error<D::Error>> {
+        bus::with_timeout(&self.dev, reg, Some(self.timeout), transaction)
+    }
+}
+
+impl<D: I2CDevice> I2cInterface<D> {
+    fn new(dev: D) -> Self {
+        Self {
+            dev: Device::Owned(dev),
+            verify_writes: false,
+        }
+    }
+
+    fn transact<F>(&mut self, reg: u8, f: F) -> Result<Vec<u8>, crate::Error<D::Error>>
+    where
+        F: FnOnce(&mut D) -> Result<Vec<u8>, crate::Error<D::Error>> + Send + 'static,
+    {
+        match &mut self.dev {
+            Device::Owned(dev) => f(dev),
+            Device::Timed(dev) => dev.run(reg, Box::new(f)),
+        }
+    }
+}
+
+impl<D> I2cInterface<D>
+where
+    D: I2CDevice + Send + 'static,
+    D::Error: Send + 'static,
+{
+    fn with_options(dev: D, options: crate::SensorOptions) -> Self {
+        let dev = match options.read_timeout {
+            Some(timeout) => Device::Timed(Box::new(Timeout {
+                dev: Arc::new(Mutex::new(dev)),
+                timeout,
+            })),
+            None => Device::Owned(dev),
+        };
+        Self {
+            dev,
+            verify_writes: options.verify_writes,
+        }
+    }
+}
+
+impl<D: I2CDevice> RegisterAccess for I2cInterface<D> {
+    type Error = D::Error;
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<D::Error>> {
+        let value = self.transact(reg, move |dev| {
+            dev.smbus_read_byte_data(reg)
+                .map(|value| vec![value])
+                .map_err(crate::Error::read(reg))
+        })?;
+        Ok(value[0])
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<D::Error>> {
+        self.transact(reg, move |dev| {
+            dev.smbus_write_byte_data(reg, value)
+                .map(|()| Vec::new())
+                .map_err(crate::Error::write(reg))
+        })?;
+        Ok(())
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), crate::Error<D::Error>> {
+        let len = buf.len() as u8;
+        let block = self.transact(reg, move |dev| {
+            dev.smbus_read_i2c_block_data(reg, len)
+                .map_err(crate::Error::read(reg))
+        })?;
+        if block.len() != buf.len() {
+            return Err(crate::Error::Read { reg, cause: None });
+        }
+        buf.copy_from_slice(&block);
+        Ok(())
+    }
+
+    fn verify_writes(&self) -> bool {
+        self.verify_writes
     }
-    Ok(block)
 }
 
 /// An accelerometer reader.
-pub struct Accelerometer<D: I2CDevice>(D);
+pub type Accelerometer<D> = lsm6dsl::Accelerometer<I2cInterface<D>>;
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
 impl Accelerometer<LinuxI2CDevice> {
@@ -38,9 +431,48 @@ impl Accelerometer<LinuxI2CDevice> {
     /// # Arguments
     /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
     pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<LinuxI2CError>> {
-        let dev = LinuxI2CDevice::new(addr, crate::LSM6DSL_ADDRESS)?;
+        Accelerometer::new_from_address_with_slave(addr, crate::Lsm6dslAddress::default())
+    }
+
+    /// Creates a new accelerometer reader from an address, with the LSM6DSL at the
+    /// given slave address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    /// * `slave`: The LSM6DSL's slave address.
+    pub fn new_from_address_with_slave<P: AsRef<Path>>(
+        addr: P,
+        slave: crate::Lsm6dslAddress,
+    ) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let dev = LinuxI2CDevice::new(addr, slave as u16)?;
         Accelerometer::new(dev)
     }
+
+    /// Creates a new accelerometer reader from an address, probing each of the
+    /// LSM6DSL's slave addresses in turn.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address_probing<P: AsRef<Path>>(
+        addr: P,
+    ) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let chip = crate::Chip::Lsm6dsl;
+        probe(addr.as_ref(), chip, LSM6DSL_ADDRESSES, Accelerometer::new)
+    }
+}
+
+impl<D: SlaveAddress> Accelerometer<SharedDevice<D>> {
+    /// Creates a new accelerometer reader from a shared I2C bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The shared I2C bus.
+    /// * `address`: The chip's slave address on the bus.
+    pub fn new_from_shared_bus(
+        bus: &SharedBus<D>,
+        address: u16,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Accelerometer::new(bus.device(address))
+    }
 }
 
 impl<D: I2CDevice> Accelerometer<D> {
@@ -48,27 +480,306 @@ impl<D: I2CDevice> Accelerometer<D> {
     ///
     /// # Arguments
     /// * `dev`: The I2C device.
-    pub fn new(mut dev: D) -> Result<Self, crate::Error<D::Error>> {
-        init(&mut dev, crate::LSM6DSL_WHO_AM_I, 0x6A)?;
-        dev.smbus_write_byte_data(crate::LSM6DSL_CTRL1_XL, 0b10011111)?; // ODR 3.33 kHz, +/- 8g , BW = 400hz
-        dev.smbus_write_byte_data(crate::LSM6DSL_CTRL8_XL, 0b11001000)?; // Low pass filter enabled, BW9, composite filter
-        dev.smbus_write_byte_data(crate::LSM6DSL_CTRL3_C, 0b01000100)?; // Enable Block Data update, increment during multi byte read
-        Ok(Self(dev))
+    pub fn new(dev: D) -> Result<Self, crate::Error<D::Error>> {
+        Accelerometer::new_with_config(dev, crate::AccelConfig::default())
+    }
+
+    /// Creates a new accelerometer reader from an I2C device, with the
+    /// given configuration.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `config`: The accelerometer configuration.
+    pub fn new_with_config(
+        dev: D,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Accelerometer::from_bus(I2cInterface::new(dev), config)
+    }
+
+    /// Creates a new accelerometer reader from an I2C device, without
+    /// checking the chip's WHO_AM_I register, for clones that report an
+    /// unrecognized value.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `config`: The accelerometer configuration.
+    /// * `variant`: The chip to configure the clone as.
+    pub fn new_with_variant(
+        dev: D,
+        config: crate::AccelConfig,
+        variant: lsm6dsl::Variant,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        let interface = I2cInterface::new(dev);
+        Accelerometer::from_bus_with_variant(interface, config, variant)
+    }
+}
+
+impl<D> Accelerometer<D>
+where
+    D: I2CDevice + Send + 'static,
+    D::Error: Send + 'static,
+{
+    /// Creates a new accelerometer reader from an I2C device, with the given
+    /// configuration and access options.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `config`: The accelerometer configuration.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: D,
+        config: crate::AccelConfig,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Accelerometer::from_bus(I2cInterface::with_options(dev, options), config)
+    }
+}
+
+/// A gyroscope reader.
+pub type Gyroscope<D> = lsm6dsl::Gyroscope<I2cInterface<D>>;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Gyroscope<LinuxI2CDevice> {
+    /// Creates a new gyroscope reader from an address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<LinuxI2CError>> {
+        Gyroscope::new_from_address_with_slave(addr, crate::Lsm6dslAddress::default())
+    }
+
+    /// Creates a new gyroscope reader from an address, with the LSM6DSL at the
+    /// given slave address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    /// * `slave`: The LSM6DSL's slave address.
+    pub fn new_from_address_with_slave<P: AsRef<Path>>(
+        addr: P,
+        slave: crate::Lsm6dslAddress,
+    ) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let dev = LinuxI2CDevice::new(addr, slave as u16)?;
+        Gyroscope::new(dev)
+    }
+
+    /// Creates a new gyroscope reader from an address, probing each of the
+    /// LSM6DSL's slave addresses in turn.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address_probing<P: AsRef<Path>>(
+        addr: P,
+    ) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let chip = crate::Chip::Lsm6dsl;
+        probe(addr.as_ref(), chip, LSM6DSL_ADDRESSES, Gyroscope::new)
+    }
+}
+
+impl<D: SlaveAddress> Gyroscope<SharedDevice<D>> {
+    /// Creates a new gyroscope reader from a shared I2C bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The shared I2C bus.
+    /// * `address`: The chip's slave address on the bus.
+    pub fn new_from_shared_bus(
+        bus: &SharedBus<D>,
+        address: u16,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Gyroscope::new(bus.device(address))
+    }
+}
+
+impl<D: I2CDevice> Gyroscope<D> {
+    /// Creates a new gyroscope reader from an I2C device.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    pub fn new(dev: D) -> Result<Self, crate::Error<D::Error>> {
+        Gyroscope::new_with_config(dev, crate::GyroConfig::default())
+    }
+
+    /// Creates a new gyroscope reader from an I2C device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `config`: The gyroscope configuration.
+    pub fn new_with_config(
+        dev: D,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Gyroscope::from_bus(I2cInterface::new(dev), config)
+    }
+
+    /// Creates a new gyroscope reader from an I2C device, without checking
+    /// the chip's WHO_AM_I register, for clones that report an unrecognized
+    /// value.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `config`: The gyroscope configuration.
+    /// * `variant`: The chip to configure the clone as.
+    pub fn new_with_variant(
+        dev: D,
+        config: crate::GyroConfig,
+        variant: lsm6dsl::Variant,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        let interface = I2cInterface::new(dev);
+        Gyroscope::from_bus_with_variant(interface, config, variant)
+    }
+}
+
+impl<D> Gyroscope<D>
+where
+    D: I2CDevice + Send + 'static,
+    D::Error: Send + 'static,
+{
+    /// Creates a new gyroscope reader from an I2C device, with the given
+    /// configuration and access options.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `config`: The gyroscope configuration.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: D,
+        config: crate::GyroConfig,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Gyroscope::from_bus(I2cInterface::with_options(dev, options), config)
+    }
+}
+
+/// A reader for both the accelerometer and gyroscope, which share the
+/// LSM6DSL chip.
+pub type Lsm6dsl<D> = lsm6dsl::Lsm6dsl<I2cInterface<D>>;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Lsm6dsl<LinuxI2CDevice> {
+    /// Creates a new LSM6DSL reader from an address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<LinuxI2CError>> {
+        Lsm6dsl::new_from_address_with_slave(addr, crate::Lsm6dslAddress::default())
+    }
+
+    /// Creates a new LSM6DSL reader from an address, with the LSM6DSL at the
+    /// given slave address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    /// * `slave`: The LSM6DSL's slave address.
+    pub fn new_from_address_with_slave<P: AsRef<Path>>(
+        addr: P,
+        slave: crate::Lsm6dslAddress,
+    ) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let dev = LinuxI2CDevice::new(addr, slave as u16)?;
+        Lsm6dsl::new(dev)
     }
 
-    /// Read the raw accelerometer values.
-    pub fn read(&mut self) -> Result<(i32, i32, i32), crate::Error<D::Error>> {
-        let block = read_block(&mut self.0, crate::LSM6DSL_OUTX_L_XL, 6)?;
-        // Combine readings for each axis
-        let x = ((block[0] as i16) | (block[1] as i16) << 8) as i32;
-        let y = ((block[2] as i16) | (block[3] as i16) << 8) as i32;
-        let z = ((block[4] as i16) | (block[5] as i16) << 8) as i32;
-        Ok((x, y, z))
+    /// Creates a new LSM6DSL reader from an address, probing each of the
+    /// LSM6DSL's slave addresses in turn.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address_probing<P: AsRef<Path>>(
+        addr: P,
+    ) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let chip = crate::Chip::Lsm6dsl;
+        probe(addr.as_ref(), chip, LSM6DSL_ADDRESSES, Lsm6dsl::new)
+    }
+}
+
+impl<D: SlaveAddress> Lsm6dsl<SharedDevice<D>> {
+    /// Creates a new LSM6DSL reader from a shared I2C bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The shared I2C bus.
+    /// * `address`: The chip's slave address on the bus.
+    pub fn new_from_shared_bus(
+        bus: &SharedBus<D>,
+        address: u16,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Lsm6dsl::new(bus.device(address))
+    }
+}
+
+impl<D: I2CDevice> Lsm6dsl<D> {
+    /// Creates a new LSM6DSL reader from an I2C device.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    pub fn new(dev: D) -> Result<Self, crate::Error<D::Error>> {
+        Lsm6dsl::new_with_config(
+            dev,
+            crate::AccelConfig::default(),
+            crate::GyroConfig::default(),
+        )
+    }
+
+    /// Creates a new LSM6DSL reader from an I2C device, with the given
+    /// accelerometer and gyroscope configurations.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    pub fn new_with_config(
+        dev: D,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Lsm6dsl::from_bus(I2cInterface::new(dev), accel_config, gyro_config)
+    }
+
+    /// Creates a new LSM6DSL reader from an I2C device, without checking the
+    /// chip's WHO_AM_I register, for clones that report an unrecognized
+    /// value.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    /// * `variant`: The chip to configure the clone as.
+    pub fn new_with_variant(
+        dev: D,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+        variant: lsm6dsl::Variant,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        let interface = I2cInterface::new(dev);
+        Lsm6dsl::from_bus_with_variant(interface, accel_config, gyro_config, variant)
+    }
+}
+
+impl<D> Lsm6dsl<D>
+where
+    D: I2CDevice + Send + 'static,
+    D::Error: Send + 'static,
+{
+    /// Creates a new LSM6DSL reader from an I2C device, with the given
+    /// accelerometer and gyroscope configurations and access options.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: D,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        let interface = I2cInterface::with_options(dev, options);
+        Lsm6dsl::from_bus(interface, accel_config, gyro_config)
     }
 }
 
 /// A magnetometer reader.
-pub struct Magnetometer<D: I2CDevice>(D);
+pub type Magnetometer<D> = lis3mdl::Magnetometer<I2cInterface<D>>;
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
 impl Magnetometer<LinuxI2CDevice> {
@@ -77,9 +788,48 @@ impl Magnetometer<LinuxI2CDevice> {
     /// # Arguments
     /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
     pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<LinuxI2CError>> {
-        let dev = LinuxI2CDevice::new(addr, crate::LIS3MDL_ADDRESS)?;
+        Magnetometer::new_from_address_with_slave(addr, crate::Lis3mdlAddress::default())
+    }
+
+    /// Creates a new magnetometer reader from an address, with the LIS3MDL at the
+    /// given slave address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    /// * `slave`: The LIS3MDL's slave address.
+    pub fn new_from_address_with_slave<P: AsRef<Path>>(
+        addr: P,
+        slave: crate::Lis3mdlAddress,
+    ) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let dev = LinuxI2CDevice::new(addr, slave as u16)?;
         Magnetometer::new(dev)
     }
+
+    /// Creates a new magnetometer reader from an address, probing each of the
+    /// LIS3MDL's slave addresses in turn.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address_probing<P: AsRef<Path>>(
+        addr: P,
+    ) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let chip = crate::Chip::Lis3mdl;
+        probe(addr.as_ref(), chip, LIS3MDL_ADDRESSES, Magnetometer::new)
+    }
+}
+
+impl<D: SlaveAddress> Magnetometer<SharedDevice<D>> {
+    /// Creates a new magnetometer reader from a shared I2C bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The shared I2C bus.
+    /// * `address`: The chip's slave address on the bus.
+    pub fn new_from_shared_bus(
+        bus: &SharedBus<D>,
+        address: u16,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Magnetometer::new(bus.device(address))
+    }
 }
 
 impl<D: I2CDevice> Magnetometer<D> {
@@ -87,22 +837,617 @@ impl<D: I2CDevice> Magnetometer<D> {
     ///
     /// # Arguments
     /// * `dev`: The I2C device.
-    pub fn new(mut dev: D) -> Result<Self, crate::Error<D::Error>> {
-        init(&mut dev, crate::LIS3MDL_WHO_AM_I, 0x3D)?;
-        // Enable the magnetometer
-        dev.smbus_write_byte_data(crate::LIS3MDL_CTRL_REG1, 0b11011100)?; // Temp sensor enabled, High performance, ODR 80 Hz, FAST ODR disabled and Selft test disabled.
-        dev.smbus_write_byte_data(crate::LIS3MDL_CTRL_REG2, 0b00100000)?; // +/- 8 gauss
-        dev.smbus_write_byte_data(crate::LIS3MDL_CTRL_REG3, 0b00000000)?; // Continuous-conversion mode
-        Ok(Self(dev))
+    pub fn new(dev: D) -> Result<Self, crate::Error<D::Error>> {
+        Magnetometer::new_with_config(dev, crate::MagConfig::default())
+    }
+
+    /// Creates a new magnetometer reader from an I2C device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `config`: The magnetometer configuration.
+    pub fn new_with_config(
+        dev: D,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Magnetometer::from_bus(I2cInterface::new(dev), config)
+    }
+}
+
+impl<D> Magnetometer<D>
+where
+    D: I2CDevice + Send + 'static,
+    D::Error: Send + 'static,
+{
+    /// Creates a new magnetometer reader from an I2C device, with the given
+    /// configuration and access options.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `config`: The magnetometer configuration.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: D,
+        config: crate::MagConfig,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Magnetometer::from_bus(I2cInterface::with_options(dev, options), config)
+    }
+}
+
+/// A reader for all sensors.
+pub type Imu<D> = imu::Imu<I2cInterface<D>>;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Imu<LinuxI2CDevice> {
+    /// Creates a new IMU reader from an address, opening a device for each
+    /// chip.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let lsm6dsl = Lsm6dsl::new_from_address(addr.as_ref())?;
+        let magnetometer = Magnetometer::new_from_address(addr)?;
+        Ok(Imu::from_readers(lsm6dsl, magnetometer))
+    }
+}
+
+impl<D: SlaveAddress> Imu<SharedDevice<D>> {
+    /// Creates a new IMU reader from a shared I2C bus, with the chips at
+    /// their default slave addresses.
+    ///
+    /// # Arguments
+    /// * `bus`: The shared I2C bus.
+    pub fn new_from_shared_bus(bus: &SharedBus<D>) -> Result<Self, crate::Error<D::Error>> {
+        Imu::new(
+            bus.device(crate::Chip::Lsm6dsl.i2c_address()),
+            bus.device(crate::Chip::Lis3mdl.i2c_address()),
+        )
+    }
+}
+
+impl<D: I2CDevice> Imu<D> {
+    /// Creates a new IMU reader from an I2C device for each chip.
+    ///
+    /// # Arguments
+    /// * `lsm6dsl_dev`: The I2C device for the accelerometer & gyroscope.
+    /// * `lis3mdl_dev`: The I2C device for the magnetometer.
+    pub fn new(lsm6dsl_dev: D, lis3mdl_dev: D) -> Result<Self, crate::Error<D::Error>> {
+        Ok(Imu::from_readers(
+            Lsm6dsl::new(lsm6dsl_dev)?,
+            Magnetometer::new(lis3mdl_dev)?,
+        ))
+    }
+
+    /// Creates a new IMU reader from an I2C device for each chip, configuring
+    /// the sensors with a preset profile.
+    ///
+    /// # Arguments
+    /// * `lsm6dsl_dev`: The I2C device for the accelerometer & gyroscope.
+    /// * `lis3mdl_dev`: The I2C device for the magnetometer.
+    /// * `profile`: The preset configuration of all sensors.
+    pub fn new_with_profile(
+        lsm6dsl_dev: D,
+        lis3mdl_dev: D,
+        profile: crate::Profile,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Imu::with_profile(
+            I2cInterface::new(lsm6dsl_dev),
+            I2cInterface::new(lis3mdl_dev),
+            profile,
+        )
+    }
+}
+
+/// An accelerometer reader that runs on its own thread, for use with tokio.
+/// Create it with `AsyncAccelerometer::new(accelerometer)`.
+#[cfg(feature = "tokio")]
+pub type AsyncAccelerometer<D> = crate::worker::Worker<Accelerometer<D>>;
+
+/// A gyroscope reader that runs on its own thread, for use with tokio.
+/// Create it with `AsyncGyroscope::new(gyroscope)`.
+#[cfg(feature = "tokio")]
+pub type AsyncGyroscope<D> = crate::worker::Worker<Gyroscope<D>>;
+
+/// A magnetometer reader that runs on its own thread, for use with tokio.
+/// Create it with `AsyncMagnetometer::new(magnetometer)`.
+#[cfg(feature = "tokio")]
+pub type AsyncMagnetometer<D> = crate::worker::Worker<Magnetometer<D>>;
+
+/// An I2C device whose slave address can be changed after it's been opened,
+/// allowing it to be shared between chips via `SharedBus`.
+pub trait SlaveAddress: I2CDevice {
+    /// Sets the slave address that subsequent transactions are sent to.
+    fn set_slave_address(&mut self, address: u16) -> Result<(), Self::Error>;
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl SlaveAddress for LinuxI2CDevice {
+    fn set_slave_address(&mut self, address: u16) -> Result<(), LinuxI2CError> {
+        LinuxI2CDevice::set_slave_address(self, address)
+    }
+}
+
+/// An I2C bus that's shared between readers, e.g. so that both the
+/// accelerometer and magnetometer multiplex a single file descriptor, and
+/// access to the bus is guarded by a single lock. Cloning the bus yields
+/// another handle to the same device.
+pub struct SharedBus<D: SlaveAddress>(Arc<Mutex<D>>);
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl SharedBus<LinuxI2CDevice> {
+    /// Creates a new shared bus from an address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<LinuxI2CError>> {
+        // The slave address is selected before each transaction, so the
+        // initial one doesn't matter.
+        let dev = LinuxI2CDevice::new(addr, crate::Chip::Lsm6dsl.i2c_address())?;
+        Ok(SharedBus::new(dev))
+    }
+}
+
+impl<D: SlaveAddress> SharedBus<D> {
+    /// Creates a new shared bus from an I2C device.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    pub fn new(dev: D) -> Self {
+        Self::from(Arc::new(Mutex::new(dev)))
+    }
+
+    /// Gets a handle to the device at a slave address on this bus.
+    ///
+    /// # Arguments
+    /// * `address`: The slave address.
+    pub fn device(&self, address: u16) -> SharedDevice<D> {
+        SharedDevice {
+            bus: self.0.clone(),
+            address,
+        }
+    }
+}
+
+impl<D: SlaveAddress> Clone for SharedBus<D> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<D: SlaveAddress> From<Arc<Mutex<D>>> for SharedBus<D> {
+    fn from(bus: Arc<Mutex<D>>) -> Self {
+        Self(bus)
+    }
+}
+
+/// A device at a given slave address on a `SharedBus`. Each transaction
+/// locks the bus and selects the slave address before proceeding.
+pub struct SharedDevice<D: SlaveAddress> {
+    bus: Arc<Mutex<D>>,
+    address: u16,
+}
+
+impl<D: SlaveAddress> SharedDevice<D> {
+    fn with<T, F>(&mut self, f: F) -> Result<T, D::Error>
+    where
+        F: FnOnce(&mut D) -> Result<T, D::Error>,
+    {
+        // A panic while holding the lock can't leave the device in an
+        // inconsistent state, since the slave address is always re-selected.
+        let mut dev = self.bus.lock().unwrap_or_else(PoisonError::into_inner);
+        dev.set_slave_address(self.address)?;
+        f(&mut dev)
+    }
+}
+
+impl<D: SlaveAddress> I2CDevice for SharedDevice<D> {
+    type Error = D::Error;
+
+    fn read(&mut self, data: &mut [u8]) -> Result<(), D::Error> {
+        self.with(|dev| dev.read(data))
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), D::Error> {
+        self.with(|dev| dev.write(data))
+    }
+
+    fn smbus_write_quick(&mut self, bit: bool) -> Result<(), D::Error> {
+        self.with(|dev| dev.smbus_write_quick(bit))
+    }
+
+    fn smbus_read_byte(&mut self) -> Result<u8, D::Error> {
+        self.with(|dev| dev.smbus_read_byte())
+    }
+
+    fn smbus_write_byte(&mut self, value: u8) -> Result<(), D::Error> {
+        self.with(|dev| dev.smbus_write_byte(value))
+    }
+
+    fn smbus_read_byte_data(&mut self, register: u8) -> Result<u8, D::Error> {
+        self.with(|dev| dev.smbus_read_byte_data(register))
+    }
+
+    fn smbus_write_byte_data(&mut self, register: u8, value: u8) -> Result<(), D::Error> {
+        self.with(|dev| dev.smbus_write_byte_data(register, value))
+    }
+
+    fn smbus_read_word_data(&mut self, register: u8) -> Result<u16, D::Error> {
+        self.with(|dev| dev.smbus_read_word_data(register))
+    }
+
+    fn smbus_write_word_data(&mut self, register: u8, value: u16) -> Result<(), D::Error> {
+        self.with(|dev| dev.smbus_write_word_data(register, value))
+    }
+
+    fn smbus_process_word(&mut self, register: u8, value: u16) -> Result<u16, D::Error> {
+        self.with(|dev| dev.smbus_process_word(register, value))
+    }
+
+    fn smbus_read_block_data(&mut self, register: u8) -> Result<Vec<u8>, D::Error> {
+        self.with(|dev| dev.smbus_read_block_data(register))
+    }
+
+    fn smbus_read_i2c_block_data(&mut self, register: u8, len: u8) -> Result<Vec<u8>, D::Error> {
+        self.with(|dev| dev.smbus_read_i2c_block_data(register, len))
+    }
+
+    fn smbus_write_block_data(&mut self, register: u8, values: &[u8]) -> Result<(), D::Error> {
+        self.with(|dev| dev.smbus_write_block_data(register, values))
+    }
+
+    fn smbus_write_i2c_block_data(&mut self, register: u8, values: &[u8]) -> Result<(), D::Error> {
+        self.with(|dev| dev.smbus_write_i2c_block_data(register, values))
+    }
+
+    fn smbus_process_block(&mut self, register: u8, values: &[u8]) -> Result<Vec<u8>, D::Error> {
+        self.with(|dev| dev.smbus_process_block(register, values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io;
+    use std::thread;
+
+    /// A fake I2C bus with a register map for each chip on it.
+    #[derive(Clone, Default)]
+    struct FakeBus {
+        address: u16,
+        chips: HashMap<u16, [u8; 256]>,
+        // How long each register read takes
+        delay: Duration,
+    }
+
+    impl FakeBus {
+        fn with_chip(mut self, address: u16, registers: &[(u8, &[u8])]) -> Self {
+            let chip = self.chips.entry(address).or_insert([0; 256]);
+            for &(reg, values) in registers {
+                chip[reg as usize..reg as usize + values.len()].copy_from_slice(values);
+            }
+            self
+        }
+
+        fn chip(&mut self) -> io::Result<&mut [u8; 256]> {
+            // A missing chip doesn't acknowledge its address
+            self.chips
+                .get_mut(&self.address)
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        }
+    }
+
+    impl I2CDevice for FakeBus {
+        type Error = io::Error;
+
+        fn read(&mut self, _data: &mut [u8]) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn write(&mut self, _data: &[u8]) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn smbus_write_quick(&mut self, _bit: bool) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn smbus_read_byte_data(&mut self, register: u8) -> io::Result<u8> {
+            thread::sleep(self.delay);
+            Ok(self.chip()?[register as usize])
+        }
+
+        fn smbus_write_byte_data(&mut self, register: u8, value: u8) -> io::Result<()> {
+            self.chip()?[register as usize] = value;
+            Ok(())
+        }
+
+        fn smbus_read_block_data(&mut self, _register: u8) -> io::Result<Vec<u8>> {
+            unimplemented!()
+        }
+
+        fn smbus_read_i2c_block_data(&mut self, register: u8, len: u8) -> io::Result<Vec<u8>> {
+            let start = register as usize;
+            Ok(self.chip()?[start..start + len as usize].to_vec())
+        }
+
+        fn smbus_write_block_data(&mut self, _register: u8, _values: &[u8]) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn smbus_write_i2c_block_data(&mut self, _register: u8, _values: &[u8]) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn smbus_process_block(&mut self, _register: u8, _values: &[u8]) -> io::Result<Vec<u8>> {
+            unimplemented!()
+        }
+    }
+
+    impl SlaveAddress for FakeBus {
+        fn set_slave_address(&mut self, address: u16) -> io::Result<()> {
+            self.address = address;
+            Ok(())
+        }
+    }
+
+    /// A bus with the LSM6DSL and LIS3MDL at their default addresses. The
+    /// accelerometer's and magnetometer's outputs both start at 0x28, each
+    /// on its own chip.
+    fn berryimu() -> FakeBus {
+        FakeBus::default()
+            .with_chip(
+                0x6A,
+                &[
+                    (crate::LSM6DSL_WHO_AM_I, &[0x6A]),
+                    (crate::LSM6DSL_OUTX_L_G, &[4, 0, 5, 0, 6, 0]),
+                    (crate::LSM6DSL_OUTX_L_XL, &[1, 0, 2, 0, 3, 0]),
+                ],
+            )
+            .with_chip(
+                0x1C,
+                &[
+                    (crate::LIS3MDL_WHO_AM_I, &[0x3D]),
+                    (crate::LIS3MDL_OUT_X_L, &[7, 0, 8, 0, 9, 0]),
+                ],
+            )
+    }
+
+    #[test]
+    fn shared_bus_interleaved_reads() {
+        let bus = SharedBus::new(berryimu());
+        let mut accel = Accelerometer::new_from_shared_bus(&bus, 0x6A).unwrap();
+        let mut gyro = Gyroscope::new_from_shared_bus(&bus, 0x6A).unwrap();
+        let mut mag = Magnetometer::new_from_shared_bus(&bus, 0x1C).unwrap();
+        for _ in 0..3 {
+            assert_eq!(accel.read().unwrap(), (1, 2, 3).into());
+            assert_eq!(mag.read().unwrap(), (7, 8, 9).into());
+            assert_eq!(gyro.read().unwrap(), (4, 5, 6).into());
+            assert_eq!(mag.read().unwrap(), (7, 8, 9).into());
+        }
     }
 
-    /// Read the raw magnetometer values.
-    pub fn read(&mut self) -> Result<(i32, i32, i32), crate::Error<D::Error>> {
-        let block = read_block(&mut self.0, crate::LIS3MDL_OUT_X_L, 6)?;
-        // Combine readings for each axis
-        let x = ((block[0] as i16) | (block[1] as i16) << 8) as i32;
-        let y = ((block[2] as i16) | (block[3] as i16) << 8) as i32;
-        let z = ((block[4] as i16) | (block[5] as i16) << 8) as i32;
-        Ok((x, y, z))
+    #[test]
+    fn shared_bus_concurrent_reads() {
+        let bus = SharedBus::new(berryimu());
+        let mut accel = Accelerometer::new_from_shared_bus(&bus, 0x6A).unwrap();
+        let mut mag = Magnetometer::new_from_shared_bus(&bus, 0x1C).unwrap();
+        let accel = thread::spawn(move || {
+            for _ in 0..1000 {
+                assert_eq!(accel.read().unwrap(), (1, 2, 3).into());
+            }
+        });
+        let mag = thread::spawn(move || {
+            for _ in 0..1000 {
+                assert_eq!(mag.read().unwrap(), (7, 8, 9).into());
+            }
+        });
+        accel.join().unwrap();
+        mag.join().unwrap();
+    }
+
+    #[test]
+    fn shared_bus_configures_each_chip() {
+        let bus = SharedBus::new(berryimu());
+        let _imu = Imu::new_from_shared_bus(&bus).unwrap();
+        let dev = bus.0.lock().unwrap();
+        let (lsm6dsl, lis3mdl) = (dev.chips[&0x6A], dev.chips[&0x1C]);
+        assert_eq!(lsm6dsl[crate::LSM6DSL_CTRL1_XL as usize], 0x9F);
+        assert_eq!(lis3mdl[crate::LIS3MDL_CTRL_REG1 as usize], 0xDC);
+        // Each chip's writes must only reach that chip
+        assert_eq!(lsm6dsl[crate::LIS3MDL_CTRL_REG1 as usize], 0);
+        assert_eq!(lis3mdl[crate::LSM6DSL_CTRL1_XL as usize], 0);
+    }
+
+    /// Probes for the LIS3MDL on a bus, returning the addresses opened.
+    fn probe_lis3mdl(
+        bus: FakeBus,
+    ) -> (
+        Result<Magnetometer<FakeBus>, crate::Error<io::Error>>,
+        Vec<u16>,
+    ) {
+        let mut opened = Vec::new();
+        let open = |address| {
+            opened.push(address);
+            let mut dev = bus.clone();
+            dev.address = address;
+            Ok(dev)
+        };
+        let result = probe_with(crate::Chip::Lis3mdl, [0x1C, 0x1E], open, Magnetometer::new);
+        (result, opened)
+    }
+
+    #[test]
+    fn probe_low_address() {
+        let bus = FakeBus::default()
+            .with_chip(
+                0x1C,
+                &[
+                    (crate::LIS3MDL_WHO_AM_I, &[0x3D]),
+                    (crate::LIS3MDL_OUT_X_L, &[1, 0, 2, 0, 3, 0]),
+                ],
+            )
+            .with_chip(
+                0x1E,
+                &[
+                    (crate::LIS3MDL_WHO_AM_I, &[0x3D]),
+                    (crate::LIS3MDL_OUT_X_L, &[7, 0, 8, 0, 9, 0]),
+                ],
+            );
+        let (result, opened) = probe_lis3mdl(bus);
+        assert_eq!(result.unwrap().read().unwrap(), (1, 2, 3).into());
+        assert_eq!(opened, [0x1C]);
+    }
+
+    #[test]
+    fn probe_high_address() {
+        let bus = FakeBus::default().with_chip(
+            0x1E,
+            &[
+                (crate::LIS3MDL_WHO_AM_I, &[0x3D]),
+                (crate::LIS3MDL_OUT_X_L, &[7, 0, 8, 0, 9, 0]),
+            ],
+        );
+        let (result, opened) = probe_lis3mdl(bus);
+        assert_eq!(result.unwrap().read().unwrap(), (7, 8, 9).into());
+        assert_eq!(opened, [0x1C, 0x1E]);
+    }
+
+    #[test]
+    fn probe_not_found() {
+        // Another chip answers at 0x1E
+        let bus = FakeBus::default().with_chip(0x1E, &[(crate::LIS3MDL_WHO_AM_I, &[0x6A])]);
+        let (result, opened) = probe_lis3mdl(bus);
+        let err = result.err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "no device found (0x1c: no response, 0x1e: WHO_AM_I 0x6A)"
+        );
+        assert!(matches!(
+            err,
+            crate::Error::NotFound([
+                crate::Probe {
+                    address: 0x1C,
+                    who_am_i: None
+                },
+                crate::Probe {
+                    address: 0x1E,
+                    who_am_i: Some(0x6A)
+                },
+            ])
+        ));
+        assert_eq!(opened, [0x1C, 0x1E]);
+    }
+
+    /// A detected board whose chips answered with the given WHO_AM_I values
+    /// at each of `DETECT_ADDRESSES`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn detection(version: crate::BoardVersion, who_am_i: [Option<u8>; 5]) -> Detection {
+        let mut probes = DETECT_ADDRESSES.map(|address| crate::Probe {
+            address,
+            who_am_i: None,
+        });
+        for (probe, who_am_i) in probes.iter_mut().zip(who_am_i) {
+            probe.who_am_i = who_am_i;
+        }
+        Detection { version, probes }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn detection_v3_addresses() {
+        let detection = detection(
+            crate::BoardVersion::V3,
+            [None, Some(0x6A), None, None, Some(0x3D)],
+        );
+        assert_eq!(
+            detection.lsm6dsl_address(),
+            Some(crate::Lsm6dslAddress::High)
+        );
+        assert_eq!(
+            detection.lis3mdl_address(),
+            Some(crate::Lis3mdlAddress::High)
+        );
+        assert_eq!(detection.lsm9ds1_accel_gyro_address(), None);
+        assert_eq!(detection.lsm9ds1_magnetometer_address(), None);
+        assert_eq!(detection.lsm9ds0_gyro_address(), None);
+        assert_eq!(detection.lsm9ds0_accel_mag_address(), None);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn detection_v2_addresses() {
+        let detection = detection(
+            crate::BoardVersion::V2,
+            [Some(0x68), None, Some(0x3D), None, None],
+        );
+        assert_eq!(detection.lsm9ds1_accel_gyro_address(), Some(0x6A));
+        assert_eq!(detection.lsm9ds1_magnetometer_address(), Some(0x1C));
+        // The LSM9DS1's magnetometer has the LIS3MDL's WHO_AM_I value
+        assert_eq!(detection.lis3mdl_address(), None);
+        assert_eq!(detection.lsm6dsl_address(), None);
+        assert_eq!(detection.lsm9ds0_gyro_address(), None);
+        assert!(matches!(
+            detection.lsm9ds0("/dev/i2c-1"),
+            Err(crate::Error::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            detection.imu("/dev/i2c-1"),
+            Err(crate::Error::InvalidConfig(_))
+        ));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn detection_v1_addresses() {
+        let detection = detection(
+            crate::BoardVersion::V1,
+            [None, Some(0xD4), None, Some(0x49), None],
+        );
+        assert_eq!(detection.lsm9ds0_gyro_address(), Some(0x6B));
+        assert_eq!(detection.lsm9ds0_accel_mag_address(), Some(0x1D));
+        assert_eq!(detection.lsm9ds1_accel_gyro_address(), None);
+        assert_eq!(detection.lsm6dsl_address(), None);
+        assert!(matches!(
+            detection.lsm9ds1("/dev/i2c-1"),
+            Err(crate::Error::InvalidConfig(_))
+        ));
+    }
+
+    // The plain constructors don't require a device that can be moved to a
+    // helper thread.
+    #[allow(dead_code)]
+    fn new_without_send_bounds<D: I2CDevice>(dev: D) {
+        let _ = Magnetometer::new(dev);
+    }
+
+    #[test]
+    fn read_timeout() {
+        let dev = FakeBus {
+            address: 0x1C,
+            ..berryimu()
+        };
+        let config = crate::MagConfig::default();
+        let options = crate::SensorOptions {
+            read_timeout: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let mut mag = Magnetometer::new_with_options(dev.clone(), config, options).unwrap();
+        assert_eq!(mag.read().unwrap(), (7, 8, 9).into());
+
+        let dev = FakeBus {
+            delay: Duration::from_millis(200),
+            ..dev
+        };
+        let options = crate::SensorOptions {
+            read_timeout: Some(Duration::from_millis(10)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Magnetometer::new_with_options(dev, config, options),
+            Err(crate::Error::Timeout { reg: 0x0F, .. })
+        ));
     }
 }
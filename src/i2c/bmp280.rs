@@ -0,0 +1,67 @@
+//! A reader for the BerryIMUv2's BMP280 barometer over I2C.
+
+use super::{I2cInterface, SharedBus, SharedDevice, SlaveAddress};
+use crate::bmp280;
+use i2cdev::core::I2CDevice;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use i2cdev::linux::{LinuxI2CDevice, LinuxI2CError};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::path::Path;
+
+/// A barometer reader.
+pub type Barometer<D> = bmp280::Barometer<I2cInterface<D>>;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Barometer<LinuxI2CDevice> {
+    /// Creates a new barometer reader from an address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let dev = LinuxI2CDevice::new(addr, crate::Chip::Bmp280.i2c_address())?;
+        Barometer::new(dev)
+    }
+}
+
+impl<D: SlaveAddress> Barometer<SharedDevice<D>> {
+    /// Creates a new barometer reader from a shared I2C bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The shared I2C bus.
+    /// * `address`: The chip's slave address on the bus.
+    pub fn new_from_shared_bus(
+        bus: &SharedBus<D>,
+        address: u16,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Barometer::new(bus.device(address))
+    }
+}
+
+impl<D: I2CDevice> Barometer<D> {
+    /// Creates a new barometer reader from an I2C device.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    pub fn new(dev: D) -> Result<Self, crate::Error<D::Error>> {
+        Barometer::from_bus(I2cInterface::new(dev))
+    }
+}
+
+impl<D> Barometer<D>
+where
+    D: I2CDevice + Send + 'static,
+    D::Error: Send + 'static,
+{
+    /// Creates a new barometer reader from an I2C device, with the given access
+    /// options.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: D,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Barometer::from_bus(I2cInterface::with_options(dev, options))
+    }
+}
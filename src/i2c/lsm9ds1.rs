@@ -0,0 +1,194 @@
+//! Readers for the BerryIMUv2's LSM9DS1 over I2C.
+
+use super::{I2cInterface, SharedBus, SharedDevice, SlaveAddress};
+use crate::lsm9ds1;
+use i2cdev::core::I2CDevice;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use i2cdev::linux::{LinuxI2CDevice, LinuxI2CError};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::path::Path;
+
+/// An accelerometer reader.
+pub type Accelerometer<D> = lsm9ds1::Accelerometer<I2cInterface<D>>;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Accelerometer<LinuxI2CDevice> {
+    /// Creates a new accelerometer reader from an address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let dev = LinuxI2CDevice::new(addr, crate::Chip::Lsm9ds1AccelGyro.i2c_address())?;
+        Accelerometer::new(dev)
+    }
+}
+
+impl<D: SlaveAddress> Accelerometer<SharedDevice<D>> {
+    /// Creates a new accelerometer reader from a shared I2C bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The shared I2C bus.
+    /// * `address`: The chip's slave address on the bus.
+    pub fn new_from_shared_bus(
+        bus: &SharedBus<D>,
+        address: u16,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Accelerometer::new(bus.device(address))
+    }
+}
+
+impl<D: I2CDevice> Accelerometer<D> {
+    /// Creates a new accelerometer reader from an I2C device.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    pub fn new(dev: D) -> Result<Self, crate::Error<D::Error>> {
+        Accelerometer::from_bus(I2cInterface::new(dev))
+    }
+}
+
+impl<D> Accelerometer<D>
+where
+    D: I2CDevice + Send + 'static,
+    D::Error: Send + 'static,
+{
+    /// Creates a new accelerometer reader from an I2C device, with the given access
+    /// options.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: D,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Accelerometer::from_bus(I2cInterface::with_options(dev, options))
+    }
+}
+
+/// A gyroscope reader.
+pub type Gyroscope<D> = lsm9ds1::Gyroscope<I2cInterface<D>>;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Gyroscope<LinuxI2CDevice> {
+    /// Creates a new gyroscope reader from an address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let dev = LinuxI2CDevice::new(addr, crate::Chip::Lsm9ds1AccelGyro.i2c_address())?;
+        Gyroscope::new(dev)
+    }
+}
+
+impl<D: SlaveAddress> Gyroscope<SharedDevice<D>> {
+    /// Creates a new gyroscope reader from a shared I2C bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The shared I2C bus.
+    /// * `address`: The chip's slave address on the bus.
+    pub fn new_from_shared_bus(
+        bus: &SharedBus<D>,
+        address: u16,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Gyroscope::new(bus.device(address))
+    }
+}
+
+impl<D: I2CDevice> Gyroscope<D> {
+    /// Creates a new gyroscope reader from an I2C device.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    pub fn new(dev: D) -> Result<Self, crate::Error<D::Error>> {
+        Gyroscope::from_bus(I2cInterface::new(dev))
+    }
+}
+
+impl<D> Gyroscope<D>
+where
+    D: I2CDevice + Send + 'static,
+    D::Error: Send + 'static,
+{
+    /// Creates a new gyroscope reader from an I2C device, with the given access
+    /// options.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: D,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Gyroscope::from_bus(I2cInterface::with_options(dev, options))
+    }
+}
+
+/// A magnetometer reader.
+pub type Magnetometer<D> = lsm9ds1::Magnetometer<I2cInterface<D>>;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Magnetometer<LinuxI2CDevice> {
+    /// Creates a new magnetometer reader from an address.
+    ///
+    /// # Arguments
+    /// * `addr`: The I2C device address, e.g. `/dev/i2c-1`.
+    pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<LinuxI2CError>> {
+        let dev = LinuxI2CDevice::new(addr, crate::Chip::Lsm9ds1Mag.i2c_address())?;
+        Magnetometer::new(dev)
+    }
+}
+
+impl<D: SlaveAddress> Magnetometer<SharedDevice<D>> {
+    /// Creates a new magnetometer reader from a shared I2C bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The shared I2C bus.
+    /// * `address`: The chip's slave address on the bus.
+    pub fn new_from_shared_bus(
+        bus: &SharedBus<D>,
+        address: u16,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Magnetometer::new(bus.device(address))
+    }
+}
+
+impl<D: I2CDevice> Magnetometer<D> {
+    /// Creates a new magnetometer reader from an I2C device.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    pub fn new(dev: D) -> Result<Self, crate::Error<D::Error>> {
+        Magnetometer::from_bus(I2cInterface::new(dev))
+    }
+}
+
+impl<D> Magnetometer<D>
+where
+    D: I2CDevice + Send + 'static,
+    D::Error: Send + 'static,
+{
+    /// Creates a new magnetometer reader from an I2C device, with the given access
+    /// options.
+    ///
+    /// # Arguments
+    /// * `dev`: The I2C device.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: D,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<D::Error>> {
+        Magnetometer::from_bus(I2cInterface::with_options(dev, options))
+    }
+}
+
+/// The readers for each of the LSM9DS1's sensors, as created by
+/// `Detection::lsm9ds1`.
+pub struct Readers<D: I2CDevice> {
+    /// The accelerometer reader.
+    pub accelerometer: Accelerometer<D>,
+    /// The gyroscope reader.
+    pub gyroscope: Gyroscope<D>,
+    /// The magnetometer reader.
+    pub magnetometer: Magnetometer<D>,
+}
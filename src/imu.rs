@@ -0,0 +1,63 @@
+//! A reader for all of the BerryIMUv3's sensors, generic over the bus
+//! they're accessed through. See the backend modules, e.g. `i2c`, for
+//! constructors.
+
+use crate::bus::RegisterAccess;
+use crate::lis3mdl::Magnetometer;
+use crate::lsm6dsl::Lsm6dsl;
+
+/// A reader for the accelerometer, gyroscope and magnetometer.
+pub struct Imu<B: RegisterAccess> {
+    lsm6dsl: Lsm6dsl<B>,
+    magnetometer: Magnetometer<B>,
+}
+
+impl<B: RegisterAccess> Imu<B> {
+    /// Creates a new IMU reader from readers for each chip.
+    ///
+    /// # Arguments
+    /// * `lsm6dsl`: The accelerometer & gyroscope reader.
+    /// * `magnetometer`: The magnetometer reader.
+    pub fn from_readers(lsm6dsl: Lsm6dsl<B>, magnetometer: Magnetometer<B>) -> Self {
+        Self {
+            lsm6dsl,
+            magnetometer,
+        }
+    }
+
+    /// Creates a new IMU reader from a bus for each chip, configuring the
+    /// sensors with a preset profile.
+    ///
+    /// # Arguments
+    /// * `lsm6dsl_bus`: The bus for the accelerometer & gyroscope.
+    /// * `lis3mdl_bus`: The bus for the magnetometer.
+    /// * `profile`: The preset configuration of all sensors.
+    pub fn with_profile(
+        lsm6dsl_bus: B,
+        lis3mdl_bus: B,
+        profile: crate::Profile,
+    ) -> Result<Self, crate::Error<B::Error>> {
+        Ok(Self::from_readers(
+            Lsm6dsl::from_bus(lsm6dsl_bus, profile.accel_config(), profile.gyro_config())?,
+            Magnetometer::from_bus(lis3mdl_bus, profile.mag_config())?,
+        ))
+    }
+
+    /// The accelerometer & gyroscope reader.
+    pub fn lsm6dsl(&mut self) -> &mut Lsm6dsl<B> {
+        &mut self.lsm6dsl
+    }
+
+    /// The magnetometer reader.
+    pub fn magnetometer(&mut self) -> &mut Magnetometer<B> {
+        &mut self.magnetometer
+    }
+
+    /// Read the raw values of all sensors. The accelerometer and gyroscope
+    /// values come from the same sample.
+    pub fn read(&mut self) -> Result<crate::ImuReading, crate::Error<B::Error>> {
+        let (accel, gyro) = self.lsm6dsl.read_accel_gyro()?;
+        let mag = self.magnetometer.read()?;
+        Ok(crate::ImuReading { accel, gyro, mag })
+    }
+}
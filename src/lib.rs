@@ -1,67 +1,651 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// A HAL may implement both embedded-hal versions' I2C traits, so only one of
+// their backends can be enabled, to keep it unambiguous which is used.
+#[cfg(all(feature = "eh1", feature = "eh02"))]
+compile_error!("the `eh1` and `eh02` features are mutually exclusive");
+
+#[cfg(feature = "asynch")]
+pub mod asynch;
+#[cfg(feature = "bmp280")]
+pub mod bmp280;
+mod bus;
+pub mod calibration;
+mod config;
+#[cfg(feature = "eh02")]
+pub mod eh02;
+#[cfg(feature = "eh1")]
+pub mod eh1;
+pub mod filter;
+#[cfg(all(feature = "i2c", unix))]
 pub mod i2c;
+pub mod imu;
+pub mod lis3mdl;
+pub mod lsm6dsl;
+pub mod lsm9ds0;
+pub mod lsm9ds1;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "std")]
+pub mod nav;
+#[cfg(feature = "std")]
+mod retry;
+#[cfg(feature = "rppal")]
+pub mod rppal;
+mod sensor;
+#[cfg(all(feature = "spi", any(target_os = "linux", target_os = "android")))]
 pub mod spi;
+#[cfg(feature = "spi_eh")]
+pub mod spi_eh;
+#[cfg(test)]
+mod testing;
+// The test harness needs std anyway, and the fake bus uses it
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+#[cfg(feature = "tokio")]
+pub mod worker;
+
+pub use bus::RegisterAccess;
+pub use config::*;
+#[cfg(feature = "std")]
+pub use retry::{RetryPolicy, Retryable};
+pub use sensor::{AccelSensor, GyroSensor, MagSensor};
 
-use std::error::Error as StdError;
-use std::fmt;
+use core::error::Error as CoreError;
+use core::fmt;
+use core::time::Duration;
 
 // LSM6DSL
+#[cfg(any(
+    feature = "i2c",
+    feature = "eh1",
+    feature = "eh02",
+    feature = "asynch",
+    feature = "rppal"
+))]
 const LSM6DSL_ADDRESS: u16 = 0x6A;
+const LSM6DSL_FIFO_CTRL1: u8 = 0x06;
+const LSM6DSL_FIFO_CTRL2: u8 = 0x07;
+const LSM6DSL_FIFO_CTRL3: u8 = 0x08;
+const LSM6DSL_FIFO_CTRL5: u8 = 0x0A;
+const LSM6DSL_INT1_CTRL: u8 = 0x0D;
+const LSM6DSL_INT2_CTRL: u8 = 0x0E;
 const LSM6DSL_WHO_AM_I: u8 = 0x0F;
 const LSM6DSL_CTRL1_XL: u8 = 0x10;
 const LSM6DSL_CTRL8_XL: u8 = 0x17;
 const LSM6DSL_CTRL2_G: u8 = 0x11;
 const LSM6DSL_CTRL3_C: u8 = 0x12;
+const LSM6DSL_CTRL4_C: u8 = 0x13;
+const LSM6DSL_CTRL5_C: u8 = 0x14;
+const LSM6DSL_CTRL6_C: u8 = 0x15;
+const LSM6DSL_CTRL7_G: u8 = 0x16;
+const LSM6DSL_STATUS_REG: u8 = 0x1E;
+const LSM6DSL_OUT_TEMP_L: u8 = 0x20;
 const LSM6DSL_OUTX_L_XL: u8 = 0x28;
-const LSM6DSL_OUTX_H_XL: u8 = 0x29;
-const LSM6DSL_OUTY_L_XL: u8 = 0x2A;
-const LSM6DSL_OUTY_H_XL: u8 = 0x2B;
-const LSM6DSL_OUTZ_L_XL: u8 = 0x2C;
-const LSM6DSL_OUTZ_H_XL: u8 = 0x2D;
 const LSM6DSL_OUTX_L_G: u8 = 0x22;
-const LSM6DSL_OUTX_H_G: u8 = 0x23;
-const LSM6DSL_OUTY_L_G: u8 = 0x24;
-const LSM6DSL_OUTY_H_G: u8 = 0x25;
-const LSM6DSL_OUTZ_L_G: u8 = 0x26;
-const LSM6DSL_OUTZ_H_G: u8 = 0x27;
+const LSM6DSL_FIFO_STATUS1: u8 = 0x3A;
+const LSM6DSL_FIFO_DATA_OUT_L: u8 = 0x3E;
 
 // LIS3MDL
+#[cfg(any(
+    feature = "i2c",
+    feature = "eh1",
+    feature = "eh02",
+    feature = "asynch",
+    feature = "rppal"
+))]
 const LIS3MDL_ADDRESS: u16 = 0x1C;
 const LIS3MDL_WHO_AM_I: u8 = 0x0F;
 const LIS3MDL_CTRL_REG1: u8 = 0x20;
 const LIS3MDL_CTRL_REG2: u8 = 0x21;
 const LIS3MDL_CTRL_REG3: u8 = 0x22;
+const LIS3MDL_CTRL_REG4: u8 = 0x23;
+const LIS3MDL_CTRL_REG5: u8 = 0x24;
+const LIS3MDL_STATUS_REG: u8 = 0x27;
 const LIS3MDL_OUT_X_L: u8 = 0x28;
 
+// LSM9DS1
+#[cfg(any(
+    feature = "i2c",
+    feature = "eh1",
+    feature = "eh02",
+    feature = "asynch",
+    feature = "rppal"
+))]
+const LSM9DS1_AG_ADDRESS: u16 = 0x6A;
+const LSM9DS1_WHO_AM_I: u8 = 0x0F;
+const LSM9DS1_CTRL_REG1_G: u8 = 0x10;
+const LSM9DS1_OUT_X_L_G: u8 = 0x18;
+const LSM9DS1_CTRL_REG4: u8 = 0x1E;
+const LSM9DS1_CTRL_REG5_XL: u8 = 0x1F;
+const LSM9DS1_CTRL_REG6_XL: u8 = 0x20;
+const LSM9DS1_OUT_X_L_XL: u8 = 0x28;
+#[cfg(any(
+    feature = "i2c",
+    feature = "eh1",
+    feature = "eh02",
+    feature = "asynch",
+    feature = "rppal"
+))]
+const LSM9DS1_M_ADDRESS: u16 = 0x1C;
+const LSM9DS1_WHO_AM_I_M: u8 = 0x0F;
+const LSM9DS1_CTRL_REG1_M: u8 = 0x20;
+const LSM9DS1_CTRL_REG2_M: u8 = 0x21;
+const LSM9DS1_CTRL_REG3_M: u8 = 0x22;
+const LSM9DS1_CTRL_REG4_M: u8 = 0x23;
+const LSM9DS1_OUT_X_L_M: u8 = 0x28;
+
+// LSM9DS0
+#[cfg(any(
+    feature = "i2c",
+    feature = "eh1",
+    feature = "eh02",
+    feature = "asynch",
+    feature = "rppal"
+))]
+const LSM9DS0_G_ADDRESS: u16 = 0x6A;
+const LSM9DS0_WHO_AM_I_G: u8 = 0x0F;
+const LSM9DS0_CTRL_REG1_G: u8 = 0x20;
+const LSM9DS0_CTRL_REG4_G: u8 = 0x23;
+const LSM9DS0_OUT_X_L_G: u8 = 0x28;
+#[cfg(any(
+    feature = "i2c",
+    feature = "eh1",
+    feature = "eh02",
+    feature = "asynch",
+    feature = "rppal"
+))]
+const LSM9DS0_XM_ADDRESS: u16 = 0x1E;
+const LSM9DS0_WHO_AM_I_XM: u8 = 0x0F;
+const LSM9DS0_OUT_X_L_M: u8 = 0x08;
+const LSM9DS0_CTRL_REG1_XM: u8 = 0x20;
+const LSM9DS0_CTRL_REG2_XM: u8 = 0x21;
+const LSM9DS0_CTRL_REG5_XM: u8 = 0x24;
+const LSM9DS0_CTRL_REG6_XM: u8 = 0x25;
+const LSM9DS0_CTRL_REG7_XM: u8 = 0x26;
+const LSM9DS0_OUT_X_L_A: u8 = 0x28;
+
+// BMP280
+#[cfg(all(
+    feature = "bmp280",
+    any(
+        feature = "i2c",
+        feature = "eh1",
+        feature = "eh02",
+        feature = "asynch",
+        feature = "rppal"
+    )
+))]
+const BMP280_ADDRESS: u16 = 0x77;
+#[cfg(feature = "bmp280")]
+const BMP280_ID: u8 = 0xD0;
+#[cfg(feature = "bmp280")]
+const BMP280_CALIB00: u8 = 0x88;
+#[cfg(feature = "bmp280")]
+const BMP280_CTRL_MEAS: u8 = 0xF4;
+#[cfg(feature = "bmp280")]
+const BMP280_CONFIG: u8 = 0xF5;
+#[cfg(feature = "bmp280")]
+const BMP280_PRESS_MSB: u8 = 0xF7;
+
+/// The I2C slave address of the LSM6DSL, selected by its SDO/SA0 pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum Lsm6dslAddress {
+    /// SDO/SA0 pulled low, as on the BerryIMUv3.
+    #[default]
+    Low = 0x6A,
+    /// SDO/SA0 pulled high.
+    High = 0x6B,
+}
+
+/// The I2C slave address of the LIS3MDL, selected by its SDO/SA1 pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum Lis3mdlAddress {
+    /// SDO/SA1 pulled low, as on the BerryIMUv3.
+    #[default]
+    Low = 0x1C,
+    /// SDO/SA1 pulled high.
+    High = 0x1E,
+}
+
+/// A version of the BerryIMU board. Each version has its own readers: the
+/// `lsm6dsl` and `lis3mdl` ones for the BerryIMUv3, and the `lsm9ds1` and
+/// `lsm9ds0` ones for the v2 and v1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoardVersion {
+    /// The BerryIMUv1, with an LSM9DS0.
+    V1,
+    /// The BerryIMUv2, with an LSM9DS1.
+    V2,
+    /// The BerryIMUv3, with an LSM6DSL and LIS3MDL.
+    V3,
+}
+
+/// The outcome of probing an I2C slave address while detecting the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Probe {
+    /// The slave address.
+    pub address: u16,
+    /// The value read from the WHO_AM_I register, or `None` if nothing
+    /// responded.
+    pub who_am_i: Option<u8>,
+}
+
+/// A chip on a BerryIMU.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Chip {
+    /// The BerryIMUv3's accelerometer & gyroscope.
+    Lsm6dsl,
+    /// The BerryIMUv3's magnetometer.
+    Lis3mdl,
+    /// The BerryIMUv2's accelerometer & gyroscope.
+    Lsm9ds1AccelGyro,
+    /// The BerryIMUv2's magnetometer.
+    Lsm9ds1Mag,
+    /// The BerryIMUv1's gyroscope.
+    Lsm9ds0Gyro,
+    /// The BerryIMUv1's accelerometer & magnetometer.
+    Lsm9ds0AccelMag,
+    /// The BerryIMUv2's barometer.
+    #[cfg(feature = "bmp280")]
+    Bmp280,
+}
+
+impl Chip {
+    /// The chip's I2C slave address.
+    #[cfg(any(
+        feature = "i2c",
+        feature = "eh1",
+        feature = "eh02",
+        feature = "asynch",
+        feature = "rppal"
+    ))]
+    fn i2c_address(self) -> u16 {
+        match self {
+            Chip::Lsm6dsl => LSM6DSL_ADDRESS,
+            Chip::Lis3mdl => LIS3MDL_ADDRESS,
+            Chip::Lsm9ds1AccelGyro => LSM9DS1_AG_ADDRESS,
+            Chip::Lsm9ds1Mag => LSM9DS1_M_ADDRESS,
+            Chip::Lsm9ds0Gyro => LSM9DS0_G_ADDRESS,
+            Chip::Lsm9ds0AccelMag => LSM9DS0_XM_ADDRESS,
+            #[cfg(feature = "bmp280")]
+            Chip::Bmp280 => BMP280_ADDRESS,
+        }
+    }
+
+    /// The register holding the chip's identity.
+    fn who_am_i_register(self) -> u8 {
+        match self {
+            Chip::Lsm6dsl => LSM6DSL_WHO_AM_I,
+            Chip::Lis3mdl => LIS3MDL_WHO_AM_I,
+            Chip::Lsm9ds1AccelGyro => LSM9DS1_WHO_AM_I,
+            Chip::Lsm9ds1Mag => LSM9DS1_WHO_AM_I_M,
+            Chip::Lsm9ds0Gyro => LSM9DS0_WHO_AM_I_G,
+            Chip::Lsm9ds0AccelMag => LSM9DS0_WHO_AM_I_XM,
+            #[cfg(feature = "bmp280")]
+            Chip::Bmp280 => BMP280_ID,
+        }
+    }
+
+    #[cfg(any(
+        feature = "spi",
+        feature = "spi_eh",
+        feature = "asynch",
+        feature = "rppal"
+    ))]
+    /// The address byte that starts a SPI read of `len` registers from
+    /// `reg`.
+    fn spi_read_command(self, reg: u8, len: usize) -> u8 {
+        match self {
+            // The LIS3MDL and the LSM9DS1's magnetometer only
+            // auto-increment the register address during multi byte
+            // transfers when the MS bit is set in addition to the read bit.
+            Chip::Lis3mdl | Chip::Lsm9ds1Mag if len > 1 => reg | 0xC0,
+            // The LSM6DSL instead auto-increments based on IF_INC in
+            // CTRL3_C.
+            _ => reg | 0x80,
+        }
+    }
+
+    /// The expected value of the chip's WHO_AM_I register.
+    fn who_am_i(self) -> &'static [u8] {
+        match self {
+            // Also accepts the LSM6DSO and LSM6DS3, which are compatible
+            Chip::Lsm6dsl => &[0x6A, 0x6C, 0x69],
+            Chip::Lis3mdl => &[0x3D],
+            Chip::Lsm9ds1AccelGyro => &[0x68],
+            Chip::Lsm9ds1Mag => &[0x3D],
+            Chip::Lsm9ds0Gyro => &[0xD4],
+            Chip::Lsm9ds0AccelMag => &[0x49],
+            #[cfg(feature = "bmp280")]
+            Chip::Bmp280 => &[0x58],
+        }
+    }
+}
+
+// Combines readings for each axis, in the given byte order
+fn decode_xyz(block: &[u8], endianness: Endianness) -> (i32, i32, i32) {
+    let axis = |i: usize| endianness.decode([block[i], block[i + 1]]) as i32;
+    (axis(0), axis(2), axis(4))
+}
+
+// Converts raw readings to physical units, given the sensitivity in units/LSB
+fn scale_xyz((x, y, z): (i32, i32, i32), sensitivity: f32) -> (f32, f32, f32) {
+    (
+        x as f32 * sensitivity,
+        y as f32 * sensitivity,
+        z as f32 * sensitivity,
+    )
+}
+
+// The LSM6DSL temperature output is 256 LSB/°C, with 0 at 25°C.
+fn temperature_celsius(raw: i16) -> f32 {
+    25.0 + raw as f32 / 256.0
+}
+
+/// A raw accelerometer reading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct AccelReading {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl AccelReading {
+    /// The squared magnitude of the reading, for magnitude checks that don't
+    /// need a square root.
+    pub fn magnitude_sq(&self) -> i64 {
+        let (x, y, z) = (self.x as i64, self.y as i64, self.z as i64);
+        x * x + y * y + z * z
+    }
+}
+
+impl From<(i32, i32, i32)> for AccelReading {
+    fn from((x, y, z): (i32, i32, i32)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/// A raw gyroscope reading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct GyroReading {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl GyroReading {
+    /// The squared magnitude of the reading, for magnitude checks that don't
+    /// need a square root.
+    pub fn magnitude_sq(&self) -> i64 {
+        let (x, y, z) = (self.x as i64, self.y as i64, self.z as i64);
+        x * x + y * y + z * z
+    }
+}
+
+impl From<(i32, i32, i32)> for GyroReading {
+    fn from((x, y, z): (i32, i32, i32)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/// A raw magnetometer reading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct MagReading {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl MagReading {
+    /// The squared magnitude of the reading in the x/y plane, for 2D heading
+    /// applications where z is discarded.
+    pub fn horizontal_magnitude_sq(&self) -> i64 {
+        let (x, y) = (self.x as i64, self.y as i64);
+        x * x + y * y
+    }
+}
+
+impl From<(i32, i32, i32)> for MagReading {
+    fn from((x, y, z): (i32, i32, i32)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/// Raw readings of all sensors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct ImuReading {
+    pub accel: AccelReading,
+    pub gyro: GyroReading,
+    pub mag: MagReading,
+}
+
+/// The outcome of a sensor's self-test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelfTestResult<R> {
+    /// Whether every axis' change was within the datasheet's limits.
+    pub passed: bool,
+    /// The change in the averaged raw readings when the self-test is
+    /// enabled, at the full-scale range the test runs at.
+    pub delta: R,
+}
+
+/// Options for how the readers access the device.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct SensorOptions {
+    /// How long to wait for each bus transaction before giving up with
+    /// `Error::Timeout`, or `None` to wait indefinitely. This guards against
+    /// a sensor that stops acknowledging, which would otherwise hang the
+    /// caller. Writes are bounded too, since they'd otherwise block behind a
+    /// read that never completed. Each transaction then runs on a helper
+    /// thread, so with I2C the device must be `Send`.
+    pub read_timeout: Option<Duration>,
+    /// Whether each configuration write is read back, and retried once if
+    /// it didn't take effect, before failing with `Error::WriteMismatch`.
+    /// This catches writes lost on a marginal bus, e.g. with a long cable or
+    /// weak pull-ups, at the cost of an extra read per write.
+    pub verify_writes: bool,
+}
+
 /// An error that occurred while interfacing with the BerryIMUv3 device.
 #[derive(Debug)]
-pub enum Error<E: StdError + 'static> {
-    Init,
-    Read,
-    Write,
+pub enum Error<E> {
+    /// The chip's WHO_AM_I register didn't hold the expected value, e.g.
+    /// because it's absent or wired incorrectly.
+    Init {
+        /// The values the chip, or a compatible one, would report.
+        expected: &'static [u8],
+        /// The value that was read.
+        actual: u8,
+    },
+    /// A read failed, e.g. because it returned less data than requested.
+    Read {
+        /// The register being read.
+        reg: u8,
+        /// The device error that caused the failure, if any.
+        cause: Option<E>,
+    },
+    /// A write failed.
+    Write {
+        /// The register being written.
+        reg: u8,
+        /// The device error that caused the failure, if any.
+        cause: Option<E>,
+    },
+    /// A configuration write didn't take effect, even after being retried,
+    /// e.g. because it was corrupted on the bus. Only checked when
+    /// `SensorOptions::verify_writes` is set.
+    WriteMismatch {
+        /// The register being written.
+        reg: u8,
+        /// The value that was written.
+        expected: u8,
+        /// The value that was read back.
+        actual: u8,
+    },
+    /// The requested configuration isn't supported by the device.
+    InvalidConfig(&'static str),
+    /// The chip wasn't found at any of the probed I2C slave addresses. Holds
+    /// the outcome of probing each address.
+    NotFound([Probe; 2]),
+    /// No known board was detected. Holds the outcome of probing each
+    /// address.
+    UnknownBoard([Probe; 5]),
+    /// A bus transaction didn't complete within the configured timeout.
+    Timeout {
+        /// The register being accessed.
+        reg: u8,
+        /// How long the transaction was waited on.
+        elapsed: Duration,
+    },
+    /// The sensor is asleep, so it has no fresh readings.
+    Asleep,
+    /// The sensor is powered down, so it has no fresh readings.
+    PoweredDown,
+    /// A configuration register held a bit pattern that no configuration
+    /// corresponds to, e.g. because other software wrote it.
+    UnknownRegisterValue {
+        /// The register that was read.
+        reg: u8,
+        /// The value that was read.
+        value: u8,
+    },
     Device(E),
 }
 
-impl<E: StdError + 'static> StdError for Error<E> {
-    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+impl<E: CoreError + 'static> CoreError for Error<E> {
+    fn source(&self) -> Option<&(dyn CoreError + 'static)> {
         match self {
-            Error::Device(ref err) => Some(err),
+            Error::Read {
+                cause: Some(ref err),
+                ..
+            }
+            | Error::Write {
+                cause: Some(ref err),
+                ..
+            }
+            | Error::Device(ref err) => Some(err),
             _ => None,
         }
     }
 }
 
-impl<E: StdError + 'static> fmt::Display for Error<E> {
+/// Writes the outcome of probing each address, e.g. " (0x1c: no response)".
+fn write_probes(f: &mut fmt::Formatter, probes: &[Probe]) -> fmt::Result {
+    for (i, probe) in probes.iter().enumerate() {
+        write!(
+            f,
+            "{}{:#04x}: ",
+            if i == 0 { " (" } else { ", " },
+            probe.address
+        )?;
+        match probe.who_am_i {
+            Some(who_am_i) => write!(f, "WHO_AM_I {:#04X}", who_am_i)?,
+            None => write!(f, "no response")?,
+        }
+    }
+    write!(f, ")")
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::Init => write!(f, "init failed"),
-            Error::Read => write!(f, "read failed"),
-            Error::Write => write!(f, "write failed"),
+            Error::Init { expected, actual } => {
+                write!(f, "init failed: expected WHO_AM_I ")?;
+                if let [expected] = expected {
+                    write!(f, "{:#04X}", expected)?;
+                } else {
+                    write!(f, "one of [")?;
+                    for (i, expected) in expected.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{:#04X}", expected)?;
+                    }
+                    write!(f, "]")?;
+                }
+                write!(f, ", got {:#04X}", actual)
+            }
+            Error::Read { reg, cause } => {
+                write!(f, "read failed on register {:#04X}", reg)?;
+                match cause {
+                    Some(err) => write!(f, ": {}", err),
+                    None => Ok(()),
+                }
+            }
+            Error::Write { reg, cause } => {
+                write!(f, "write failed on register {:#04X}", reg)?;
+                match cause {
+                    Some(err) => write!(f, ": {}", err),
+                    None => Ok(()),
+                }
+            }
+            Error::WriteMismatch {
+                reg,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "write to register {:#04X} didn't take effect: expected {:#04X}, read back {:#04X}",
+                reg, expected, actual
+            ),
+            Error::InvalidConfig(reason) => write!(f, "invalid configuration: {}", reason),
+            Error::NotFound(probes) => {
+                write!(f, "no device found")?;
+                write_probes(f, probes)
+            }
+            Error::UnknownBoard(probes) => {
+                write!(f, "no known board detected")?;
+                write_probes(f, probes)
+            }
+            Error::Timeout { reg, elapsed } => write!(
+                f,
+                "timed out after {:?} accessing register {:#04X}",
+                elapsed, reg
+            ),
+            Error::Asleep => write!(f, "sensor is asleep"),
+            Error::PoweredDown => write!(f, "sensor is powered down"),
+            Error::UnknownRegisterValue { reg, value } => write!(
+                f,
+                "register {:#04X} holds unrecognized value {:#04X}",
+                reg, value
+            ),
             Error::Device(err) => write!(f, "device error: {}", err),
         }
     }
 }
 
-impl<E: StdError + 'static> From<E> for Error<E> {
+#[cfg(any(
+    feature = "i2c",
+    feature = "spi",
+    feature = "eh1",
+    feature = "eh02",
+    feature = "spi_eh",
+    feature = "asynch",
+    feature = "rppal"
+))]
+impl<E> Error<E> {
+    /// Wraps a device error that occurred while reading a register.
+    fn read(reg: u8) -> impl FnOnce(E) -> Self {
+        move |err| Error::Read {
+            reg,
+            cause: Some(err),
+        }
+    }
+
+    /// Wraps a device error that occurred while writing a register.
+    fn write(reg: u8) -> impl FnOnce(E) -> Self {
+        move |err| Error::Write {
+            reg,
+            cause: Some(err),
+        }
+    }
+}
+
+impl<E> From<E> for Error<E> {
     fn from(err: E) -> Self {
         Error::Device(err)
     }
@@ -0,0 +1,610 @@
+//! Readers for the LIS3MDL magnetometer, generic over the bus it's accessed
+//! through. See the backend modules, e.g. `i2c`, for constructors.
+
+use crate::bus::{self, RegisterAccess};
+
+/// The register writes that configure the magnetometer.
+pub(crate) fn init_writes(config: &crate::MagConfig) -> Result<[(u8, u8); 5], &'static str> {
+    config.validate()?;
+    Ok([
+        (crate::LIS3MDL_CTRL_REG1, config.ctrl_reg1()),
+        (crate::LIS3MDL_CTRL_REG2, config.ctrl_reg2()),
+        (crate::LIS3MDL_CTRL_REG3, config.ctrl_reg3()),
+        (crate::LIS3MDL_CTRL_REG4, config.ctrl_reg4()),
+        (crate::LIS3MDL_CTRL_REG5, config.ctrl_reg5()),
+    ])
+}
+
+/// A magnetometer reader.
+pub struct Magnetometer<B: RegisterAccess> {
+    bus: B,
+    config: crate::MagConfig,
+}
+
+impl<B: RegisterAccess> Magnetometer<B> {
+    /// Creates a new magnetometer reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    /// * `config`: The magnetometer configuration.
+    pub fn from_bus(mut bus: B, config: crate::MagConfig) -> Result<Self, crate::Error<B::Error>> {
+        bus::init(&mut bus, crate::Chip::Lis3mdl)?;
+        // Enable the magnetometer
+        let writes = init_writes(&config).map_err(crate::Error::InvalidConfig)?;
+        bus::write_all(&mut bus, &writes)?;
+        Ok(Self { bus, config })
+    }
+
+    /// The current configuration, including any changes made since the
+    /// reader was created.
+    pub fn config(&self) -> crate::MagConfig {
+        self.config
+    }
+
+    /// Reads the configuration the chip is actually running with, e.g. to
+    /// debug it or after other software configured the chip. The mode reads
+    /// as powered down while the magnetometer is, including after a single
+    /// conversion. Bit patterns that no configuration corresponds to fail
+    /// with `Error::UnknownRegisterValue`.
+    pub fn read_config(&mut self) -> Result<crate::MagConfig, crate::Error<B::Error>> {
+        let mut values = [0; 5];
+        for (value, reg) in values.iter_mut().zip([
+            crate::LIS3MDL_CTRL_REG1,
+            crate::LIS3MDL_CTRL_REG2,
+            crate::LIS3MDL_CTRL_REG3,
+            crate::LIS3MDL_CTRL_REG4,
+            crate::LIS3MDL_CTRL_REG5,
+        ]) {
+            *value = self.bus.read_register(reg)?;
+        }
+        crate::MagConfig::decode(values, self.config.power_down_on_drop)
+    }
+
+    /// Changes the output data rate, leaving the rest of the configuration
+    /// as is. A FAST_ODR rate that requires a different operating mode than
+    /// the current one is rejected with `Error::InvalidConfig`, so the
+    /// operating mode should be changed first.
+    ///
+    /// # Arguments
+    /// * `odr`: The output data rate.
+    pub fn set_output_data_rate(
+        &mut self,
+        odr: crate::MagOdr,
+    ) -> Result<(), crate::Error<B::Error>> {
+        self.reconfigure_ctrl_reg1(crate::MagConfig { odr, ..self.config })
+    }
+
+    /// Changes the operating mode of every axis, leaving the rest of the
+    /// configuration as is. This is rejected with `Error::InvalidConfig`
+    /// while a FAST_ODR rate that requires a different mode is in use, so
+    /// the output data rate should be changed first.
+    ///
+    /// # Arguments
+    /// * `performance`: The operating mode.
+    pub fn set_performance(
+        &mut self,
+        performance: crate::MagPerformance,
+    ) -> Result<(), crate::Error<B::Error>> {
+        self.reconfigure_ctrl_reg1(crate::MagConfig {
+            performance,
+            ..self.config
+        })?;
+        self.set_z_axis_performance(performance)
+    }
+
+    /// Changes the Z axis operating mode alone, e.g. to trade its noise
+    /// against current draw separately from the X and Y axes. This is reset
+    /// to match the X and Y axes by `set_performance`.
+    ///
+    /// # Arguments
+    /// * `performance`: The Z axis operating mode.
+    pub fn set_z_axis_performance(
+        &mut self,
+        performance: crate::MagPerformance,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::MagConfig {
+            z_performance: performance,
+            ..self.config
+        };
+        // Only the OMZ bits
+        let ctrl_reg4 = config.ctrl_reg4();
+        bus::modify(
+            &mut self.bus,
+            crate::LIS3MDL_CTRL_REG4,
+            0b0000_1100,
+            ctrl_reg4,
+        )?;
+        self.config = config;
+        Ok(())
+    }
+
+    fn reconfigure_ctrl_reg1(
+        &mut self,
+        config: crate::MagConfig,
+    ) -> Result<(), crate::Error<B::Error>> {
+        config.validate().map_err(crate::Error::InvalidConfig)?;
+        // Covers the OM, DO and FAST_ODR bits
+        let ctrl_reg1 = config.ctrl_reg1();
+        bus::modify(&mut self.bus, crate::LIS3MDL_CTRL_REG1, 0x7E, ctrl_reg1)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Changes the full-scale range, leaving the output data rate as is.
+    /// Subsequent scaled readings use the new range's sensitivity.
+    ///
+    /// # Arguments
+    /// * `full_scale`: The full-scale range.
+    pub fn set_full_scale(
+        &mut self,
+        full_scale: crate::MagFullScale,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::MagConfig {
+            full_scale,
+            ..self.config
+        };
+        // Only the FS bits, so REBOOT and SOFT_RST aren't triggered
+        let ctrl_reg2 = config.ctrl_reg2();
+        bus::modify(&mut self.bus, crate::LIS3MDL_CTRL_REG2, 0x60, ctrl_reg2)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// The conversion factor from raw readings to gauss for the current
+    /// full-scale range, in gauss/LSB.
+    pub fn sensitivity_gauss_per_lsb(&self) -> f32 {
+        self.config.full_scale.gauss_per_lsb()
+    }
+
+    /// Powers the magnetometer down, or back up in continuous-conversion
+    /// mode. The rest of the configuration is kept while it's powered down.
+    /// Reads fail with `Error::PoweredDown` while it's powered down.
+    ///
+    /// # Arguments
+    /// * `enabled`: Whether the magnetometer is powered.
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), crate::Error<B::Error>> {
+        let mode = if enabled {
+            crate::MagMode::Continuous
+        } else {
+            crate::MagMode::PowerDown
+        };
+        bus::modify(&mut self.bus, crate::LIS3MDL_CTRL_REG3, 0b11, mode as u8)?;
+        self.config.mode = mode;
+        Ok(())
+    }
+
+    /// Whether the magnetometer is in continuous-conversion mode, i.e. hasn't
+    /// been powered down with `set_enabled` or by a single conversion.
+    pub fn is_enabled(&self) -> bool {
+        self.config.mode == crate::MagMode::Continuous
+    }
+
+    // Fails rather than reading a stale sample
+    fn check_enabled(&self) -> Result<(), crate::Error<B::Error>> {
+        if self.is_enabled() {
+            Ok(())
+        } else {
+            Err(crate::Error::PoweredDown)
+        }
+    }
+
+    /// Whether a new sample is available on every axis, which reading it
+    /// clears.
+    pub fn data_ready(&mut self) -> Result<bool, crate::Error<B::Error>> {
+        // ZYXDA
+        Ok(self.bus.read_register(crate::LIS3MDL_STATUS_REG)? & 0b1000 != 0)
+    }
+
+    /// Waits until a new sample is available on every axis.
+    ///
+    /// # Arguments
+    /// * `timeout`: How long to wait before giving up with `Error::Timeout`,
+    ///   or `None` to wait indefinitely.
+    #[cfg(feature = "std")]
+    pub fn wait_for_data_ready(
+        &mut self,
+        timeout: Option<core::time::Duration>,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let start = std::time::Instant::now();
+        while !self.data_ready()? {
+            let elapsed = start.elapsed();
+            if timeout.is_some_and(|timeout| elapsed >= timeout) {
+                return Err(crate::Error::Timeout {
+                    reg: crate::LIS3MDL_STATUS_REG,
+                    elapsed,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits for a new sample, failing with `Error::PoweredDown` rather than
+    /// waiting forever while the magnetometer is powered down. With `std`,
+    /// this gives up with `Error::Timeout` after a second.
+    pub(crate) fn wait_for_sample(&mut self) -> Result<(), crate::Error<B::Error>> {
+        self.check_enabled()?;
+        // ZYXDA
+        bus::wait_until_set(&mut self.bus, crate::LIS3MDL_STATUS_REG, 0b1000)
+    }
+
+    /// Read the raw magnetometer values.
+    pub fn read(&mut self) -> Result<crate::MagReading, crate::Error<B::Error>> {
+        self.check_enabled()?;
+        Ok(bus::read_xyz(&mut self.bus, crate::LIS3MDL_OUT_X_L)?.into())
+    }
+
+    /// Triggers a single conversion and reads it, e.g. to save power when
+    /// sampling rarely. The chip powers down once the conversion completes,
+    /// so afterwards `read` fails with `Error::PoweredDown` until
+    /// continuous-conversion mode is resumed with `set_enabled`.
+    ///
+    /// # Arguments
+    /// * `timeout`: How long to wait for the conversion before giving up
+    ///   with `Error::Timeout`, or `None` to wait indefinitely.
+    #[cfg(feature = "std")]
+    pub fn read_single(
+        &mut self,
+        timeout: Option<core::time::Duration>,
+    ) -> Result<crate::MagReading, crate::Error<B::Error>> {
+        // Reading any stale sample clears ZYXDA
+        bus::read_xyz(&mut self.bus, crate::LIS3MDL_OUT_X_L)?;
+        // The MD bits select single-conversion mode, and return to
+        // power-down once the conversion completes
+        bus::set_self_clearing(
+            &mut self.bus,
+            crate::LIS3MDL_CTRL_REG3,
+            0b11,
+            crate::MagMode::Single as u8,
+        )?;
+        self.config.mode = crate::MagMode::PowerDown;
+        self.wait_for_data_ready(timeout)?;
+        Ok(bus::read_xyz(&mut self.bus, crate::LIS3MDL_OUT_X_L)?.into())
+    }
+
+    /// Read the magnetometer values, in gauss.
+    pub fn read_scaled(&mut self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        self.check_enabled()?;
+        let xyz = bus::read_xyz(&mut self.bus, crate::LIS3MDL_OUT_X_L)?;
+        let sensitivity = self.config.full_scale.gauss_per_lsb();
+        Ok(crate::scale_xyz(xyz, sensitivity))
+    }
+
+    /// Runs the self-test, which applies an internal magnetic field and
+    /// checks that the output changes by as much as the datasheet specifies.
+    /// The magnetometer should be kept still, and away from changing fields,
+    /// throughout. The configuration is restored afterwards. The delta is at
+    /// +/- 12 gauss.
+    pub fn run_self_test(
+        &mut self,
+    ) -> Result<crate::SelfTestResult<crate::MagReading>, crate::Error<B::Error>> {
+        self.check_enabled()?;
+        let ctrl_reg1 = self.bus.read_register(crate::LIS3MDL_CTRL_REG1)?;
+        let ctrl_reg2 = self.bus.read_register(crate::LIS3MDL_CTRL_REG2)?;
+        let delta = self_test_delta(&mut self.bus, self.config);
+        // CTRL_REG1 first, which ends the self-test
+        bus::write_all(
+            &mut self.bus,
+            &[
+                (crate::LIS3MDL_CTRL_REG1, ctrl_reg1),
+                (crate::LIS3MDL_CTRL_REG2, ctrl_reg2),
+            ],
+        )?;
+
+        let delta = crate::MagReading::from(delta?);
+        let gauss_per_lsb = crate::MagFullScale::Gauss12.gauss_per_lsb();
+        let gauss = |lsb: i32| lsb as f32 * gauss_per_lsb;
+        let passed = (1.0..=3.0).contains(&gauss(delta.x))
+            && (1.0..=3.0).contains(&gauss(delta.y))
+            && (0.1..=1.0).contains(&gauss(delta.z));
+        Ok(crate::SelfTestResult { passed, delta })
+    }
+
+    /// Resets the configuration registers to their defaults, e.g. to recover
+    /// from the chip hanging. This leaves the chip unconfigured, and this
+    /// reader unusable, so afterwards a new reader should be created, e.g.
+    /// with `new_with_config`.
+    pub fn software_reset(&mut self) -> Result<(), crate::Error<B::Error>> {
+        // SOFT_RST, which clears itself once the reset completes
+        bus::set_self_clearing(
+            &mut self.bus,
+            crate::LIS3MDL_CTRL_REG2,
+            0b0000_0100,
+            0b0000_0100,
+        )?;
+        bus::wait_until_clear(&mut self.bus, crate::LIS3MDL_CTRL_REG2, 0b0000_0100)
+    }
+
+    /// Reloads the chip's trimming parameters from its flash memory, e.g.
+    /// when they're suspected to have been corrupted by a power glitch. The
+    /// configuration is kept.
+    pub fn reboot_memory(&mut self) -> Result<(), crate::Error<B::Error>> {
+        bus::set_self_clearing(
+            &mut self.bus,
+            crate::LIS3MDL_CTRL_REG2,
+            0b0000_1000,
+            0b0000_1000,
+        )
+    }
+
+    /// Read a run of consecutive registers, bypassing the typed API, e.g. to
+    /// inspect the raw bytes behind a reading.
+    ///
+    /// # Arguments
+    /// * `start`: The address of the first register.
+    /// * `count`: The number of registers to read.
+    #[cfg(feature = "std")]
+    pub fn read_raw_block(
+        &mut self,
+        start: u8,
+        count: u8,
+    ) -> Result<Vec<u8>, crate::Error<B::Error>> {
+        bus::read_block(&mut self.bus, start, count)
+    }
+
+    /// Read a register directly, for functionality the typed API doesn't
+    /// cover.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    #[doc(hidden)]
+    pub fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<B::Error>> {
+        self.bus.read_register(reg)
+    }
+
+    /// Write a register directly, for functionality the typed API doesn't
+    /// cover. The caller is responsible for not corrupting the state the
+    /// reader relies on: `CTRL_REG2` holds the configured full-scale range,
+    /// which the scaled readings assume, and `CTRL_REG3` must keep the chip
+    /// in continuous-conversion mode for readings to update.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    /// * `value`: The value to write.
+    #[doc(hidden)]
+    pub fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<B::Error>> {
+        self.bus.write_register(reg, value)
+    }
+}
+
+impl<B: RegisterAccess> Drop for Magnetometer<B> {
+    fn drop(&mut self) {
+        if self.config.power_down_on_drop {
+            // Errors are ignored, since there's no way to report them
+            let _ = self.set_enabled(false);
+        }
+    }
+}
+
+// How much the averaged output changes in the self-test, leaving the
+// registers for the caller to restore.
+fn self_test_delta<B: RegisterAccess>(
+    bus: &mut B,
+    config: crate::MagConfig,
+) -> Result<(i32, i32, i32), crate::Error<B::Error>> {
+    // The datasheet's procedure runs at 80 Hz and +/- 12 gauss
+    let test_config = crate::MagConfig {
+        odr: crate::MagOdr::Hz80,
+        full_scale: crate::MagFullScale::Gauss12,
+        performance: crate::MagPerformance::LowPower,
+        ..config
+    };
+    bus::write_all(
+        bus,
+        &[
+            (crate::LIS3MDL_CTRL_REG1, test_config.ctrl_reg1()),
+            (crate::LIS3MDL_CTRL_REG2, test_config.ctrl_reg2()),
+        ],
+    )?;
+    // The first sample is discarded while the output settles
+    let baseline = average_samples(bus, 1)?;
+    // ST
+    bus::write_config(bus, crate::LIS3MDL_CTRL_REG1, test_config.ctrl_reg1() | 0b1)?;
+    // And then around 60ms, or 5 samples at 80 Hz
+    let stimulated = average_samples(bus, 5)?;
+    Ok(bus::delta_xyz(stimulated, baseline))
+}
+
+// Averages 10 self-test samples, after discarding those taken while the
+// output settles.
+fn average_samples<B: RegisterAccess>(
+    bus: &mut B,
+    discard: u16,
+) -> Result<(i32, i32, i32), crate::Error<B::Error>> {
+    // ZYXDA
+    bus::average_xyz(
+        bus,
+        crate::LIS3MDL_STATUS_REG,
+        0b1000,
+        crate::LIS3MDL_OUT_X_L,
+        crate::Endianness::Little,
+        discard,
+        10,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeBus;
+    use crate::{MagConfig, MagFullScale, MagMode, MagOdr, MagPerformance};
+
+    #[test]
+    fn read_config() {
+        let config = MagConfig::builder()
+            .odr(MagOdr::Hz20)
+            .full_scale(MagFullScale::Gauss16)
+            .performance(MagPerformance::Medium)
+            .z_axis_performance(MagPerformance::UltraHigh)
+            .bdu(false)
+            .build();
+        let mut mag = Magnetometer::from_bus(FakeBus::lis3mdl(), config).unwrap();
+        assert_eq!(mag.read_config().unwrap(), config);
+    }
+
+    #[test]
+    fn performance_setters() {
+        let mut mag = Magnetometer::from_bus(FakeBus::lis3mdl(), MagConfig::default()).unwrap();
+        mag.set_z_axis_performance(MagPerformance::LowPower)
+            .unwrap();
+        let config = mag.read_config().unwrap();
+        assert_eq!(config, mag.config());
+        assert_eq!(config.performance, MagPerformance::High);
+        assert_eq!(config.z_performance, MagPerformance::LowPower);
+
+        // Every axis is brought back in line
+        mag.set_performance(MagPerformance::UltraHigh).unwrap();
+        let config = mag.read_config().unwrap();
+        assert_eq!(config, mag.config());
+        assert_eq!(config.performance, MagPerformance::UltraHigh);
+        assert_eq!(config.z_performance, MagPerformance::UltraHigh);
+    }
+
+    #[test]
+    fn self_test_restores_registers() {
+        let bus = FakeBus::lis3mdl();
+        let config = MagConfig::builder()
+            .odr(MagOdr::Hz10)
+            .full_scale(MagFullScale::Gauss4)
+            .build();
+        let mut mag = Magnetometer::from_bus(bus.clone(), config).unwrap();
+        let registers = [crate::LIS3MDL_CTRL_REG1, crate::LIS3MDL_CTRL_REG2];
+        let before = registers.map(|reg| bus.get(reg));
+        // ZYXDA
+        bus.set(crate::LIS3MDL_STATUS_REG, 0b1000);
+
+        // The fake's output doesn't change
+        let result = mag.run_self_test().unwrap();
+        assert!(!result.passed);
+        assert_eq!(result.delta, (0, 0, 0).into());
+        // ST, at 80 Hz in low-power mode
+        assert!(bus
+            .writes_to(crate::LIS3MDL_CTRL_REG1)
+            .contains(&0b1001_1101));
+        assert_eq!(registers.map(|reg| bus.get(reg)), before);
+        assert_eq!(mag.read_config().unwrap(), config);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn self_test_times_out() {
+        let bus = FakeBus::lis3mdl();
+        let mut mag = Magnetometer::from_bus(bus.clone(), MagConfig::default()).unwrap();
+        let registers = [crate::LIS3MDL_CTRL_REG1, crate::LIS3MDL_CTRL_REG2];
+        let before = registers.map(|reg| bus.get(reg));
+        // ZYXDA is never set
+        assert!(matches!(
+            mag.run_self_test(),
+            Err(crate::Error::Timeout {
+                reg: crate::LIS3MDL_STATUS_REG,
+                ..
+            })
+        ));
+        assert_eq!(registers.map(|reg| bus.get(reg)), before);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn software_reset_times_out() {
+        let bus = FakeBus::lis3mdl();
+        let mut mag = Magnetometer::from_bus(bus.clone(), MagConfig::default()).unwrap();
+        // SOFT_RST never clears, since the fake doesn't reset
+        assert!(matches!(
+            mag.software_reset(),
+            Err(crate::Error::Timeout {
+                reg: crate::LIS3MDL_CTRL_REG2,
+                ..
+            })
+        ));
+        let writes = bus.writes_to(crate::LIS3MDL_CTRL_REG2);
+        assert_eq!(writes.last(), Some(&0b0010_0100));
+    }
+
+    #[test]
+    fn starts_in_configured_mode() {
+        let bus = FakeBus::lis3mdl();
+        let config = MagConfig::builder().mode(MagMode::PowerDown).build();
+        let mut mag = Magnetometer::from_bus(bus.clone(), config).unwrap();
+        assert_eq!(bus.get(crate::LIS3MDL_CTRL_REG3), 0b11);
+        assert!(!mag.is_enabled());
+        assert!(matches!(mag.read(), Err(crate::Error::PoweredDown)));
+        assert_eq!(mag.read_config().unwrap(), config);
+        mag.set_enabled(true).unwrap();
+        assert_eq!(mag.read_config().unwrap(), MagConfig::default());
+        // Other software setting LP, which no configuration does
+        bus.set(crate::LIS3MDL_CTRL_REG3, 0b0010_0000);
+        assert!(matches!(
+            mag.read_config(),
+            Err(crate::Error::UnknownRegisterValue {
+                reg: crate::LIS3MDL_CTRL_REG3,
+                value: 0b0010_0000,
+            })
+        ));
+    }
+
+    #[test]
+    fn bdu_stays_set() {
+        let bus = FakeBus::lis3mdl();
+        let bdu = || bus.get(crate::LIS3MDL_CTRL_REG5) & 0b0100_0000 != 0;
+        let mut mag = Magnetometer::from_bus(bus.clone(), MagConfig::default()).unwrap();
+        assert!(bdu());
+        mag.set_output_data_rate(MagOdr::Hz10).unwrap();
+        mag.set_performance(MagPerformance::Medium).unwrap();
+        mag.set_z_axis_performance(MagPerformance::LowPower)
+            .unwrap();
+        mag.set_full_scale(MagFullScale::Gauss4).unwrap();
+        mag.set_enabled(false).unwrap();
+        mag.set_enabled(true).unwrap();
+        // ZYXDA
+        bus.set(crate::LIS3MDL_STATUS_REG, 0b1000);
+        mag.run_self_test().unwrap();
+        #[cfg(feature = "std")]
+        mag.read_single(None).unwrap();
+        mag.reboot_memory().unwrap();
+        assert!(bdu());
+    }
+
+    #[test]
+    fn verifies_writes() {
+        // A lost write is retried
+        let bus = FakeBus::lis3mdl();
+        bus.verify();
+        bus.lose_writes_to(crate::LIS3MDL_CTRL_REG2, 1);
+        let mut mag = Magnetometer::from_bus(bus.clone(), MagConfig::default()).unwrap();
+        assert_eq!(bus.writes_to(crate::LIS3MDL_CTRL_REG2), [0x20, 0x20]);
+        assert_eq!(mag.read_config().unwrap(), MagConfig::default());
+
+        // But not twice
+        let bus = FakeBus::lis3mdl();
+        bus.verify();
+        bus.lose_writes_to(crate::LIS3MDL_CTRL_REG2, 2);
+        assert!(matches!(
+            Magnetometer::from_bus(bus.clone(), MagConfig::default()),
+            Err(crate::Error::WriteMismatch {
+                reg: crate::LIS3MDL_CTRL_REG2,
+                expected: 0x20,
+                actual: 0,
+            })
+        ));
+
+        let bus = FakeBus::lis3mdl();
+        bus.verify();
+        bus.fail_writes();
+        assert!(matches!(
+            Magnetometer::from_bus(bus.clone(), MagConfig::default()),
+            Err(crate::Error::Write { .. })
+        ));
+    }
+
+    #[test]
+    fn drop_ignores_bus_errors() {
+        for fail in [FakeBus::fail_reads, FakeBus::fail_writes] {
+            let bus = FakeBus::lis3mdl();
+            let config = MagConfig::builder().power_down_on_drop(true).build();
+            let mag = Magnetometer::from_bus(bus.clone(), config).unwrap();
+            fail(&bus);
+            drop(mag);
+            // Still in continuous-conversion mode
+            assert_eq!(bus.get(crate::LIS3MDL_CTRL_REG3), 0);
+        }
+    }
+}
@@ -0,0 +1,2137 @@
+//! Readers for the LSM6DSL accelerometer & gyroscope, generic over the bus
+//! they're accessed through. See the backend modules, e.g. `i2c`, for
+//! constructors.
+
+use crate::bus::{self, RegisterAccess};
+
+/// The chip found where the LSM6DSL is expected. Newer boards ship with the
+/// LSM6DSO, which is configured slightly differently, but whose output
+/// registers are read the same way. Some clones and older boards ship with
+/// the LSM6DS3, which is configured like the LSM6DSL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Lsm6dsl,
+    Lsm6dso,
+    Lsm6ds3,
+}
+
+impl Variant {
+    /// Identifies the chip from the value of its WHO_AM_I register.
+    pub(crate) fn from_who_am_i<E>(response: u8) -> Result<Self, crate::Error<E>> {
+        match response {
+            0x6A => Ok(Variant::Lsm6dsl),
+            0x6C => Ok(Variant::Lsm6dso),
+            0x69 => Ok(Variant::Lsm6ds3),
+            _ => Err(crate::Error::Init {
+                expected: crate::Chip::Lsm6dsl.who_am_i(),
+                actual: response,
+            }),
+        }
+    }
+}
+
+fn identify<B: RegisterAccess>(bus: &mut B) -> Result<Variant, crate::Error<B::Error>> {
+    let who_am_i_response = bus.read_register(crate::Chip::Lsm6dsl.who_am_i_register())?;
+    Variant::from_who_am_i(who_am_i_response)
+}
+
+/// The state of the FIFO.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FifoStatus {
+    /// The number of unread 16-bit words.
+    pub count: u16,
+    /// Whether the watermark has been reached.
+    pub watermark: bool,
+    /// Whether samples have been overwritten, or dropped, since the FIFO
+    /// filled up.
+    pub overrun: bool,
+    /// Whether the FIFO will be full once the next sample is stored.
+    pub full: bool,
+    /// Whether the FIFO is empty.
+    pub empty: bool,
+}
+
+impl FifoStatus {
+    fn decode(block: [u8; 2]) -> Self {
+        Self {
+            count: u16::from_le_bytes(block) & 0x07FF,
+            watermark: block[1] & 0b1000_0000 != 0,
+            overrun: block[1] & 0b0100_0000 != 0,
+            full: block[1] & 0b0010_0000 != 0,
+            empty: block[1] & 0b0001_0000 != 0,
+        }
+    }
+}
+
+/// The register writes that configure the accelerometer, besides
+/// `accel_shared_bits`.
+pub(crate) fn accel_init_writes(
+    config: &crate::AccelConfig,
+    variant: Variant,
+) -> Result<[(u8, u8); 2], &'static str> {
+    config.validate()?;
+    Ok([
+        (crate::LSM6DSL_CTRL1_XL, config.ctrl1_xl(variant)),
+        (crate::LSM6DSL_CTRL8_XL, config.ctrl8_xl(variant)),
+    ])
+}
+
+/// The accelerometer's bits of the registers it shares with the gyroscope,
+/// as (register, mask, value), which are set without touching the others.
+pub(crate) fn accel_shared_bits(config: &crate::AccelConfig) -> [(u8, u8, u8); 2] {
+    [
+        // BDU, IF_INC and BLE
+        (crate::LSM6DSL_CTRL3_C, 0b0100_0110, config.ctrl3_c()),
+        // XL_HM_MODE, since FTYPE is the gyroscope's
+        (crate::LSM6DSL_CTRL6_C, 0b0001_0000, config.ctrl6_c()),
+    ]
+}
+
+/// Whether a sensor is powered, from its ODR register, `CTRL1_XL` or
+/// `CTRL2_G`.
+pub(crate) fn is_powered(odr_reg: u8) -> bool {
+    odr_reg & 0xF0 != 0
+}
+
+/// Checks that a reader of one sensor can use `endianness` once the other
+/// sensor's been powered, e.g. by another reader of the same chip, since
+/// they share the BLE bit of `CTRL3_C`.
+pub(crate) fn check_shared_endianness(
+    ctrl3_c: u8,
+    endianness: crate::Endianness,
+) -> Result<(), &'static str> {
+    if crate::Endianness::from_ctrl3_c(ctrl3_c) == endianness {
+        Ok(())
+    } else {
+        Err("the other sensor on the chip uses a different endianness")
+    }
+}
+
+/// The register writes that configure the gyroscope, besides
+/// `gyro_shared_bits`.
+pub(crate) fn gyro_init_writes(
+    config: &crate::GyroConfig,
+    variant: Variant,
+) -> Result<[(u8, u8); 2], &'static str> {
+    validate_gyro(config, variant)?;
+    Ok([
+        (crate::LSM6DSL_CTRL2_G, config.ctrl2_g()),
+        (crate::LSM6DSL_CTRL7_G, config.ctrl7_g()),
+    ])
+}
+
+/// Checks that a gyroscope configuration can be applied to the variant.
+fn validate_gyro(config: &crate::GyroConfig, variant: Variant) -> Result<(), &'static str> {
+    config.validate()?;
+    if variant == Variant::Lsm6ds3 && config.low_pass.is_some() {
+        return Err("the LSM6DS3 has no gyroscope low-pass filter");
+    }
+    Ok(())
+}
+
+/// The gyroscope's bits of the registers it shares with the accelerometer,
+/// as (register, mask, value), which are set without touching the others.
+pub(crate) fn gyro_shared_bits(
+    config: &crate::GyroConfig,
+    variant: Variant,
+) -> impl Iterator<Item = (u8, u8, u8)> {
+    let [ctrl3_c, ctrl4_c, ctrl6_c] = [
+        // BLE
+        (crate::LSM6DSL_CTRL3_C, 0b10, config.endianness.ctrl3_c()),
+        // LPF1_SEL_G and FTYPE, which the LSM6DS3 lacks
+        (crate::LSM6DSL_CTRL4_C, 0b10, config.ctrl4_c()),
+        (
+            crate::LSM6DSL_CTRL6_C,
+            ftype_mask(variant),
+            config.ctrl6_c(),
+        ),
+    ];
+    let filters = (variant != Variant::Lsm6ds3).then_some([ctrl4_c, ctrl6_c]);
+    core::iter::once(ctrl3_c).chain(filters.into_iter().flatten())
+}
+
+// FTYPE is 2 bits on the LSM6DSL, and 3 on the LSM6DSO, whose upper bit is
+// cleared. XL_HM_MODE is left as is.
+fn ftype_mask(variant: Variant) -> u8 {
+    match variant {
+        Variant::Lsm6dsl | Variant::Lsm6ds3 => 0b011,
+        Variant::Lsm6dso => 0b111,
+    }
+}
+
+/// The register writes that configure the FIFO.
+fn fifo_writes(config: &crate::FifoConfig) -> Result<[(u8, u8); 4], &'static str> {
+    config.validate()?;
+    let [watermark_l, watermark_h] = config.watermark.to_le_bytes();
+    Ok([
+        (crate::LSM6DSL_FIFO_CTRL1, watermark_l),
+        (crate::LSM6DSL_FIFO_CTRL2, watermark_h),
+        (crate::LSM6DSL_FIFO_CTRL3, config.fifo_ctrl3()),
+        (crate::LSM6DSL_FIFO_CTRL5, config.fifo_ctrl5()),
+    ])
+}
+
+// Fails if the other sensor, whose ODR is in `other_odr_reg`, is already
+// configured with a different endianness
+fn check_endianness<B: RegisterAccess>(
+    bus: &mut B,
+    other_odr_reg: u8,
+    endianness: crate::Endianness,
+) -> Result<(), crate::Error<B::Error>> {
+    if !is_powered(bus.read_register(other_odr_reg)?) {
+        return Ok(());
+    }
+    let ctrl3_c = bus.read_register(crate::LSM6DSL_CTRL3_C)?;
+    check_shared_endianness(ctrl3_c, endianness).map_err(crate::Error::InvalidConfig)
+}
+
+fn init_accel<B: RegisterAccess>(
+    bus: &mut B,
+    config: crate::AccelConfig,
+    variant: Variant,
+) -> Result<(), crate::Error<B::Error>> {
+    let writes = accel_init_writes(&config, variant).map_err(crate::Error::InvalidConfig)?;
+    bus::write_all(bus, &writes)?;
+    for (reg, mask, value) in accel_shared_bits(&config) {
+        bus::modify(bus, reg, mask, value)?;
+    }
+    Ok(())
+}
+
+fn init_gyro<B: RegisterAccess>(
+    bus: &mut B,
+    config: crate::GyroConfig,
+    variant: Variant,
+) -> Result<(), crate::Error<B::Error>> {
+    let writes = gyro_init_writes(&config, variant).map_err(crate::Error::InvalidConfig)?;
+    bus::write_all(bus, &writes)?;
+    for (reg, mask, value) in gyro_shared_bits(&config, variant) {
+        bus::modify(bus, reg, mask, value)?;
+    }
+    Ok(())
+}
+
+use crate::{LSM6DSL_OUTX_L_G as OUTX_L_G, LSM6DSL_OUTX_L_XL as OUTX_L_XL};
+
+// The STATUS_REG bits that signal a new sample
+const XLDA: u8 = 0b01;
+const GDA: u8 = 0b10;
+
+fn data_ready<B: RegisterAccess>(bus: &mut B, mask: u8) -> Result<bool, crate::Error<B::Error>> {
+    Ok(bus.read_register(crate::LSM6DSL_STATUS_REG)? & mask != 0)
+}
+
+// Averages 5 self-test samples, after discarding those taken while the
+// output settles.
+fn average_samples<B: RegisterAccess>(
+    bus: &mut B,
+    ready_mask: u8,
+    reg: u8,
+    endianness: crate::Endianness,
+    discard: u16,
+) -> Result<(i32, i32, i32), crate::Error<B::Error>> {
+    let status_reg = crate::LSM6DSL_STATUS_REG;
+    bus::average_xyz(bus, status_reg, ready_mask, reg, endianness, discard, 5)
+}
+
+// How much the accelerometer's averaged output changes in the positive sign
+// self-test, leaving the registers for the caller to restore.
+fn accel_self_test_delta<B: RegisterAccess>(
+    bus: &mut B,
+    config: crate::AccelConfig,
+    variant: Variant,
+) -> Result<(i32, i32, i32), crate::Error<B::Error>> {
+    // The datasheet's procedure runs at 52 Hz and +/- 4g, unfiltered, with
+    // block data update
+    let test_config = crate::AccelConfig {
+        odr: crate::AccelOdr::Hz52,
+        full_scale: crate::AccelFullScale::G4,
+        power_mode: crate::PowerMode::HighPerformance,
+        filter: crate::AccelFilter::Bypass,
+        ..config
+    };
+    let writes = [
+        (crate::LSM6DSL_CTRL1_XL, test_config.ctrl1_xl(variant)),
+        (crate::LSM6DSL_CTRL8_XL, test_config.ctrl8_xl(variant)),
+    ];
+    bus::write_all(bus, &writes)?;
+    for (reg, mask, value) in accel_shared_bits(&test_config) {
+        bus::modify(bus, reg, mask, value)?;
+    }
+    bus::modify(bus, crate::LSM6DSL_CTRL3_C, 0b0100_0000, 0b0100_0000)?;
+    // The output settles within around 100ms, or 6 samples at 52 Hz
+    let endianness = config.endianness;
+    let baseline = average_samples(bus, XLDA, OUTX_L_XL, endianness, 6)?;
+    // Positive sign self-test, in ST_XL
+    bus::modify(bus, crate::LSM6DSL_CTRL5_C, 0b11, 0b01)?;
+    let stimulated = average_samples(bus, XLDA, OUTX_L_XL, endianness, 6)?;
+    Ok(bus::delta_xyz(stimulated, baseline))
+}
+
+// How much the gyroscope's averaged output changes in the positive sign
+// self-test, leaving the registers for the caller to restore.
+fn gyro_self_test_delta<B: RegisterAccess>(
+    bus: &mut B,
+    config: crate::GyroConfig,
+    variant: Variant,
+) -> Result<(i32, i32, i32), crate::Error<B::Error>> {
+    // The datasheet's procedure runs at 208 Hz and 2000 dps, without the
+    // high-pass filter
+    let test_config = crate::GyroConfig {
+        odr: crate::GyroOdr::Hz208,
+        full_scale: crate::GyroFullScale::Dps2000,
+        power_mode: crate::GyroPowerMode::HighPerformance,
+        high_pass: None,
+        ..config
+    };
+    let writes = gyro_init_writes(&test_config, variant).map_err(crate::Error::InvalidConfig)?;
+    bus::write_all(bus, &writes)?;
+    // The output settles within around 150ms, or 32 samples at 208 Hz
+    let endianness = config.endianness;
+    let baseline = average_samples(bus, GDA, OUTX_L_G, endianness, 32)?;
+    // Positive sign self-test, in ST_G
+    bus::modify(bus, crate::LSM6DSL_CTRL5_C, 0b1100, 0b0100)?;
+    // And then within 50ms, or 11 samples
+    let stimulated = average_samples(bus, GDA, OUTX_L_G, endianness, 11)?;
+    Ok(bus::delta_xyz(stimulated, baseline))
+}
+
+fn software_reset<B: RegisterAccess>(bus: &mut B) -> Result<(), crate::Error<B::Error>> {
+    bus::set_self_clearing(bus, crate::LSM6DSL_CTRL3_C, 0b1, 0b1)?;
+    // SW_RESET clears itself once the reset completes, in around 50us
+    bus::wait_until_clear(bus, crate::LSM6DSL_CTRL3_C, 0b1)
+}
+
+// Resets the chip, optionally reloading its trimming parameters too
+fn reset<B: RegisterAccess>(bus: &mut B, reboot: bool) -> Result<(), crate::Error<B::Error>> {
+    software_reset(bus)?;
+    if reboot {
+        // BOOT, keeping IF_INC set, which clears itself in around 15ms
+        bus::set_self_clearing(bus, crate::LSM6DSL_CTRL3_C, 0b1000_0000, 0b1000_0000)?;
+        bus::wait_until_clear(bus, crate::LSM6DSL_CTRL3_C, 0b1000_0000)?;
+    }
+    Ok(())
+}
+
+/// An accelerometer reader.
+pub struct Accelerometer<B: RegisterAccess> {
+    bus: B,
+    config: crate::AccelConfig,
+    variant: Variant,
+    // The filter that `disable_high_pass` restores
+    filter_before_high_pass: crate::AccelFilter,
+    enabled: bool,
+}
+
+impl<B: RegisterAccess> Accelerometer<B> {
+    /// Creates a new accelerometer reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    /// * `config`: The accelerometer configuration.
+    pub fn from_bus(
+        mut bus: B,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<B::Error>> {
+        let variant = identify(&mut bus)?;
+        Accelerometer::from_bus_with_variant(bus, config, variant)
+    }
+
+    /// Creates a new accelerometer reader from a bus, without checking the
+    /// chip's WHO_AM_I register, for clones that report an unrecognized
+    /// value.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    /// * `config`: The accelerometer configuration.
+    /// * `variant`: The chip to configure the clone as.
+    pub fn from_bus_with_variant(
+        mut bus: B,
+        config: crate::AccelConfig,
+        variant: Variant,
+    ) -> Result<Self, crate::Error<B::Error>> {
+        check_endianness(&mut bus, crate::LSM6DSL_CTRL2_G, config.endianness)?;
+        init_accel(&mut bus, config, variant)?;
+        let filter_before_high_pass = match config.filter {
+            crate::AccelFilter::HighPass(_) => crate::AccelFilter::Bypass,
+            filter => filter,
+        };
+        Ok(Self {
+            bus,
+            config,
+            variant,
+            filter_before_high_pass,
+            enabled: true,
+        })
+    }
+
+    /// The chip that was found.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// The current configuration, including any changes made since the
+    /// reader was created.
+    pub fn config(&self) -> crate::AccelConfig {
+        self.config
+    }
+
+    /// Reads the configuration the chip is actually running with, e.g. to
+    /// debug it or after other software configured the chip. The output
+    /// data rate reads as powered down while the accelerometer is. Bit
+    /// patterns that no configuration corresponds to fail with
+    /// `Error::UnknownRegisterValue`.
+    pub fn read_config(&mut self) -> Result<crate::AccelConfig, crate::Error<B::Error>> {
+        let mut values = [0; 4];
+        for (value, reg) in values.iter_mut().zip([
+            crate::LSM6DSL_CTRL1_XL,
+            crate::LSM6DSL_CTRL3_C,
+            crate::LSM6DSL_CTRL6_C,
+            crate::LSM6DSL_CTRL8_XL,
+        ]) {
+            *value = self.bus.read_register(reg)?;
+        }
+        crate::AccelConfig::decode(self.variant, values, self.config.power_down_on_drop)
+    }
+
+    /// Changes the output data rate, leaving the rest of the configuration,
+    /// e.g. the full-scale range, as is.
+    ///
+    /// # Arguments
+    /// * `odr`: The output data rate.
+    pub fn set_output_data_rate(
+        &mut self,
+        odr: crate::AccelOdr,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::AccelConfig { odr, ..self.config };
+        config.validate().map_err(crate::Error::InvalidConfig)?;
+        // While powered down, the rate's applied on powering back up
+        if self.enabled {
+            let ctrl1_xl = config.ctrl1_xl(self.variant);
+            bus::modify(&mut self.bus, crate::LSM6DSL_CTRL1_XL, 0xF0, ctrl1_xl)?;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    /// Powers the accelerometer down, or back up at the configured output
+    /// data rate. Reads fail with `Error::PoweredDown` while it's powered
+    /// down.
+    ///
+    /// # Arguments
+    /// * `enabled`: Whether the accelerometer is powered.
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), crate::Error<B::Error>> {
+        let odr = if enabled {
+            self.config.odr
+        } else {
+            crate::AccelOdr::PowerDown
+        };
+        let ctrl1_xl = crate::AccelConfig { odr, ..self.config }.ctrl1_xl(self.variant);
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL1_XL, 0xF0, ctrl1_xl)?;
+        self.enabled = enabled;
+        Ok(())
+    }
+
+    /// Whether the accelerometer is powered, i.e. hasn't been powered down
+    /// with `set_enabled`.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Fails rather than reading a stale sample
+    fn check_enabled(&self) -> Result<(), crate::Error<B::Error>> {
+        if self.enabled {
+            Ok(())
+        } else {
+            Err(crate::Error::PoweredDown)
+        }
+    }
+
+    /// Changes the full-scale range, leaving the rest of the configuration,
+    /// e.g. the output data rate, as is. Subsequent scaled readings use the
+    /// new range's sensitivity.
+    ///
+    /// # Arguments
+    /// * `full_scale`: The full-scale range.
+    pub fn set_full_scale(
+        &mut self,
+        full_scale: crate::AccelFullScale,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::AccelConfig {
+            full_scale,
+            ..self.config
+        };
+        let ctrl1_xl = config.ctrl1_xl(self.variant);
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL1_XL, 0x0C, ctrl1_xl)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Whether a new sample is available, which reading it clears.
+    pub fn data_ready(&mut self) -> Result<bool, crate::Error<B::Error>> {
+        data_ready(&mut self.bus, XLDA)
+    }
+
+    /// Waits until a new sample is available, e.g. to avoid reading a stale
+    /// one in single-shot applications.
+    ///
+    /// # Arguments
+    /// * `timeout`: How long to wait before giving up with `Error::Timeout`,
+    ///   or `None` to wait indefinitely.
+    #[cfg(feature = "std")]
+    pub fn wait_for_data_ready(
+        &mut self,
+        timeout: Option<core::time::Duration>,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let start = std::time::Instant::now();
+        while !data_ready(&mut self.bus, XLDA)? {
+            let elapsed = start.elapsed();
+            if timeout.is_some_and(|timeout| elapsed >= timeout) {
+                return Err(crate::Error::Timeout {
+                    reg: crate::LSM6DSL_STATUS_REG,
+                    elapsed,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the raw accelerometer values.
+    pub fn read(&mut self) -> Result<crate::AccelReading, crate::Error<B::Error>> {
+        self.check_enabled()?;
+        let endianness = self.config.endianness;
+        Ok(bus::read_xyz_ordered(&mut self.bus, OUTX_L_XL, endianness)?.into())
+    }
+
+    /// Read the accelerometer values, in g.
+    pub fn read_scaled(&mut self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        self.check_enabled()?;
+        let endianness = self.config.endianness;
+        let xyz = bus::read_xyz_ordered(&mut self.bus, OUTX_L_XL, endianness)?;
+        let sensitivity = self.config.full_scale.sensitivity();
+        Ok(crate::scale_xyz(xyz, sensitivity))
+    }
+
+    /// Changes the power mode, leaving the rest of the configuration as is.
+    /// A mode that isn't available at the current output data rate is
+    /// rejected with `Error::InvalidConfig`, so the rate may need to be
+    /// changed first.
+    ///
+    /// # Arguments
+    /// * `power_mode`: The power mode.
+    pub fn set_power_mode(
+        &mut self,
+        power_mode: crate::PowerMode,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::AccelConfig {
+            power_mode,
+            ..self.config
+        };
+        config.validate().map_err(crate::Error::InvalidConfig)?;
+        let ctrl6_c = config.ctrl6_c();
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL6_C, 0b0001_0000, ctrl6_c)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Changes the digital filtering, leaving the rest of the configuration
+    /// as is. Readings are distorted until the new filter settles, after
+    /// `AccelFilter::settling_samples` samples, so this can discard those
+    /// before returning.
+    ///
+    /// # Arguments
+    /// * `filter`: The digital filtering.
+    /// * `settle`: Whether to wait for the filter to settle, discarding the
+    ///   readings until then. This is skipped while the accelerometer is
+    ///   powered down.
+    pub fn set_filter(
+        &mut self,
+        filter: crate::AccelFilter,
+        settle: bool,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::AccelConfig {
+            filter,
+            ..self.config
+        };
+        let ctrl1_xl = config.ctrl1_xl(self.variant);
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL1_XL, 0b11, ctrl1_xl)?;
+        let ctrl8_xl = config.ctrl8_xl(self.variant);
+        bus::write_config(&mut self.bus, crate::LSM6DSL_CTRL8_XL, ctrl8_xl)?;
+        self.config = config;
+        if !matches!(filter, crate::AccelFilter::HighPass(_)) {
+            self.filter_before_high_pass = filter;
+        }
+        if settle && self.enabled && config.odr != crate::AccelOdr::PowerDown {
+            for _ in 0..filter.settling_samples() {
+                // Wait for XLDA, which reading the sample clears
+                bus::wait_until_set(&mut self.bus, crate::LSM6DSL_STATUS_REG, XLDA)?;
+                bus::read_xyz_ordered(&mut self.bus, OUTX_L_XL, config.endianness)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Enables the high-pass filter, e.g. to remove gravity from the
+    /// readings for vibration monitoring. This replaces the low-pass
+    /// filter, which `disable_high_pass` restores.
+    ///
+    /// # Arguments
+    /// * `cutoff`: The high-pass filter's cutoff.
+    pub fn enable_high_pass(
+        &mut self,
+        cutoff: crate::HighPassCutoff,
+    ) -> Result<(), crate::Error<B::Error>> {
+        self.set_filter(crate::AccelFilter::HighPass(cutoff), false)
+    }
+
+    /// Disables the high-pass filter, restoring the most recent other
+    /// filtering, or bypassing the filters if there wasn't any.
+    pub fn disable_high_pass(&mut self) -> Result<(), crate::Error<B::Error>> {
+        self.set_filter(self.filter_before_high_pass, false)
+    }
+
+    /// Runs the self-test, which applies an internal force to the sensing
+    /// element and checks that the output changes by as much as the
+    /// datasheet specifies. The accelerometer should be kept still
+    /// throughout. The registers the test changes are restored afterwards,
+    /// even if it fails partway. The delta is at +/- 4g.
+    pub fn run_self_test(
+        &mut self,
+    ) -> Result<crate::SelfTestResult<crate::AccelReading>, crate::Error<B::Error>> {
+        self.check_enabled()?;
+        // CTRL5_C first, so the self-test ends before the rest is restored
+        let mut saved = [
+            (crate::LSM6DSL_CTRL5_C, 0),
+            (crate::LSM6DSL_CTRL1_XL, 0),
+            (crate::LSM6DSL_CTRL8_XL, 0),
+            (crate::LSM6DSL_CTRL6_C, 0),
+            (crate::LSM6DSL_CTRL3_C, 0),
+        ];
+        for (reg, value) in saved.iter_mut() {
+            *value = self.bus.read_register(*reg)?;
+        }
+        let delta = accel_self_test_delta(&mut self.bus, self.config, self.variant);
+        bus::write_all(&mut self.bus, &saved)?;
+        let delta = crate::AccelReading::from(delta?);
+        let mg_per_lsb = crate::AccelFullScale::G4.sensitivity() * 1000.0;
+        let passed = [delta.x, delta.y, delta.z].iter().all(|&lsb| {
+            let mg = lsb as f32 * mg_per_lsb;
+            (60.0..=3000.0).contains(&mg)
+        });
+        Ok(crate::SelfTestResult { passed, delta })
+    }
+
+    /// Configures the FIFO, which buffers samples so they can be drained in
+    /// bursts rather than polled.
+    ///
+    /// # Arguments
+    /// * `config`: The FIFO configuration.
+    pub fn configure_fifo(
+        &mut self,
+        config: crate::FifoConfig,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let writes = fifo_writes(&config).map_err(crate::Error::InvalidConfig)?;
+        bus::write_all(&mut self.bus, &writes)
+    }
+
+    /// Routes the FIFO watermark interrupt to a pin, so that the FIFO can be
+    /// drained when it's signaled rather than polled. The polarity applies
+    /// to both pins' interrupts.
+    ///
+    /// # Arguments
+    /// * `pin`: The pin to signal the interrupt on.
+    /// * `active_high`: Whether the pin is driven high, rather than low,
+    ///   while the interrupt is signaled.
+    pub fn configure_fifo_interrupt(
+        &mut self,
+        pin: crate::IntPin,
+        active_high: bool,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let reg = match pin {
+            crate::IntPin::Int1 => crate::LSM6DSL_INT1_CTRL,
+            crate::IntPin::Int2 => crate::LSM6DSL_INT2_CTRL,
+        };
+        // H_LACTIVE selects active low
+        let h_lactive = (!active_high as u8) << 5;
+        bus::modify(
+            &mut self.bus,
+            crate::LSM6DSL_CTRL3_C,
+            0b0010_0000,
+            h_lactive,
+        )?;
+        // INT1_FTH or INT2_FTH
+        bus::modify(&mut self.bus, reg, 0b0000_1000, 0b0000_1000)
+    }
+
+    /// Read the state of the FIFO, from `FIFO_STATUS1` and `FIFO_STATUS2`.
+    pub fn read_fifo_status(&mut self) -> Result<FifoStatus, crate::Error<B::Error>> {
+        let mut block = [0; 2];
+        self.bus
+            .read_registers(crate::LSM6DSL_FIFO_STATUS1, &mut block)?;
+        Ok(FifoStatus::decode(block))
+    }
+
+    /// Read the number of unread 16-bit words in the FIFO.
+    pub fn read_fifo_count(&mut self) -> Result<u16, crate::Error<B::Error>> {
+        Ok(self.read_fifo_status()?.count)
+    }
+
+    /// Whether the FIFO will be full once the next sample is stored.
+    pub fn is_fifo_full(&mut self) -> Result<bool, crate::Error<B::Error>> {
+        Ok(self.read_fifo_status()?.full)
+    }
+
+    /// Whether samples have been lost since the FIFO filled up.
+    pub fn is_fifo_overrun(&mut self) -> Result<bool, crate::Error<B::Error>> {
+        Ok(self.read_fifo_status()?.overrun)
+    }
+
+    /// Drain raw samples from the FIFO, returning how many were read. This
+    /// reads up to as many samples as fit in `buf`, and no more than are
+    /// stored. With both sensors' samples stored, they alternate,
+    /// gyroscope first.
+    ///
+    /// # Arguments
+    /// * `buf`: The buffer to read samples into.
+    pub fn read_fifo_burst(
+        &mut self,
+        buf: &mut [(i16, i16, i16)],
+    ) -> Result<usize, crate::Error<B::Error>> {
+        let stored = usize::from(self.read_fifo_count()? / 3);
+        let count = stored.min(buf.len());
+        for sample in &mut buf[..count] {
+            // The register address rolls back from FIFO_DATA_OUT_H to
+            // FIFO_DATA_OUT_L, so each burst reads the next three words
+            let mut block = [0; 6];
+            self.bus
+                .read_registers(crate::LSM6DSL_FIFO_DATA_OUT_L, &mut block)?;
+            *sample = (
+                i16::from_le_bytes([block[0], block[1]]),
+                i16::from_le_bytes([block[2], block[3]]),
+                i16::from_le_bytes([block[4], block[5]]),
+            );
+        }
+        Ok(count)
+    }
+
+    /// Read the raw value of the embedded temperature sensor.
+    pub fn read_temperature_raw(&mut self) -> Result<i16, crate::Error<B::Error>> {
+        let mut block = [0; 2];
+        self.bus
+            .read_registers(crate::LSM6DSL_OUT_TEMP_L, &mut block)?;
+        Ok(self.config.endianness.decode(block))
+    }
+
+    /// Read the embedded temperature sensor, in degrees celsius.
+    pub fn read_temperature_celsius(&mut self) -> Result<f32, crate::Error<B::Error>> {
+        let raw = self.read_temperature_raw()?;
+        Ok(crate::temperature_celsius(raw))
+    }
+
+    /// Resets every register of the chip to its default, e.g. to recover
+    /// from the chip hanging. This leaves the chip unconfigured, and this
+    /// reader unusable, so afterwards a new reader should be created, e.g.
+    /// with `new_with_config`, or `reset` should be used instead.
+    pub fn software_reset(&mut self) -> Result<(), crate::Error<B::Error>> {
+        software_reset(&mut self.bus)
+    }
+
+    /// Resets every register of the chip to its default, e.g. to recover
+    /// from a brown-out, and then reapplies this reader's configuration.
+    /// Settings changed since, e.g. the FIFO and interrupts, aren't
+    /// restored, and neither are the gyroscope's, which shares the chip.
+    /// With `std`, this fails with `Error::Timeout` if the chip doesn't
+    /// complete the reset.
+    ///
+    /// # Arguments
+    /// * `reboot`: Whether to also reload the chip's trimming parameters
+    ///   from its flash memory.
+    pub fn reset(&mut self, reboot: bool) -> Result<(), crate::Error<B::Error>> {
+        reset(&mut self.bus, reboot)?;
+        init_accel(&mut self.bus, self.config, self.variant)?;
+        self.enabled = true;
+        Ok(())
+    }
+
+    /// Read a run of consecutive registers, bypassing the typed API, e.g. to
+    /// inspect the raw bytes behind a reading.
+    ///
+    /// # Arguments
+    /// * `start`: The address of the first register.
+    /// * `count`: The number of registers to read.
+    #[cfg(feature = "std")]
+    pub fn read_raw_block(
+        &mut self,
+        start: u8,
+        count: u8,
+    ) -> Result<Vec<u8>, crate::Error<B::Error>> {
+        bus::read_block(&mut self.bus, start, count)
+    }
+
+    /// Read a register directly, for functionality the typed API doesn't
+    /// cover.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    #[doc(hidden)]
+    pub fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<B::Error>> {
+        self.bus.read_register(reg)
+    }
+
+    /// Write a register directly, for functionality the typed API doesn't
+    /// cover. The caller is responsible for not corrupting the state the
+    /// reader relies on: `CTRL1_XL` and `CTRL2_G` hold the configured data
+    /// rates and full-scale ranges, which the scaled readings assume, and
+    /// `CTRL3_C` must keep IF_INC set for multi byte reads to work.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    /// * `value`: The value to write.
+    #[doc(hidden)]
+    pub fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<B::Error>> {
+        self.bus.write_register(reg, value)
+    }
+}
+
+impl<B: RegisterAccess> Drop for Accelerometer<B> {
+    fn drop(&mut self) {
+        if self.config.power_down_on_drop {
+            // Errors are ignored, since there's no way to report them
+            let _ = self.set_enabled(false);
+        }
+    }
+}
+
+/// A gyroscope reader.
+pub struct Gyroscope<B: RegisterAccess> {
+    bus: B,
+    config: crate::GyroConfig,
+    variant: Variant,
+    enabled: bool,
+    asleep: bool,
+}
+
+impl<B: RegisterAccess> Gyroscope<B> {
+    /// Creates a new gyroscope reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    /// * `config`: The gyroscope configuration.
+    pub fn from_bus(mut bus: B, config: crate::GyroConfig) -> Result<Self, crate::Error<B::Error>> {
+        let variant = identify(&mut bus)?;
+        Gyroscope::from_bus_with_variant(bus, config, variant)
+    }
+
+    /// Creates a new gyroscope reader from a bus, without checking the
+    /// chip's WHO_AM_I register, for clones that report an unrecognized
+    /// value.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    /// * `config`: The gyroscope configuration.
+    /// * `variant`: The chip to configure the clone as.
+    pub fn from_bus_with_variant(
+        mut bus: B,
+        config: crate::GyroConfig,
+        variant: Variant,
+    ) -> Result<Self, crate::Error<B::Error>> {
+        // Enable the gyroscope
+        check_endianness(&mut bus, crate::LSM6DSL_CTRL1_XL, config.endianness)?;
+        init_gyro(&mut bus, config, variant)?;
+        Ok(Self {
+            bus,
+            config,
+            variant,
+            enabled: true,
+            asleep: false,
+        })
+    }
+
+    /// The chip that was found.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// The current configuration, including any changes made since the
+    /// reader was created.
+    pub fn config(&self) -> crate::GyroConfig {
+        self.config
+    }
+
+    /// Reads the configuration the chip is actually running with, e.g. to
+    /// debug it or after other software configured the chip. The output
+    /// data rate reads as powered down while the gyroscope is. Bit patterns
+    /// that no configuration corresponds to fail with
+    /// `Error::UnknownRegisterValue`.
+    pub fn read_config(&mut self) -> Result<crate::GyroConfig, crate::Error<B::Error>> {
+        let mut values = [0; 5];
+        for (value, reg) in values.iter_mut().zip([
+            crate::LSM6DSL_CTRL2_G,
+            crate::LSM6DSL_CTRL3_C,
+            crate::LSM6DSL_CTRL4_C,
+            crate::LSM6DSL_CTRL6_C,
+            crate::LSM6DSL_CTRL7_G,
+        ]) {
+            *value = self.bus.read_register(reg)?;
+        }
+        let power_down_on_drop = self.config.power_down_on_drop;
+        crate::GyroConfig::decode(self.variant, values, power_down_on_drop)
+    }
+
+    /// Changes the output data rate, leaving the rest of the configuration,
+    /// e.g. the full-scale range, as is.
+    ///
+    /// # Arguments
+    /// * `odr`: The output data rate.
+    pub fn set_output_data_rate(
+        &mut self,
+        odr: crate::GyroOdr,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::GyroConfig { odr, ..self.config };
+        config.validate().map_err(crate::Error::InvalidConfig)?;
+        // While powered down, the rate's applied on powering back up
+        if self.enabled {
+            let ctrl2_g = config.ctrl2_g();
+            bus::modify(&mut self.bus, crate::LSM6DSL_CTRL2_G, 0xF0, ctrl2_g)?;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    /// Changes the full-scale range, leaving the rest of the configuration,
+    /// e.g. the output data rate, as is. Subsequent scaled readings use the
+    /// new range's sensitivity.
+    ///
+    /// # Arguments
+    /// * `full_scale`: The full-scale range.
+    pub fn set_full_scale(
+        &mut self,
+        full_scale: crate::GyroFullScale,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::GyroConfig {
+            full_scale,
+            ..self.config
+        };
+        // Covers both FS_G and FS_125
+        let ctrl2_g = config.ctrl2_g();
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL2_G, 0x0E, ctrl2_g)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Changes the power mode, leaving the rest of the configuration as is.
+    /// A mode that isn't available at the current output data rate is
+    /// rejected with `Error::InvalidConfig`, since the chip would otherwise
+    /// silently stay in high-performance mode, so the rate may need to be
+    /// changed first.
+    ///
+    /// # Arguments
+    /// * `power_mode`: The power mode.
+    pub fn set_power_mode(
+        &mut self,
+        power_mode: crate::GyroPowerMode,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::GyroConfig {
+            power_mode,
+            ..self.config
+        };
+        config.validate().map_err(crate::Error::InvalidConfig)?;
+        let ctrl7_g = config.ctrl7_g();
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL7_G, 0b1000_0000, ctrl7_g)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Powers the gyroscope down, or back up at the configured output data
+    /// rate. Reads fail with `Error::PoweredDown` while it's powered down.
+    ///
+    /// # Arguments
+    /// * `enabled`: Whether the gyroscope is powered.
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), crate::Error<B::Error>> {
+        let odr = if enabled {
+            self.config.odr
+        } else {
+            crate::GyroOdr::PowerDown
+        };
+        let ctrl2_g = crate::GyroConfig { odr, ..self.config }.ctrl2_g();
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL2_G, 0xF0, ctrl2_g)?;
+        self.enabled = enabled;
+        Ok(())
+    }
+
+    /// Whether the gyroscope is powered, i.e. hasn't been powered down with
+    /// `set_enabled`.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Puts the gyroscope to sleep, or wakes it. Unlike powering it down,
+    /// sleep keeps the filter chain running, so it wakes up faster. The
+    /// accelerometer is unaffected. Reads fail with `Error::Asleep` while
+    /// the gyroscope sleeps.
+    ///
+    /// # Arguments
+    /// * `sleep`: Whether the gyroscope sleeps.
+    pub fn set_sleep(&mut self, sleep: bool) -> Result<(), crate::Error<B::Error>> {
+        let sleep_g = (sleep as u8) << 6;
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL4_C, 0b0100_0000, sleep_g)?;
+        self.asleep = sleep;
+        Ok(())
+    }
+
+    /// Whether the gyroscope was put to sleep with `set_sleep`.
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    // Fails rather than reading a stale sample
+    fn check_readable(&self) -> Result<(), crate::Error<B::Error>> {
+        if !self.enabled {
+            Err(crate::Error::PoweredDown)
+        } else if self.asleep {
+            Err(crate::Error::Asleep)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Enables the high-pass filter, which removes the gyroscope's bias,
+    /// e.g. to stop it drifting an integrated heading.
+    ///
+    /// # Arguments
+    /// * `cutoff`: The high-pass filter's cutoff.
+    pub fn enable_high_pass(
+        &mut self,
+        cutoff: crate::GyroHighPass,
+    ) -> Result<(), crate::Error<B::Error>> {
+        self.set_high_pass(Some(cutoff))
+    }
+
+    /// Disables the high-pass filter.
+    pub fn disable_high_pass(&mut self) -> Result<(), crate::Error<B::Error>> {
+        self.set_high_pass(None)
+    }
+
+    fn set_high_pass(
+        &mut self,
+        high_pass: Option<crate::GyroHighPass>,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::GyroConfig {
+            high_pass,
+            ..self.config
+        };
+        // HP_EN_G and HPM_G
+        let ctrl7_g = config.ctrl7_g();
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL7_G, 0b0111_0000, ctrl7_g)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Enables the low-pass filter, LPF1, e.g. to stop noise above a control
+    /// loop's bandwidth making it ring. The LSM6DS3 has no LPF1, so this
+    /// fails with `Error::InvalidConfig` on it.
+    ///
+    /// # Arguments
+    /// * `bandwidth`: The low-pass filter's bandwidth.
+    pub fn enable_low_pass(
+        &mut self,
+        bandwidth: crate::GyroLpf1Bandwidth,
+    ) -> Result<(), crate::Error<B::Error>> {
+        self.set_low_pass(Some(bandwidth))
+    }
+
+    /// Disables the low-pass filter.
+    pub fn disable_low_pass(&mut self) -> Result<(), crate::Error<B::Error>> {
+        self.set_low_pass(None)
+    }
+
+    fn set_low_pass(
+        &mut self,
+        low_pass: Option<crate::GyroLpf1Bandwidth>,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::GyroConfig {
+            low_pass,
+            ..self.config
+        };
+        validate_gyro(&config, self.variant).map_err(crate::Error::InvalidConfig)?;
+        // Skips BLE, which is unchanged
+        for (reg, mask, value) in gyro_shared_bits(&config, self.variant).skip(1) {
+            bus::modify(&mut self.bus, reg, mask, value)?;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    /// Resets the high-pass filter, if it's enabled, to re-center it after a
+    /// large disturbance.
+    pub fn reset_high_pass(&mut self) -> Result<(), crate::Error<B::Error>> {
+        let ctrl7_g = self.bus.read_register(crate::LSM6DSL_CTRL7_G)?;
+        if ctrl7_g & 0b0100_0000 == 0 {
+            return Ok(());
+        }
+        let reset = match self.variant {
+            // The LSM6DS3 has a dedicated HP_G_RST bit
+            Variant::Lsm6ds3 => 0b0000_1000,
+            // Otherwise the filter's reset by toggling HP_EN_G
+            Variant::Lsm6dsl | Variant::Lsm6dso => 0b0100_0000,
+        };
+        bus::write_config(&mut self.bus, crate::LSM6DSL_CTRL7_G, ctrl7_g ^ reset)?;
+        bus::write_config(&mut self.bus, crate::LSM6DSL_CTRL7_G, ctrl7_g)
+    }
+
+    /// Runs the self-test, which applies an internal force to the sensing
+    /// element and checks that the output changes by as much as the
+    /// datasheet specifies. The gyroscope should be kept still throughout,
+    /// and the result isn't valid while the FIFO is capturing. The registers
+    /// the test changes are restored afterwards, even if it fails partway.
+    /// The delta is at 2000 dps.
+    pub fn run_self_test(
+        &mut self,
+    ) -> Result<crate::SelfTestResult<crate::GyroReading>, crate::Error<B::Error>> {
+        self.check_readable()?;
+        // CTRL5_C first, so the self-test ends before the rest is restored
+        let mut saved = [
+            (crate::LSM6DSL_CTRL5_C, 0),
+            (crate::LSM6DSL_CTRL2_G, 0),
+            (crate::LSM6DSL_CTRL7_G, 0),
+        ];
+        for (reg, value) in saved.iter_mut() {
+            *value = self.bus.read_register(*reg)?;
+        }
+        let delta = gyro_self_test_delta(&mut self.bus, self.config, self.variant);
+        bus::write_all(&mut self.bus, &saved)?;
+        let delta = crate::GyroReading::from(delta?);
+        let dps_per_lsb = crate::GyroFullScale::Dps2000.degrees_per_lsb();
+        let passed = [delta.x, delta.y, delta.z].iter().all(|&lsb| {
+            let dps = lsb as f32 * dps_per_lsb;
+            (150.0..=700.0).contains(&dps)
+        });
+        Ok(crate::SelfTestResult { passed, delta })
+    }
+
+    /// The conversion factor from raw readings to degrees per second for
+    /// the current full-scale range, in dps/LSB.
+    pub fn sensitivity_dps_per_lsb(&self) -> f32 {
+        self.config.full_scale.degrees_per_lsb()
+    }
+
+    /// Whether a new sample is available, which reading it clears.
+    pub fn data_ready(&mut self) -> Result<bool, crate::Error<B::Error>> {
+        data_ready(&mut self.bus, GDA)
+    }
+
+    /// Read the raw gyroscope values.
+    pub fn read(&mut self) -> Result<crate::GyroReading, crate::Error<B::Error>> {
+        self.check_readable()?;
+        let endianness = self.config.endianness;
+        Ok(bus::read_xyz_ordered(&mut self.bus, OUTX_L_G, endianness)?.into())
+    }
+
+    /// Read the gyroscope values, in degrees per second.
+    pub fn read_scaled(&mut self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        self.check_readable()?;
+        let endianness = self.config.endianness;
+        let xyz = bus::read_xyz_ordered(&mut self.bus, OUTX_L_G, endianness)?;
+        let sensitivity = self.config.full_scale.degrees_per_lsb();
+        Ok(crate::scale_xyz(xyz, sensitivity))
+    }
+
+    /// Resets every register of the chip to its default, e.g. to recover
+    /// from the chip hanging. This leaves the chip unconfigured, and this
+    /// reader unusable, so afterwards a new reader should be created, e.g.
+    /// with `new_with_config`, or `reset` should be used instead.
+    pub fn software_reset(&mut self) -> Result<(), crate::Error<B::Error>> {
+        software_reset(&mut self.bus)
+    }
+
+    /// Resets every register of the chip to its default, e.g. to recover
+    /// from a brown-out, and then reapplies this reader's configuration.
+    /// Settings changed since, e.g. sleep, aren't restored, and neither are
+    /// the accelerometer's, which shares the chip.
+    /// With `std`, this fails with `Error::Timeout` if the chip doesn't
+    /// complete the reset.
+    ///
+    /// # Arguments
+    /// * `reboot`: Whether to also reload the chip's trimming parameters
+    ///   from its flash memory.
+    pub fn reset(&mut self, reboot: bool) -> Result<(), crate::Error<B::Error>> {
+        reset(&mut self.bus, reboot)?;
+        // Restores IF_INC, which the accelerometer's configuration covers
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL3_C, 0b100, 0b100)?;
+        init_gyro(&mut self.bus, self.config, self.variant)?;
+        self.enabled = true;
+        self.asleep = false;
+        Ok(())
+    }
+
+    /// Read a run of consecutive registers, bypassing the typed API, e.g. to
+    /// inspect the raw bytes behind a reading.
+    ///
+    /// # Arguments
+    /// * `start`: The address of the first register.
+    /// * `count`: The number of registers to read.
+    #[cfg(feature = "std")]
+    pub fn read_raw_block(
+        &mut self,
+        start: u8,
+        count: u8,
+    ) -> Result<Vec<u8>, crate::Error<B::Error>> {
+        bus::read_block(&mut self.bus, start, count)
+    }
+
+    /// Read a register directly, for functionality the typed API doesn't
+    /// cover.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    #[doc(hidden)]
+    pub fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<B::Error>> {
+        self.bus.read_register(reg)
+    }
+
+    /// Write a register directly, for functionality the typed API doesn't
+    /// cover. The caller is responsible for not corrupting the state the
+    /// reader relies on: `CTRL1_XL` and `CTRL2_G` hold the configured data
+    /// rates and full-scale ranges, which the scaled readings assume, and
+    /// `CTRL3_C` must keep IF_INC set for multi byte reads to work.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    /// * `value`: The value to write.
+    #[doc(hidden)]
+    pub fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<B::Error>> {
+        self.bus.write_register(reg, value)
+    }
+}
+
+impl<B: RegisterAccess> Drop for Gyroscope<B> {
+    fn drop(&mut self) {
+        if self.config.power_down_on_drop {
+            // Errors are ignored, since there's no way to report them
+            let _ = self.set_enabled(false);
+        }
+    }
+}
+
+/// A reader for both the accelerometer and gyroscope, which share the
+/// LSM6DSL chip. The chip is identified and configured once, rather than
+/// once per sensor, and the underlying device only needs to be opened once.
+pub struct Lsm6dsl<B: RegisterAccess> {
+    bus: B,
+    accel_config: crate::AccelConfig,
+    gyro_config: crate::GyroConfig,
+    variant: Variant,
+    accel_enabled: bool,
+    gyro_enabled: bool,
+    gyro_asleep: bool,
+}
+
+impl<B: RegisterAccess> Lsm6dsl<B> {
+    /// Creates a new LSM6DSL reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    pub fn from_bus(
+        mut bus: B,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<B::Error>> {
+        let variant = identify(&mut bus)?;
+        Lsm6dsl::from_bus_with_variant(bus, accel_config, gyro_config, variant)
+    }
+
+    /// Creates a new LSM6DSL reader from a bus, without checking the chip's
+    /// WHO_AM_I register, for clones that report an unrecognized value.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    /// * `variant`: The chip to configure the clone as.
+    pub fn from_bus_with_variant(
+        mut bus: B,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+        variant: Variant,
+    ) -> Result<Self, crate::Error<B::Error>> {
+        if accel_config.endianness != gyro_config.endianness {
+            return Err(crate::Error::InvalidConfig(
+                "the accelerometer and gyroscope endianness differ",
+            ));
+        }
+        init_accel(&mut bus, accel_config, variant)?;
+        init_gyro(&mut bus, gyro_config, variant)?;
+        Ok(Self {
+            bus,
+            accel_config,
+            gyro_config,
+            variant,
+            accel_enabled: true,
+            gyro_enabled: true,
+            gyro_asleep: false,
+        })
+    }
+
+    /// The chip that was found.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// The current accelerometer configuration, including any changes made
+    /// since the reader was created.
+    pub fn accel_config(&self) -> crate::AccelConfig {
+        self.accel_config
+    }
+
+    /// The current gyroscope configuration, including any changes made
+    /// since the reader was created.
+    pub fn gyro_config(&self) -> crate::GyroConfig {
+        self.gyro_config
+    }
+
+    /// Changes the accelerometer's output data rate, leaving the rest of its
+    /// configuration, e.g. the full-scale range, as is.
+    ///
+    /// # Arguments
+    /// * `odr`: The output data rate.
+    pub fn set_accel_output_data_rate(
+        &mut self,
+        odr: crate::AccelOdr,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::AccelConfig {
+            odr,
+            ..self.accel_config
+        };
+        config.validate().map_err(crate::Error::InvalidConfig)?;
+        // While powered down, the rate's applied on powering back up
+        if self.accel_enabled {
+            let ctrl1_xl = config.ctrl1_xl(self.variant);
+            bus::modify(&mut self.bus, crate::LSM6DSL_CTRL1_XL, 0xF0, ctrl1_xl)?;
+        }
+        self.accel_config = config;
+        Ok(())
+    }
+
+    /// Changes the accelerometer's full-scale range, leaving the rest of its
+    /// configuration, e.g. the output data rate, as is. Subsequent scaled
+    /// readings use the new range's sensitivity.
+    ///
+    /// # Arguments
+    /// * `full_scale`: The full-scale range.
+    pub fn set_accel_full_scale(
+        &mut self,
+        full_scale: crate::AccelFullScale,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::AccelConfig {
+            full_scale,
+            ..self.accel_config
+        };
+        let ctrl1_xl = config.ctrl1_xl(self.variant);
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL1_XL, 0x0C, ctrl1_xl)?;
+        self.accel_config = config;
+        Ok(())
+    }
+
+    /// Changes the gyroscope's output data rate, leaving the rest of its
+    /// configuration, e.g. the full-scale range, as is.
+    ///
+    /// # Arguments
+    /// * `odr`: The output data rate.
+    pub fn set_gyro_output_data_rate(
+        &mut self,
+        odr: crate::GyroOdr,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::GyroConfig {
+            odr,
+            ..self.gyro_config
+        };
+        config.validate().map_err(crate::Error::InvalidConfig)?;
+        // While powered down, the rate's applied on powering back up
+        if self.gyro_enabled {
+            let ctrl2_g = config.ctrl2_g();
+            bus::modify(&mut self.bus, crate::LSM6DSL_CTRL2_G, 0xF0, ctrl2_g)?;
+        }
+        self.gyro_config = config;
+        Ok(())
+    }
+
+    /// Changes the gyroscope's full-scale range, leaving the rest of its
+    /// configuration, e.g. the output data rate, as is. Subsequent scaled
+    /// readings use the new range's sensitivity.
+    ///
+    /// # Arguments
+    /// * `full_scale`: The full-scale range.
+    pub fn set_gyro_full_scale(
+        &mut self,
+        full_scale: crate::GyroFullScale,
+    ) -> Result<(), crate::Error<B::Error>> {
+        let config = crate::GyroConfig {
+            full_scale,
+            ..self.gyro_config
+        };
+        // Covers both FS_G and FS_125
+        let ctrl2_g = config.ctrl2_g();
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL2_G, 0x0E, ctrl2_g)?;
+        self.gyro_config = config;
+        Ok(())
+    }
+
+    /// Powers the accelerometer down, or back up at the configured output
+    /// data rate. Accelerometer reads fail with `Error::PoweredDown` while
+    /// it's powered down.
+    ///
+    /// # Arguments
+    /// * `enabled`: Whether the accelerometer is powered.
+    pub fn set_accel_enabled(&mut self, enabled: bool) -> Result<(), crate::Error<B::Error>> {
+        let odr = if enabled {
+            self.accel_config.odr
+        } else {
+            crate::AccelOdr::PowerDown
+        };
+        let config = crate::AccelConfig {
+            odr,
+            ..self.accel_config
+        };
+        let ctrl1_xl = config.ctrl1_xl(self.variant);
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL1_XL, 0xF0, ctrl1_xl)?;
+        self.accel_enabled = enabled;
+        Ok(())
+    }
+
+    /// Whether the accelerometer is powered, i.e. hasn't been powered down
+    /// with `set_accel_enabled`.
+    pub fn is_accel_enabled(&self) -> bool {
+        self.accel_enabled
+    }
+
+    /// Powers the gyroscope down, or back up at the configured output data
+    /// rate. Gyroscope reads fail with `Error::PoweredDown` while it's
+    /// powered down.
+    ///
+    /// # Arguments
+    /// * `enabled`: Whether the gyroscope is powered.
+    pub fn set_gyro_enabled(&mut self, enabled: bool) -> Result<(), crate::Error<B::Error>> {
+        let odr = if enabled {
+            self.gyro_config.odr
+        } else {
+            crate::GyroOdr::PowerDown
+        };
+        let ctrl2_g = crate::GyroConfig {
+            odr,
+            ..self.gyro_config
+        }
+        .ctrl2_g();
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL2_G, 0xF0, ctrl2_g)?;
+        self.gyro_enabled = enabled;
+        Ok(())
+    }
+
+    /// Whether the gyroscope is powered, i.e. hasn't been powered down with
+    /// `set_gyro_enabled`.
+    pub fn is_gyro_enabled(&self) -> bool {
+        self.gyro_enabled
+    }
+
+    /// Powers both sensors down, e.g. to stop the chip drawing current
+    /// between bursts of readings. Reads fail with `Error::PoweredDown`
+    /// until the sensors are powered back up.
+    pub fn power_down(&mut self) -> Result<(), crate::Error<B::Error>> {
+        self.set_accel_enabled(false)?;
+        self.set_gyro_enabled(false)
+    }
+
+    /// Powers both sensors back up at their configured output data rates.
+    pub fn power_up(&mut self) -> Result<(), crate::Error<B::Error>> {
+        self.set_accel_enabled(true)?;
+        self.set_gyro_enabled(true)
+    }
+
+    /// Puts the gyroscope to sleep, or wakes it, leaving the accelerometer
+    /// running. Gyroscope reads fail with `Error::Asleep` while it sleeps.
+    ///
+    /// # Arguments
+    /// * `sleep`: Whether the gyroscope sleeps.
+    pub fn set_gyro_sleep(&mut self, sleep: bool) -> Result<(), crate::Error<B::Error>> {
+        let sleep_g = (sleep as u8) << 6;
+        bus::modify(&mut self.bus, crate::LSM6DSL_CTRL4_C, 0b0100_0000, sleep_g)?;
+        self.gyro_asleep = sleep;
+        Ok(())
+    }
+
+    /// Whether the gyroscope was put to sleep with `set_gyro_sleep`.
+    pub fn is_gyro_asleep(&self) -> bool {
+        self.gyro_asleep
+    }
+
+    // Fail rather than reading a stale sample
+    fn check_accel_readable(&self) -> Result<(), crate::Error<B::Error>> {
+        if self.accel_enabled {
+            Ok(())
+        } else {
+            Err(crate::Error::PoweredDown)
+        }
+    }
+
+    fn check_gyro_readable(&self) -> Result<(), crate::Error<B::Error>> {
+        if !self.gyro_enabled {
+            Err(crate::Error::PoweredDown)
+        } else if self.gyro_asleep {
+            Err(crate::Error::Asleep)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether a new accelerometer sample is available, which reading it
+    /// clears.
+    pub fn accel_data_ready(&mut self) -> Result<bool, crate::Error<B::Error>> {
+        data_ready(&mut self.bus, XLDA)
+    }
+
+    /// Whether a new gyroscope sample is available, which reading it clears.
+    pub fn gyro_data_ready(&mut self) -> Result<bool, crate::Error<B::Error>> {
+        data_ready(&mut self.bus, GDA)
+    }
+
+    /// Read the raw accelerometer values.
+    pub fn read_accel(&mut self) -> Result<crate::AccelReading, crate::Error<B::Error>> {
+        self.check_accel_readable()?;
+        let endianness = self.accel_config.endianness;
+        Ok(bus::read_xyz_ordered(&mut self.bus, OUTX_L_XL, endianness)?.into())
+    }
+
+    /// Read the accelerometer values, in g.
+    pub fn read_accel_scaled(&mut self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        self.check_accel_readable()?;
+        let endianness = self.accel_config.endianness;
+        let xyz = bus::read_xyz_ordered(&mut self.bus, OUTX_L_XL, endianness)?;
+        let sensitivity = self.accel_config.full_scale.sensitivity();
+        Ok(crate::scale_xyz(xyz, sensitivity))
+    }
+
+    /// Read the raw gyroscope values.
+    pub fn read_gyro(&mut self) -> Result<crate::GyroReading, crate::Error<B::Error>> {
+        self.check_gyro_readable()?;
+        let endianness = self.gyro_config.endianness;
+        Ok(bus::read_xyz_ordered(&mut self.bus, OUTX_L_G, endianness)?.into())
+    }
+
+    /// Read the gyroscope values, in degrees per second.
+    pub fn read_gyro_scaled(&mut self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        self.check_gyro_readable()?;
+        let endianness = self.gyro_config.endianness;
+        let xyz = bus::read_xyz_ordered(&mut self.bus, OUTX_L_G, endianness)?;
+        let sensitivity = self.gyro_config.full_scale.degrees_per_lsb();
+        Ok(crate::scale_xyz(xyz, sensitivity))
+    }
+
+    /// Read the raw accelerometer and gyroscope values in a single
+    /// transaction, so that both come from the same sample.
+    pub fn read_accel_gyro(
+        &mut self,
+    ) -> Result<(crate::AccelReading, crate::GyroReading), crate::Error<B::Error>> {
+        self.check_accel_readable()?;
+        self.check_gyro_readable()?;
+        // The gyroscope output registers immediately precede the
+        // accelerometer's
+        let mut block = [0; 12];
+        self.bus
+            .read_registers(crate::LSM6DSL_OUTX_L_G, &mut block)?;
+        let endianness = self.accel_config.endianness;
+        Ok((
+            crate::decode_xyz(&block[6..], endianness).into(),
+            crate::decode_xyz(&block[..6], endianness).into(),
+        ))
+    }
+
+    /// Resets every register of the chip to its default, e.g. to recover
+    /// from the chip hanging. This leaves the chip unconfigured, and this
+    /// reader unusable, so afterwards a new reader should be created, e.g.
+    /// with `new_with_config`, or `reset` should be used instead.
+    pub fn software_reset(&mut self) -> Result<(), crate::Error<B::Error>> {
+        software_reset(&mut self.bus)
+    }
+
+    /// Resets every register of the chip to its default, e.g. to recover
+    /// from a brown-out, and then reapplies this reader's configuration.
+    /// With `std`, this fails with `Error::Timeout` if the chip doesn't
+    /// complete the reset.
+    ///
+    /// # Arguments
+    /// * `reboot`: Whether to also reload the chip's trimming parameters
+    ///   from its flash memory.
+    pub fn reset(&mut self, reboot: bool) -> Result<(), crate::Error<B::Error>> {
+        reset(&mut self.bus, reboot)?;
+        init_accel(&mut self.bus, self.accel_config, self.variant)?;
+        init_gyro(&mut self.bus, self.gyro_config, self.variant)?;
+        self.accel_enabled = true;
+        self.gyro_enabled = true;
+        self.gyro_asleep = false;
+        Ok(())
+    }
+
+    /// Read a run of consecutive registers, bypassing the typed API, e.g. to
+    /// inspect the raw bytes behind a reading.
+    ///
+    /// # Arguments
+    /// * `start`: The address of the first register.
+    /// * `count`: The number of registers to read.
+    #[cfg(feature = "std")]
+    pub fn read_raw_block(
+        &mut self,
+        start: u8,
+        count: u8,
+    ) -> Result<Vec<u8>, crate::Error<B::Error>> {
+        bus::read_block(&mut self.bus, start, count)
+    }
+
+    /// Read a register directly, for functionality the typed API doesn't
+    /// cover.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    #[doc(hidden)]
+    pub fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<B::Error>> {
+        self.bus.read_register(reg)
+    }
+
+    /// Write a register directly, for functionality the typed API doesn't
+    /// cover. The caller is responsible for not corrupting the state the
+    /// reader relies on: `CTRL1_XL` and `CTRL2_G` hold the configured data
+    /// rates and full-scale ranges, which the scaled readings assume, and
+    /// `CTRL3_C` must keep IF_INC set for multi byte reads to work.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    /// * `value`: The value to write.
+    #[doc(hidden)]
+    pub fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<B::Error>> {
+        self.bus.write_register(reg, value)
+    }
+}
+
+impl<B: RegisterAccess> Drop for Lsm6dsl<B> {
+    fn drop(&mut self) {
+        // Errors are ignored, since there's no way to report them
+        if self.accel_config.power_down_on_drop {
+            let _ = bus::modify(&mut self.bus, crate::LSM6DSL_CTRL1_XL, 0xF0, 0);
+        }
+        if self.gyro_config.power_down_on_drop {
+            let _ = bus::modify(&mut self.bus, crate::LSM6DSL_CTRL2_G, 0xF0, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeBus;
+    use crate::{AccelConfig, AccelFullScale, AccelOdr, PowerMode};
+    use crate::{GyroConfig, GyroFullScale, GyroOdr};
+
+    #[test]
+    fn accel_output_data_rate() {
+        let bus = FakeBus::lsm6dsl();
+        let config = AccelConfig::builder()
+            .full_scale(AccelFullScale::G16)
+            .build();
+        let mut accel = Accelerometer::from_bus(bus.clone(), config).unwrap();
+        // The full-scale and anti-aliasing filter bits are kept
+        for (odr, ctrl1_xl) in [
+            (AccelOdr::Hz12_5, 0b0001_0111),
+            (AccelOdr::Hz26, 0b0010_0111),
+            (AccelOdr::Hz52, 0b0011_0111),
+            (AccelOdr::Hz104, 0b0100_0111),
+            (AccelOdr::Hz208, 0b0101_0111),
+            (AccelOdr::Hz416, 0b0110_0111),
+            (AccelOdr::Hz833, 0b0111_0111),
+            (AccelOdr::Hz1660, 0b1000_0111),
+            (AccelOdr::Hz3330, 0b1001_0111),
+            (AccelOdr::Hz6660, 0b1010_0111),
+            (AccelOdr::PowerDown, 0b0000_0111),
+        ] {
+            accel.set_output_data_rate(odr).unwrap();
+            assert_eq!(bus.get(crate::LSM6DSL_CTRL1_XL), ctrl1_xl, "{:?}", odr);
+        }
+    }
+
+    #[test]
+    fn accel_low_power_output_data_rate() {
+        let bus = FakeBus::lsm6dsl();
+        let config = AccelConfig::builder()
+            .odr(AccelOdr::Hz12_5)
+            .power_mode(PowerMode::LowPower)
+            .build();
+        let mut accel = Accelerometer::from_bus(bus.clone(), config).unwrap();
+        accel.set_output_data_rate(AccelOdr::Hz1_6).unwrap();
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL1_XL), 0b1011_1111);
+        // Only available in low-power mode, so the register isn't touched
+        let mut accel = Accelerometer::from_bus(bus.clone(), AccelConfig::default()).unwrap();
+        assert!(matches!(
+            accel.set_output_data_rate(AccelOdr::Hz1_6),
+            Err(crate::Error::InvalidConfig(_))
+        ));
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL1_XL), 0b1001_1111);
+    }
+
+    #[test]
+    fn gyro_output_data_rate() {
+        let bus = FakeBus::lsm6dsl();
+        let config = GyroConfig::builder()
+            .full_scale(GyroFullScale::Dps125)
+            .build();
+        let mut gyro = Gyroscope::from_bus(bus.clone(), config).unwrap();
+        // The full-scale bits are kept
+        for (odr, ctrl2_g) in [
+            (GyroOdr::Hz12_5, 0b0001_0010),
+            (GyroOdr::Hz26, 0b0010_0010),
+            (GyroOdr::Hz52, 0b0011_0010),
+            (GyroOdr::Hz104, 0b0100_0010),
+            (GyroOdr::Hz208, 0b0101_0010),
+            (GyroOdr::Hz416, 0b0110_0010),
+            (GyroOdr::Hz833, 0b0111_0010),
+            (GyroOdr::Hz1660, 0b1000_0010),
+            (GyroOdr::Hz3330, 0b1001_0010),
+            (GyroOdr::Hz6660, 0b1010_0010),
+            (GyroOdr::PowerDown, 0b0000_0010),
+        ] {
+            gyro.set_output_data_rate(odr).unwrap();
+            assert_eq!(bus.get(crate::LSM6DSL_CTRL2_G), ctrl2_g, "{:?}", odr);
+        }
+    }
+
+    #[test]
+    fn filter_settles() {
+        let bus = FakeBus::lsm6dsl();
+        let mut accel = Accelerometer::from_bus(bus.clone(), AccelConfig::default()).unwrap();
+        bus.set(crate::LSM6DSL_STATUS_REG, XLDA);
+        let filter = crate::AccelFilter::HighPass(crate::HighPassCutoff::Odr4);
+        accel.set_filter(filter, true).unwrap();
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL8_XL), 0b0000_0100);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn filter_settling_times_out() {
+        let bus = FakeBus::lsm6dsl();
+        let mut accel = Accelerometer::from_bus(bus.clone(), AccelConfig::default()).unwrap();
+        // XLDA is never set
+        let filter = crate::AccelFilter::HighPass(crate::HighPassCutoff::Odr4);
+        assert!(matches!(
+            accel.set_filter(filter, true),
+            Err(crate::Error::Timeout {
+                reg: crate::LSM6DSL_STATUS_REG,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn disable_high_pass_restores_low_pass() {
+        let bus = FakeBus::lsm6dsl();
+        let mut accel = Accelerometer::from_bus(bus.clone(), AccelConfig::default()).unwrap();
+        // LPF2_XL_EN, with the ODR/9 cutoff, and the composite filter input
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL8_XL), 0b1100_1000);
+        for cutoff in [
+            crate::HighPassCutoff::Odr4,
+            crate::HighPassCutoff::Odr100,
+            crate::HighPassCutoff::Odr9,
+            crate::HighPassCutoff::Odr400,
+        ] {
+            accel.enable_high_pass(cutoff).unwrap();
+            assert_ne!(bus.get(crate::LSM6DSL_CTRL8_XL), 0b1100_1000);
+            accel.disable_high_pass().unwrap();
+            assert_eq!(
+                bus.get(crate::LSM6DSL_CTRL8_XL),
+                0b1100_1000,
+                "{:?}",
+                cutoff
+            );
+            assert_eq!(accel.config().filter, AccelConfig::default().filter);
+        }
+    }
+
+    #[test]
+    fn gyro_filters() {
+        let bus = FakeBus::lsm6dsl();
+        let mut gyro = Gyroscope::from_bus(bus.clone(), GyroConfig::default()).unwrap();
+        gyro.enable_low_pass(crate::GyroLpf1Bandwidth::Narrowest)
+            .unwrap();
+        // LPF1_SEL_G, with FTYPE
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL4_C), 0b10);
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL6_C), 0b10);
+        gyro.enable_high_pass(crate::GyroHighPass::Hz0_26).unwrap();
+        // HP_EN_G and HPM_G
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL7_G), 0b0110_0000);
+        assert_eq!(
+            gyro.config().low_pass,
+            Some(crate::GyroLpf1Bandwidth::Narrowest)
+        );
+        assert_eq!(gyro.config().high_pass, Some(crate::GyroHighPass::Hz0_26));
+        assert_eq!(gyro.read_config().unwrap(), gyro.config());
+
+        gyro.disable_low_pass().unwrap();
+        gyro.disable_high_pass().unwrap();
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL4_C), 0);
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL7_G), 0);
+        assert_eq!(gyro.config(), GyroConfig::default());
+        assert_eq!(gyro.read_config().unwrap(), gyro.config());
+    }
+
+    #[test]
+    fn reset_high_pass_verifies_writes() {
+        let bus = FakeBus::lsm6dsl();
+        let mut gyro = Gyroscope::from_bus(bus.clone(), GyroConfig::default()).unwrap();
+        gyro.enable_high_pass(crate::GyroHighPass::Hz0_26).unwrap();
+        bus.verify();
+        // A lost write is retried
+        bus.lose_writes_to(crate::LSM6DSL_CTRL7_G, 1);
+        gyro.reset_high_pass().unwrap();
+        let writes = bus.writes_to(crate::LSM6DSL_CTRL7_G);
+        assert_eq!(
+            writes[writes.len() - 3..],
+            [0b0010_0000, 0b0010_0000, 0b0110_0000]
+        );
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL7_G), 0b0110_0000);
+        // But not twice
+        bus.lose_writes_to(crate::LSM6DSL_CTRL7_G, 2);
+        assert!(matches!(
+            gyro.reset_high_pass(),
+            Err(crate::Error::WriteMismatch {
+                reg: crate::LSM6DSL_CTRL7_G,
+                expected: 0b0010_0000,
+                actual: 0b0110_0000,
+            })
+        ));
+        bus.fail_writes();
+        assert!(matches!(
+            gyro.reset_high_pass(),
+            Err(crate::Error::Write {
+                reg: crate::LSM6DSL_CTRL7_G,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn gyro_filters_survive_accel_init() {
+        let bus = FakeBus::lsm6dsl();
+        let config = GyroConfig::builder()
+            .low_pass(crate::GyroLpf1Bandwidth::Narrow)
+            .high_pass(crate::GyroHighPass::Hz1_04)
+            .build();
+        let mut gyro = Gyroscope::from_bus(bus.clone(), config).unwrap();
+        // Configuring the accelerometer afterwards keeps FTYPE
+        let accel_config = AccelConfig::builder()
+            .odr(AccelOdr::Hz26)
+            .power_mode(PowerMode::LowPower)
+            .build();
+        Accelerometer::from_bus(bus.clone(), accel_config).unwrap();
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL6_C), 0b0001_0001);
+        assert_eq!(gyro.read_config().unwrap(), config);
+    }
+
+    #[test]
+    fn gyro_low_pass_unsupported() {
+        let bus = FakeBus::new();
+        // The LSM6DS3's WHO_AM_I
+        bus.set(crate::LSM6DSL_WHO_AM_I, 0x69);
+        let config = GyroConfig::builder()
+            .low_pass(crate::GyroLpf1Bandwidth::Wide)
+            .build();
+        assert!(matches!(
+            Gyroscope::from_bus(bus.clone(), config),
+            Err(crate::Error::InvalidConfig(_))
+        ));
+        let mut gyro = Gyroscope::from_bus(bus.clone(), GyroConfig::default()).unwrap();
+        assert!(gyro
+            .enable_low_pass(crate::GyroLpf1Bandwidth::Wide)
+            .is_err());
+        gyro.disable_low_pass().unwrap();
+        assert_eq!(bus.writes_to(crate::LSM6DSL_CTRL4_C), []);
+        assert_eq!(bus.writes_to(crate::LSM6DSL_CTRL6_C), []);
+    }
+
+    // The registers the accelerometer's self-test changes
+    const ACCEL_SELF_TEST_REGISTERS: [u8; 5] = [
+        crate::LSM6DSL_CTRL1_XL,
+        crate::LSM6DSL_CTRL3_C,
+        crate::LSM6DSL_CTRL5_C,
+        crate::LSM6DSL_CTRL6_C,
+        crate::LSM6DSL_CTRL8_XL,
+    ];
+
+    #[test]
+    fn accel_self_test_restores_registers() {
+        let bus = FakeBus::lsm6dsl();
+        let mut gyro = Gyroscope::from_bus(bus.clone(), GyroConfig::default()).unwrap();
+        gyro.enable_low_pass(crate::GyroLpf1Bandwidth::Narrowest)
+            .unwrap();
+        let config = AccelConfig::builder()
+            .odr(AccelOdr::Hz104)
+            .power_mode(PowerMode::Normal)
+            .bdu(false)
+            .build();
+        let mut accel = Accelerometer::from_bus(bus.clone(), config).unwrap();
+        // H_LACTIVE, which isn't part of the configuration
+        bus.set(
+            crate::LSM6DSL_CTRL3_C,
+            bus.get(crate::LSM6DSL_CTRL3_C) | 0b0010_0000,
+        );
+        let before = ACCEL_SELF_TEST_REGISTERS.map(|reg| bus.get(reg));
+        bus.set(crate::LSM6DSL_STATUS_REG, XLDA);
+
+        // The fake's output doesn't change
+        let result = accel.run_self_test().unwrap();
+        assert!(!result.passed);
+        assert_eq!(result.delta, (0, 0, 0).into());
+        // Positive sign self-test
+        assert_eq!(bus.writes_to(crate::LSM6DSL_CTRL5_C)[0], 0b01);
+        assert_eq!(ACCEL_SELF_TEST_REGISTERS.map(|reg| bus.get(reg)), before);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn accel_self_test_times_out() {
+        let bus = FakeBus::lsm6dsl();
+        let mut accel = Accelerometer::from_bus(bus.clone(), AccelConfig::default()).unwrap();
+        let before = ACCEL_SELF_TEST_REGISTERS.map(|reg| bus.get(reg));
+        // XLDA is never set
+        assert!(matches!(
+            accel.run_self_test(),
+            Err(crate::Error::Timeout {
+                reg: crate::LSM6DSL_STATUS_REG,
+                ..
+            })
+        ));
+        assert_eq!(ACCEL_SELF_TEST_REGISTERS.map(|reg| bus.get(reg)), before);
+    }
+
+    #[test]
+    fn gyro_self_test_restores_registers() {
+        let bus = FakeBus::lsm6dsl();
+        let config = GyroConfig::builder()
+            .odr(GyroOdr::Hz104)
+            .power_mode(crate::GyroPowerMode::Normal)
+            .high_pass(crate::GyroHighPass::Hz0_26)
+            .build();
+        let mut gyro = Gyroscope::from_bus(bus.clone(), config).unwrap();
+        let registers = [
+            crate::LSM6DSL_CTRL2_G,
+            crate::LSM6DSL_CTRL5_C,
+            crate::LSM6DSL_CTRL7_G,
+        ];
+        let before = registers.map(|reg| bus.get(reg));
+        bus.set(crate::LSM6DSL_STATUS_REG, GDA);
+
+        // The fake's output doesn't change
+        let result = gyro.run_self_test().unwrap();
+        assert!(!result.passed);
+        // Positive sign self-test
+        assert_eq!(bus.writes_to(crate::LSM6DSL_CTRL5_C)[0], 0b0100);
+        // At 208 Hz, without the high-pass filter
+        assert_eq!(bus.writes_to(crate::LSM6DSL_CTRL2_G)[1], 0b0101_1100);
+        assert_eq!(bus.writes_to(crate::LSM6DSL_CTRL7_G)[1], 0);
+        assert_eq!(registers.map(|reg| bus.get(reg)), before);
+
+        // GDA is never set, which only times out with std
+        #[cfg(feature = "std")]
+        {
+            bus.set(crate::LSM6DSL_STATUS_REG, 0);
+            assert!(matches!(
+                gyro.run_self_test(),
+                Err(crate::Error::Timeout { .. })
+            ));
+            assert_eq!(registers.map(|reg| bus.get(reg)), before);
+        }
+    }
+
+    #[test]
+    fn lsm6dsl_checks_readable() {
+        let bus = FakeBus::lsm6dsl();
+        let mut imu =
+            Lsm6dsl::from_bus(bus.clone(), AccelConfig::default(), GyroConfig::default()).unwrap();
+        imu.set_accel_enabled(false).unwrap();
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL1_XL) & 0xF0, 0);
+        assert!(matches!(imu.read_accel(), Err(crate::Error::PoweredDown)));
+        assert!(matches!(
+            imu.read_accel_scaled(),
+            Err(crate::Error::PoweredDown)
+        ));
+        assert!(matches!(
+            imu.read_accel_gyro(),
+            Err(crate::Error::PoweredDown)
+        ));
+        imu.read_gyro().unwrap();
+
+        // The rate's only applied on powering back up
+        imu.set_accel_output_data_rate(AccelOdr::Hz52).unwrap();
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL1_XL) & 0xF0, 0);
+        imu.set_accel_enabled(true).unwrap();
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL1_XL) & 0xF0, 0b0011_0000);
+        imu.read_accel().unwrap();
+
+        imu.set_gyro_sleep(true).unwrap();
+        assert!(matches!(imu.read_gyro(), Err(crate::Error::Asleep)));
+        assert!(matches!(imu.read_gyro_scaled(), Err(crate::Error::Asleep)));
+        imu.set_gyro_sleep(false).unwrap();
+        imu.set_gyro_enabled(false).unwrap();
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL2_G) & 0xF0, 0);
+        assert!(matches!(imu.read_gyro(), Err(crate::Error::PoweredDown)));
+        imu.read_accel().unwrap();
+    }
+
+    #[test]
+    fn separate_readers_share_endianness() {
+        let bus = FakeBus::lsm6dsl();
+        let big = GyroConfig::builder()
+            .endianness(crate::Endianness::Big)
+            .build();
+        let _gyro = Gyroscope::from_bus(bus.clone(), big).unwrap();
+        assert!(matches!(
+            Accelerometer::from_bus(bus.clone(), AccelConfig::default()),
+            Err(crate::Error::InvalidConfig(_))
+        ));
+        // BLE is left as the gyroscope set it
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL3_C), 0b10);
+        let big = AccelConfig::builder()
+            .endianness(crate::Endianness::Big)
+            .build();
+        let _accel = Accelerometer::from_bus(bus.clone(), big).unwrap();
+        // With BDU and IF_INC
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL3_C), 0b0100_0110);
+    }
+
+    #[test]
+    fn decode_both_endiannesses() {
+        let block = [0x01, 0x02, 0xFF, 0x7F, 0x00, 0x80];
+        assert_eq!(
+            crate::decode_xyz(&block, crate::Endianness::Little),
+            (0x0201, 0x7FFF, -0x8000)
+        );
+        assert_eq!(
+            crate::decode_xyz(&block, crate::Endianness::Big),
+            (0x0102, -0x81, 0x80)
+        );
+
+        // The readers decode in their configured order, and set BLE to match
+        for (endianness, ctrl3_c, expected) in [
+            (crate::Endianness::Little, 0x44, (0x0201, 0x7FFF, -0x8000)),
+            (crate::Endianness::Big, 0x46, (0x0102, -0x81, 0x80)),
+        ] {
+            let bus = FakeBus::lsm6dsl();
+            for (i, &byte) in block.iter().enumerate() {
+                bus.set(OUTX_L_XL + i as u8, byte);
+                bus.set(OUTX_L_G + i as u8, byte);
+            }
+            let config = AccelConfig::builder().endianness(endianness).build();
+            let mut accel = Accelerometer::from_bus(bus.clone(), config).unwrap();
+            let config = GyroConfig::builder().endianness(endianness).build();
+            let mut gyro = Gyroscope::from_bus(bus.clone(), config).unwrap();
+            assert_eq!(bus.get(crate::LSM6DSL_CTRL3_C), ctrl3_c);
+            assert_eq!(accel.read().unwrap(), expected.into());
+            assert_eq!(gyro.read().unwrap(), expected.into());
+        }
+    }
+
+    #[test]
+    fn lsm6dsl_power_down() {
+        let bus = FakeBus::lsm6dsl();
+        let mut imu =
+            Lsm6dsl::from_bus(bus.clone(), AccelConfig::default(), GyroConfig::default()).unwrap();
+        imu.power_down().unwrap();
+        // Only the ODR bits are cleared
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL1_XL), 0x0F);
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL2_G), 0x0C);
+        assert!(!imu.is_accel_enabled() && !imu.is_gyro_enabled());
+        assert!(matches!(imu.read_accel(), Err(crate::Error::PoweredDown)));
+        assert!(matches!(imu.read_gyro(), Err(crate::Error::PoweredDown)));
+
+        imu.power_up().unwrap();
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL1_XL), 0x9F);
+        assert_eq!(bus.get(crate::LSM6DSL_CTRL2_G), 0x9C);
+        imu.read_accel_gyro().unwrap();
+    }
+
+    #[test]
+    fn bdu_stays_set() {
+        let bus = FakeBus::lsm6dsl();
+        let bdu = || bus.get(crate::LSM6DSL_CTRL3_C) & 0b0100_0000 != 0;
+        let config = AccelConfig::builder()
+            .endianness(crate::Endianness::Big)
+            .build();
+        let mut accel = Accelerometer::from_bus(bus.clone(), config).unwrap();
+        assert!(bdu());
+        let config = GyroConfig::builder()
+            .endianness(crate::Endianness::Big)
+            .low_pass(crate::GyroLpf1Bandwidth::Narrow)
+            .build();
+        let mut gyro = Gyroscope::from_bus(bus.clone(), config).unwrap();
+        assert!(bdu(), "gyroscope init");
+        accel.set_output_data_rate(AccelOdr::Hz52).unwrap();
+        accel.set_full_scale(AccelFullScale::G2).unwrap();
+        accel.set_power_mode(PowerMode::LowPower).unwrap();
+        accel.set_filter(crate::AccelFilter::Bypass, false).unwrap();
+        accel.enable_high_pass(crate::HighPassCutoff::Odr9).unwrap();
+        accel.disable_high_pass().unwrap();
+        accel.set_enabled(false).unwrap();
+        accel.set_enabled(true).unwrap();
+        accel.configure_fifo(crate::FifoConfig::default()).unwrap();
+        accel
+            .configure_fifo_interrupt(crate::IntPin::Int1, false)
+            .unwrap();
+        assert!(bdu(), "accelerometer setters");
+        gyro.set_output_data_rate(GyroOdr::Hz208).unwrap();
+        gyro.set_full_scale(GyroFullScale::Dps500).unwrap();
+        gyro.set_power_mode(crate::GyroPowerMode::Normal).unwrap();
+        gyro.enable_high_pass(crate::GyroHighPass::Hz0_016).unwrap();
+        gyro.disable_low_pass().unwrap();
+        gyro.set_sleep(true).unwrap();
+        gyro.set_sleep(false).unwrap();
+        assert!(bdu(), "gyroscope setters");
+        bus.set(crate::LSM6DSL_STATUS_REG, XLDA | GDA);
+        accel.run_self_test().unwrap();
+        gyro.run_self_test().unwrap();
+        assert!(bdu(), "self-tests");
+
+        let bus = FakeBus::lsm6dsl();
+        let bdu = || bus.get(crate::LSM6DSL_CTRL3_C) & 0b0100_0000 != 0;
+        let mut imu =
+            Lsm6dsl::from_bus(bus.clone(), AccelConfig::default(), GyroConfig::default()).unwrap();
+        assert!(bdu(), "combined init");
+        imu.set_accel_output_data_rate(AccelOdr::Hz26).unwrap();
+        imu.set_accel_full_scale(AccelFullScale::G8).unwrap();
+        imu.set_gyro_output_data_rate(GyroOdr::Hz26).unwrap();
+        imu.set_gyro_full_scale(GyroFullScale::Dps250).unwrap();
+        imu.power_down().unwrap();
+        imu.power_up().unwrap();
+        imu.set_gyro_sleep(true).unwrap();
+        assert!(bdu(), "combined setters");
+    }
+
+    #[test]
+    fn drop_ignores_bus_errors() {
+        for fail in [FakeBus::fail_reads, FakeBus::fail_writes] {
+            let bus = FakeBus::lsm6dsl();
+            let config = AccelConfig::builder().power_down_on_drop(true).build();
+            let accel = Accelerometer::from_bus(bus.clone(), config).unwrap();
+            let config = GyroConfig::builder().power_down_on_drop(true).build();
+            let gyro = Gyroscope::from_bus(bus.clone(), config).unwrap();
+            let imu = Lsm6dsl::from_bus(
+                bus.clone(),
+                AccelConfig::builder().power_down_on_drop(true).build(),
+                GyroConfig::builder().power_down_on_drop(true).build(),
+            )
+            .unwrap();
+            fail(&bus);
+            drop(accel);
+            drop(gyro);
+            drop(imu);
+            // Nothing was powered down
+            assert_eq!(bus.get(crate::LSM6DSL_CTRL1_XL), 0x9F);
+            assert_eq!(bus.get(crate::LSM6DSL_CTRL2_G), 0x9C);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Readers for the BerryIMUv1's LSM9DS0, generic over the bus it's accessed
+//! through. These use fixed settings, matching ozzmaker's reference code. See
+//! `i2c::lsm9ds0` for constructors.
+
+use crate::bus::{self, RegisterAccess};
+
+// The register address only auto-increments during multi byte reads when its
+// MS bit is set.
+const AUTO_INCREMENT: u8 = 0x80;
+
+/// An accelerometer reader.
+pub struct Accelerometer<B: RegisterAccess> {
+    bus: B,
+}
+
+impl<B: RegisterAccess> Accelerometer<B> {
+    /// Creates a new accelerometer reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    pub fn from_bus(mut bus: B) -> Result<Self, crate::Error<B::Error>> {
+        bus::init(&mut bus, crate::Chip::Lsm9ds0AccelMag)?;
+        bus::write_all(
+            &mut bus,
+            &[
+                // z, y, x axis enabled, continuous update, 100 Hz ODR
+                (crate::LSM9DS0_CTRL_REG1_XM, 0b01100111),
+                (crate::LSM9DS0_CTRL_REG2_XM, 0b00100000), // +/- 16g
+            ],
+        )?;
+        Ok(Self { bus })
+    }
+
+    /// Read the raw accelerometer values.
+    pub fn read(&mut self) -> Result<crate::AccelReading, crate::Error<B::Error>> {
+        let reg = crate::LSM9DS0_OUT_X_L_A | AUTO_INCREMENT;
+        Ok(bus::read_xyz(&mut self.bus, reg)?.into())
+    }
+}
+
+/// A gyroscope reader.
+pub struct Gyroscope<B: RegisterAccess> {
+    bus: B,
+}
+
+impl<B: RegisterAccess> Gyroscope<B> {
+    /// Creates a new gyroscope reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    pub fn from_bus(mut bus: B) -> Result<Self, crate::Error<B::Error>> {
+        bus::init(&mut bus, crate::Chip::Lsm9ds0Gyro)?;
+        bus::write_all(
+            &mut bus,
+            &[
+                (crate::LSM9DS0_CTRL_REG1_G, 0b00001111), // Normal power mode, all axes enabled
+                (crate::LSM9DS0_CTRL_REG4_G, 0b00110000), // Continuous update, 2000 dps
+            ],
+        )?;
+        Ok(Self { bus })
+    }
+
+    /// Read the raw gyroscope values.
+    pub fn read(&mut self) -> Result<crate::GyroReading, crate::Error<B::Error>> {
+        let reg = crate::LSM9DS0_OUT_X_L_G | AUTO_INCREMENT;
+        Ok(bus::read_xyz(&mut self.bus, reg)?.into())
+    }
+}
+
+/// A magnetometer reader. The z axis is inverted relative to the BerryIMUv2
+/// and v3, so it's negated, allowing readings from every board to be
+/// tilt-compensated the same way.
+pub struct Magnetometer<B: RegisterAccess> {
+    bus: B,
+}
+
+impl<B: RegisterAccess> Magnetometer<B> {
+    /// Creates a new magnetometer reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    pub fn from_bus(mut bus: B) -> Result<Self, crate::Error<B::Error>> {
+        bus::init(&mut bus, crate::Chip::Lsm9ds0AccelMag)?;
+        bus::write_all(
+            &mut bus,
+            &[
+                (crate::LSM9DS0_CTRL_REG5_XM, 0b11110000), // Temperature enabled, 50 Hz ODR
+                (crate::LSM9DS0_CTRL_REG6_XM, 0b01100000), // +/- 12 gauss
+                (crate::LSM9DS0_CTRL_REG7_XM, 0b00000000), // Continuous-conversion mode
+            ],
+        )?;
+        Ok(Self { bus })
+    }
+
+    /// Read the raw magnetometer values.
+    pub fn read(&mut self) -> Result<crate::MagReading, crate::Error<B::Error>> {
+        let reg = crate::LSM9DS0_OUT_X_L_M | AUTO_INCREMENT;
+        let (x, y, z) = bus::read_xyz(&mut self.bus, reg)?;
+        Ok((x, y, -z).into())
+    }
+}
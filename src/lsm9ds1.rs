@@ -0,0 +1,95 @@
+//! Readers for the BerryIMUv2's LSM9DS1, generic over the bus it's accessed
+//! through. These use fixed settings, matching ozzmaker's reference code. See
+//! `i2c::lsm9ds1` for constructors.
+
+use crate::bus::{self, RegisterAccess};
+
+/// An accelerometer reader.
+pub struct Accelerometer<B: RegisterAccess> {
+    bus: B,
+}
+
+impl<B: RegisterAccess> Accelerometer<B> {
+    /// Creates a new accelerometer reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    pub fn from_bus(mut bus: B) -> Result<Self, crate::Error<B::Error>> {
+        bus::init(&mut bus, crate::Chip::Lsm9ds1AccelGyro)?;
+        bus::write_all(
+            &mut bus,
+            &[
+                (crate::LSM9DS1_CTRL_REG5_XL, 0b00111000), // z, y, x axis enabled
+                (crate::LSM9DS1_CTRL_REG6_XL, 0b00101000), // ODR 10 Hz, +/- 16g
+            ],
+        )?;
+        Ok(Self { bus })
+    }
+
+    /// Read the raw accelerometer values.
+    pub fn read(&mut self) -> Result<crate::AccelReading, crate::Error<B::Error>> {
+        Ok(bus::read_xyz(&mut self.bus, crate::LSM9DS1_OUT_X_L_XL)?.into())
+    }
+}
+
+/// A gyroscope reader.
+pub struct Gyroscope<B: RegisterAccess> {
+    bus: B,
+}
+
+impl<B: RegisterAccess> Gyroscope<B> {
+    /// Creates a new gyroscope reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    pub fn from_bus(mut bus: B) -> Result<Self, crate::Error<B::Error>> {
+        bus::init(&mut bus, crate::Chip::Lsm9ds1AccelGyro)?;
+        bus::write_all(
+            &mut bus,
+            &[
+                (crate::LSM9DS1_CTRL_REG4, 0b00111000), // z, y, x axis enabled
+                (crate::LSM9DS1_CTRL_REG1_G, 0b10111000), // ODR 476 Hz, 2000 dps
+            ],
+        )?;
+        Ok(Self { bus })
+    }
+
+    /// Read the raw gyroscope values.
+    pub fn read(&mut self) -> Result<crate::GyroReading, crate::Error<B::Error>> {
+        Ok(bus::read_xyz(&mut self.bus, crate::LSM9DS1_OUT_X_L_G)?.into())
+    }
+}
+
+/// A magnetometer reader.
+pub struct Magnetometer<B: RegisterAccess> {
+    bus: B,
+}
+
+impl<B: RegisterAccess> Magnetometer<B> {
+    /// Creates a new magnetometer reader from a bus.
+    ///
+    /// # Arguments
+    /// * `bus`: The bus.
+    pub fn from_bus(mut bus: B) -> Result<Self, crate::Error<B::Error>> {
+        bus::init(&mut bus, crate::Chip::Lsm9ds1Mag)?;
+        bus::write_all(
+            &mut bus,
+            &[
+                // Temperature compensation enabled, low-power mode, 80 Hz ODR
+                (crate::LSM9DS1_CTRL_REG1_M, 0b10011100),
+                (crate::LSM9DS1_CTRL_REG2_M, 0b01000000), // +/- 12 gauss
+                (crate::LSM9DS1_CTRL_REG3_M, 0b00000000), // Continuous-conversion mode
+                (crate::LSM9DS1_CTRL_REG4_M, 0b00000000), // Low-power mode for the z axis
+            ],
+        )?;
+        Ok(Self { bus })
+    }
+
+    /// Read the raw magnetometer values.
+    pub fn read(&mut self) -> Result<crate::MagReading, crate::Error<B::Error>> {
+        // The register address only auto-increments during multi byte reads
+        // when its MS bit is set.
+        let reg = crate::LSM9DS1_OUT_X_L_M | 0x80;
+        Ok(bus::read_xyz(&mut self.bus, reg)?.into())
+    }
+}
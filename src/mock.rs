@@ -0,0 +1,301 @@
+//! Readers backed by scripted register maps rather than a device, so that
+//! application logic can be developed and tested on any host. Create a
+//! `Board`, construct readers from it, then script their output with e.g.
+//! `Board::set_accel`.
+
+use crate::bus::RegisterAccess;
+use crate::imu;
+use crate::lis3mdl;
+use crate::lsm6dsl;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, PoisonError};
+
+type RegisterMap = [u8; 256];
+
+/// The register maps of a simulated BerryIMUv3. The WHO_AM_I registers are
+/// preset, so readers can be created immediately; configuration written by
+/// the readers can be inspected, and output registers scripted. Cloning the
+/// board yields another handle to the same register maps.
+#[derive(Clone)]
+pub struct Board(Arc<Mutex<(RegisterMap, RegisterMap)>>);
+
+impl Board {
+    /// Creates a new simulated board.
+    pub fn new() -> Self {
+        let mut lsm6dsl = [0; 256];
+        let mut lis3mdl = [0; 256];
+        let chip = crate::Chip::Lsm6dsl;
+        lsm6dsl[chip.who_am_i_register() as usize] = chip.who_am_i()[0];
+        let chip = crate::Chip::Lis3mdl;
+        lis3mdl[chip.who_am_i_register() as usize] = chip.who_am_i()[0];
+        Self(Arc::new(Mutex::new((lsm6dsl, lis3mdl))))
+    }
+
+    fn with_registers<T>(&self, chip: crate::Chip, f: impl FnOnce(&mut RegisterMap) -> T) -> T {
+        let mut maps = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        match chip {
+            crate::Chip::Lsm6dsl => f(&mut maps.0),
+            crate::Chip::Lis3mdl => f(&mut maps.1),
+            _ => unreachable!("the simulated board is a BerryIMUv3"),
+        }
+    }
+
+    fn set_xyz(&self, chip: crate::Chip, reg: u8, x: i32, y: i32, z: i32) {
+        self.with_registers(chip, |registers| {
+            for (i, value) in [x, y, z].into_iter().enumerate() {
+                let bytes = (value as i16).to_le_bytes();
+                registers[reg as usize + i * 2] = bytes[0];
+                registers[reg as usize + i * 2 + 1] = bytes[1];
+            }
+        })
+    }
+
+    /// Gets the value of an LSM6DSL register.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    pub fn lsm6dsl_register(&self, reg: u8) -> u8 {
+        self.with_registers(crate::Chip::Lsm6dsl, |registers| registers[reg as usize])
+    }
+
+    /// Sets the value of an LSM6DSL register.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    /// * `value`: The value.
+    pub fn set_lsm6dsl_register(&self, reg: u8, value: u8) {
+        self.with_registers(crate::Chip::Lsm6dsl, |registers| {
+            registers[reg as usize] = value
+        })
+    }
+
+    /// Gets the value of a LIS3MDL register.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    pub fn lis3mdl_register(&self, reg: u8) -> u8 {
+        self.with_registers(crate::Chip::Lis3mdl, |registers| registers[reg as usize])
+    }
+
+    /// Sets the value of a LIS3MDL register.
+    ///
+    /// # Arguments
+    /// * `reg`: The register address.
+    /// * `value`: The value.
+    pub fn set_lis3mdl_register(&self, reg: u8, value: u8) {
+        self.with_registers(crate::Chip::Lis3mdl, |registers| {
+            registers[reg as usize] = value
+        })
+    }
+
+    /// Sets the accelerometer output registers.
+    ///
+    /// # Arguments
+    /// * `reading`: The raw values to output.
+    pub fn set_accel(&self, reading: crate::AccelReading) {
+        let reg = crate::LSM6DSL_OUTX_L_XL;
+        self.set_xyz(crate::Chip::Lsm6dsl, reg, reading.x, reading.y, reading.z)
+    }
+
+    /// Sets the gyroscope output registers.
+    ///
+    /// # Arguments
+    /// * `reading`: The raw values to output.
+    pub fn set_gyro(&self, reading: crate::GyroReading) {
+        let reg = crate::LSM6DSL_OUTX_L_G;
+        self.set_xyz(crate::Chip::Lsm6dsl, reg, reading.x, reading.y, reading.z)
+    }
+
+    /// Sets the magnetometer output registers.
+    ///
+    /// # Arguments
+    /// * `reading`: The raw values to output.
+    pub fn set_mag(&self, reading: crate::MagReading) {
+        let reg = crate::LIS3MDL_OUT_X_L;
+        self.set_xyz(crate::Chip::Lis3mdl, reg, reading.x, reading.y, reading.z)
+    }
+
+    /// Sets the temperature sensor output registers.
+    ///
+    /// # Arguments
+    /// * `raw`: The raw value to output.
+    pub fn set_temperature_raw(&self, raw: i16) {
+        let [low, high] = raw.to_le_bytes();
+        self.set_lsm6dsl_register(crate::LSM6DSL_OUT_TEMP_L, low);
+        self.set_lsm6dsl_register(crate::LSM6DSL_OUT_TEMP_L + 1, high);
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Register access to a chip on a simulated board.
+pub struct MockInterface {
+    board: Board,
+    chip: crate::Chip,
+}
+
+impl RegisterAccess for MockInterface {
+    type Error = Infallible;
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<Infallible>> {
+        Ok(self
+            .board
+            .with_registers(self.chip, |registers| registers[reg as usize]))
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<Infallible>> {
+        self.board
+            .with_registers(self.chip, |registers| registers[reg as usize] = value);
+        Ok(())
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), crate::Error<Infallible>> {
+        let start = reg as usize;
+        if start + buf.len() > 256 {
+            return Err(crate::Error::Read { reg, cause: None });
+        }
+        self.board.with_registers(self.chip, |registers| {
+            buf.copy_from_slice(&registers[start..start + buf.len()])
+        });
+        Ok(())
+    }
+}
+
+/// An accelerometer reader.
+pub type Accelerometer = lsm6dsl::Accelerometer<MockInterface>;
+
+impl Accelerometer {
+    /// Creates a new accelerometer reader from a simulated board.
+    ///
+    /// # Arguments
+    /// * `board`: The simulated board.
+    pub fn new(board: &Board) -> Result<Self, crate::Error<Infallible>> {
+        Accelerometer::new_with_config(board, crate::AccelConfig::default())
+    }
+
+    /// Creates a new accelerometer reader from a simulated board, with the
+    /// given configuration.
+    ///
+    /// # Arguments
+    /// * `board`: The simulated board.
+    /// * `config`: The accelerometer configuration.
+    pub fn new_with_config(
+        board: &Board,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<Infallible>> {
+        let board = board.clone();
+        let chip = crate::Chip::Lsm6dsl;
+        Accelerometer::from_bus(MockInterface { board, chip }, config)
+    }
+}
+
+/// A gyroscope reader.
+pub type Gyroscope = lsm6dsl::Gyroscope<MockInterface>;
+
+impl Gyroscope {
+    /// Creates a new gyroscope reader from a simulated board.
+    ///
+    /// # Arguments
+    /// * `board`: The simulated board.
+    pub fn new(board: &Board) -> Result<Self, crate::Error<Infallible>> {
+        Gyroscope::new_with_config(board, crate::GyroConfig::default())
+    }
+
+    /// Creates a new gyroscope reader from a simulated board, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `board`: The simulated board.
+    /// * `config`: The gyroscope configuration.
+    pub fn new_with_config(
+        board: &Board,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<Infallible>> {
+        let board = board.clone();
+        let chip = crate::Chip::Lsm6dsl;
+        Gyroscope::from_bus(MockInterface { board, chip }, config)
+    }
+}
+
+/// A reader for both the accelerometer and gyroscope, which share the
+/// LSM6DSL chip.
+pub type Lsm6dsl = lsm6dsl::Lsm6dsl<MockInterface>;
+
+impl Lsm6dsl {
+    /// Creates a new LSM6DSL reader from a simulated board.
+    ///
+    /// # Arguments
+    /// * `board`: The simulated board.
+    pub fn new(board: &Board) -> Result<Self, crate::Error<Infallible>> {
+        Lsm6dsl::new_with_config(
+            board,
+            crate::AccelConfig::default(),
+            crate::GyroConfig::default(),
+        )
+    }
+
+    /// Creates a new LSM6DSL reader from a simulated board, with the given
+    /// accelerometer and gyroscope configurations.
+    ///
+    /// # Arguments
+    /// * `board`: The simulated board.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    pub fn new_with_config(
+        board: &Board,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<Infallible>> {
+        let board = board.clone();
+        let chip = crate::Chip::Lsm6dsl;
+        Lsm6dsl::from_bus(MockInterface { board, chip }, accel_config, gyro_config)
+    }
+}
+
+/// A magnetometer reader.
+pub type Magnetometer = lis3mdl::Magnetometer<MockInterface>;
+
+impl Magnetometer {
+    /// Creates a new magnetometer reader from a simulated board.
+    ///
+    /// # Arguments
+    /// * `board`: The simulated board.
+    pub fn new(board: &Board) -> Result<Self, crate::Error<Infallible>> {
+        Magnetometer::new_with_config(board, crate::MagConfig::default())
+    }
+
+    /// Creates a new magnetometer reader from a simulated board, with the
+    /// given configuration.
+    ///
+    /// # Arguments
+    /// * `board`: The simulated board.
+    /// * `config`: The magnetometer configuration.
+    pub fn new_with_config(
+        board: &Board,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<Infallible>> {
+        let board = board.clone();
+        let chip = crate::Chip::Lis3mdl;
+        Magnetometer::from_bus(MockInterface { board, chip }, config)
+    }
+}
+
+/// A reader for all sensors.
+pub type Imu = imu::Imu<MockInterface>;
+
+impl Imu {
+    /// Creates a new IMU reader from a simulated board.
+    ///
+    /// # Arguments
+    /// * `board`: The simulated board.
+    pub fn new(board: &Board) -> Result<Self, crate::Error<Infallible>> {
+        Ok(Imu::from_readers(
+            Lsm6dsl::new(board)?,
+            Magnetometer::new(board)?,
+        ))
+    }
+}
@@ -0,0 +1,117 @@
+//! Navigation helpers for turning raw readings into headings.
+
+/// The heading, in degrees from 0 up to 360, from the magnetometer with its
+/// tilt compensated by the accelerometer, so that it stays accurate when the
+/// board isn't level. This follows ozzmaker's reference code. It works with
+/// the readers for every board version, which orient their readings the
+/// same way.
+///
+/// # Arguments
+/// * `accel`: The raw accelerometer values.
+/// * `mag`: The raw magnetometer values.
+#[inline]
+pub fn tilt_compensated_heading(accel: &crate::AccelReading, mag: &crate::MagReading) -> f64 {
+    let (acc_x, acc_y, acc_z) = (accel.x as f64, accel.y as f64, accel.z as f64);
+    let (mag_x, mag_y, mag_z) = (mag.x as f64, mag.y as f64, mag.z as f64);
+
+    // Normalize the accelerometer values. A zero reading, e.g. in free
+    // fall, has no direction, so the board's taken to be level.
+    let acc_norm = (acc_x * acc_x + acc_y * acc_y + acc_z * acc_z).sqrt();
+    let (acc_x_norm, acc_y_norm) = if acc_norm == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (acc_x / acc_norm, acc_y / acc_norm)
+    };
+
+    // Calculate pitch and roll, clamping the sines, since noise can push
+    // them just past +/- 1
+    let pitch = acc_x_norm.clamp(-1.0, 1.0).asin();
+    let roll = -((acc_y_norm / pitch.cos()).clamp(-1.0, 1.0).asin());
+
+    // Calculate the tilt compensated values
+    let mag_x_comp = mag_x * pitch.cos() + mag_z * pitch.sin();
+    let mag_y_comp =
+        mag_x * roll.sin() * pitch.sin() + mag_y * roll.cos() - mag_z * roll.sin() * pitch.cos();
+
+    wrap_degrees(mag_y_comp.atan2(mag_x_comp).to_degrees())
+}
+
+/// Converts a magnetic heading to a true heading, in degrees from 0 up to
+/// 360.
+///
+/// # Arguments
+/// * `heading`: The magnetic heading, in degrees.
+/// * `declination_deg`: The local magnetic declination, in degrees, which
+///   is positive where magnetic north is east of true north.
+#[inline]
+pub fn heading_with_declination(heading: f64, declination_deg: f64) -> f64 {
+    wrap_degrees(heading + declination_deg)
+}
+
+// Wraps an angle to 0 up to 360 degrees. `rem_euclid` alone rounds tiny
+// negative angles up to 360.
+fn wrap_degrees(degrees: f64) -> f64 {
+    let wrapped = degrees.rem_euclid(360.0);
+    if wrapped >= 360.0 {
+        0.0
+    } else {
+        wrapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} vs {}", a, b);
+    }
+
+    #[test]
+    fn level_headings() {
+        let level = crate::AccelReading {
+            x: 0,
+            y: 0,
+            z: 1000,
+        };
+        for (mag, heading) in [
+            ((1000, 0, -400), 0.0),
+            ((0, 1000, -400), 90.0),
+            ((-1000, 0, -400), 180.0),
+            ((0, -1000, -400), 270.0),
+        ] {
+            let mag = crate::MagReading::from(mag);
+            assert_close(tilt_compensated_heading(&level, &mag), heading);
+            // Without a direction of gravity, it's taken to be level
+            let zero = crate::AccelReading { x: 0, y: 0, z: 0 };
+            assert_close(tilt_compensated_heading(&zero, &mag), heading);
+        }
+    }
+
+    #[test]
+    fn heading_stays_in_range() {
+        let mag = crate::MagReading::from((300, -200, -400));
+        // Including where the sines of the pitch and roll are at, or past,
+        // +/- 1 after rounding
+        for accel in [
+            (1000, 0, 0),
+            (-1000, 0, 0),
+            (0, 1000, 0),
+            (600, 800, 0),
+            (-600, -800, 0),
+            (1, 1, 0),
+        ] {
+            let accel = crate::AccelReading::from(accel);
+            let heading = tilt_compensated_heading(&accel, &mag);
+            assert!((0.0..360.0).contains(&heading), "{:?}: {}", accel, heading);
+        }
+    }
+
+    #[test]
+    fn declination_wraps_around() {
+        assert_close(heading_with_declination(350.0, 20.0), 10.0);
+        assert_close(heading_with_declination(10.0, -20.0), 350.0);
+        assert_close(heading_with_declination(90.0, 12.5), 102.5);
+        assert_eq!(heading_with_declination(0.0, -1e-15), 0.0);
+    }
+}
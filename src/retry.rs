@@ -0,0 +1,191 @@
+//! Retrying of reads that fail transiently, e.g. because of the spurious
+//! EREMOTEIO errors that I2C buses on the Raspberry Pi occasionally produce.
+
+use crate::sensor::{AccelSensor, GyroSensor, MagSensor};
+use std::thread;
+use std::time::Duration;
+
+/// How reads are retried by `Retryable`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first. Zero is treated
+    /// as one.
+    pub max_attempts: u32,
+    /// How long to wait between attempts.
+    pub delay: Duration,
+}
+
+/// Whether an error may go away if the read is retried: a failure of the
+/// bus or the device, rather than of the sensor's state or configuration.
+fn is_transient<E>(err: &crate::Error<E>) -> bool {
+    match err {
+        crate::Error::Read { .. }
+        | crate::Error::Write { .. }
+        | crate::Error::WriteMismatch { .. }
+        | crate::Error::Timeout { .. }
+        | crate::Error::Device(_) => true,
+        crate::Error::Init { .. }
+        | crate::Error::InvalidConfig(_)
+        | crate::Error::NotFound(_)
+        | crate::Error::UnknownBoard(_)
+        | crate::Error::Asleep
+        | crate::Error::PoweredDown
+        | crate::Error::UnknownRegisterValue { .. } => false,
+    }
+}
+
+impl RetryPolicy {
+    fn run<T, E>(
+        &self,
+        mut f: impl FnMut() -> Result<T, crate::Error<E>>,
+    ) -> Result<T, crate::Error<E>> {
+        let mut attempts = 1;
+        loop {
+            match f() {
+                Err(err) if is_transient(&err) && attempts < self.max_attempts => {
+                    attempts += 1;
+                    if !self.delay.is_zero() {
+                        thread::sleep(self.delay);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts, 1ms apart
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            delay: Duration::from_millis(1),
+        }
+    }
+}
+
+/// Wraps a reader, retrying reads that fail with a bus or device error until
+/// one succeeds or the policy's attempts run out, in which case the last
+/// error is returned. Other errors, e.g. `Error::PoweredDown`, are returned
+/// immediately, since retrying wouldn't help. Reads
+/// are made through the `AccelSensor`, `GyroSensor` and `MagSensor` traits,
+/// for whichever of them the wrapped reader implements.
+pub struct Retryable<S> {
+    sensor: S,
+    policy: RetryPolicy,
+}
+
+impl<S> Retryable<S> {
+    /// Creates a new retrying reader.
+    ///
+    /// # Arguments
+    /// * `sensor`: The reader to wrap.
+    /// * `policy`: How reads are retried.
+    pub fn new(sensor: S, policy: RetryPolicy) -> Self {
+        Self { sensor, policy }
+    }
+
+    /// The wrapped reader.
+    pub fn inner(&mut self) -> &mut S {
+        &mut self.sensor
+    }
+
+    /// Unwraps the reader.
+    pub fn into_inner(self) -> S {
+        self.sensor
+    }
+}
+
+impl<S: AccelSensor> AccelSensor for Retryable<S> {
+    type Error = S::Error;
+
+    fn read_accel(&mut self) -> Result<crate::AccelReading, crate::Error<S::Error>> {
+        let sensor = &mut self.sensor;
+        self.policy.run(|| sensor.read_accel())
+    }
+}
+
+impl<S: GyroSensor> GyroSensor for Retryable<S> {
+    type Error = S::Error;
+
+    fn read_gyro(&mut self) -> Result<crate::GyroReading, crate::Error<S::Error>> {
+        let sensor = &mut self.sensor;
+        self.policy.run(|| sensor.read_gyro())
+    }
+}
+
+impl<S: MagSensor> MagSensor for Retryable<S> {
+    type Error = S::Error;
+
+    fn read_mag(&mut self) -> Result<crate::MagReading, crate::Error<S::Error>> {
+        let sensor = &mut self.sensor;
+        self.policy.run(|| sensor.read_mag())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sensor that fails with each of the given errors in turn.
+    struct Failing(Vec<crate::Error<()>>);
+
+    impl AccelSensor for Failing {
+        type Error = ();
+
+        fn read_accel(&mut self) -> Result<crate::AccelReading, crate::Error<()>> {
+            match self.0.pop() {
+                Some(err) => Err(err),
+                None => Ok(crate::AccelReading::default()),
+            }
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            delay: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn retries_bus_errors() {
+        let errors = vec![
+            crate::Error::Timeout {
+                reg: 0x28,
+                elapsed: Duration::ZERO,
+            },
+            crate::Error::Read {
+                reg: 0x28,
+                cause: None,
+            },
+        ];
+        let mut sensor = Retryable::new(Failing(errors), policy());
+        assert!(sensor.read_accel().is_ok());
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let errors = (0..3).map(|_| crate::Error::Device(())).collect();
+        let mut sensor = Retryable::new(Failing(errors), policy());
+        assert!(matches!(sensor.read_accel(), Err(crate::Error::Device(()))));
+        assert!(sensor.read_accel().is_ok());
+    }
+
+    #[test]
+    fn does_not_retry_sensor_state() {
+        for err in [
+            crate::Error::PoweredDown,
+            crate::Error::Asleep,
+            crate::Error::InvalidConfig("invalid"),
+            crate::Error::Init {
+                expected: &[0x6A],
+                actual: 0,
+            },
+        ] {
+            let mut sensor = Retryable::new(Failing(vec![err]), policy());
+            assert!(sensor.read_accel().is_err());
+            assert!(sensor.inner().0.is_empty());
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Readers for Raspberry Pi I2C and SPI peripherals, via `rppal`. Each reader
+//! takes ownership of its `I2c`, setting the slave address of its chip, so
+//! the accelerometer and magnetometer need separate `I2c` instances. See the
+//! `spi` submodule for SPI readers.
+
+use crate::bus::RegisterAccess;
+use crate::lis3mdl;
+use crate::lsm6dsl;
+use ::rppal::i2c::{Error, I2c};
+
+pub mod spi;
+
+/// Register access to a chip through a Raspberry Pi I2C peripheral.
+pub struct I2cInterface(I2c);
+
+impl RegisterAccess for I2cInterface {
+    type Error = Error;
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<Error>> {
+        self.0.smbus_read_byte(reg).map_err(crate::Error::read(reg))
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<Error>> {
+        self.0
+            .smbus_write_byte(reg, value)
+            .map_err(crate::Error::write(reg))
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), crate::Error<Error>> {
+        self.0
+            .write_read(&[reg], buf)
+            .map_err(crate::Error::read(reg))
+    }
+}
+
+/// An accelerometer reader.
+pub type Accelerometer = lsm6dsl::Accelerometer<I2cInterface>;
+
+impl Accelerometer {
+    /// Creates a new accelerometer reader from an I2C peripheral.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C peripheral.
+    pub fn new(i2c: I2c) -> Result<Self, crate::Error<Error>> {
+        Accelerometer::new_with_config(i2c, crate::AccelConfig::default())
+    }
+
+    /// Creates a new accelerometer reader from an I2C peripheral, with the
+    /// given configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C peripheral.
+    /// * `config`: The accelerometer configuration.
+    pub fn new_with_config(
+        mut i2c: I2c,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<Error>> {
+        i2c.set_slave_address(crate::Chip::Lsm6dsl.i2c_address())?;
+        Accelerometer::from_bus(I2cInterface(i2c), config)
+    }
+}
+
+/// A gyroscope reader.
+pub type Gyroscope = lsm6dsl::Gyroscope<I2cInterface>;
+
+impl Gyroscope {
+    /// Creates a new gyroscope reader from an I2C peripheral.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C peripheral.
+    pub fn new(i2c: I2c) -> Result<Self, crate::Error<Error>> {
+        Gyroscope::new_with_config(i2c, crate::GyroConfig::default())
+    }
+
+    /// Creates a new gyroscope reader from an I2C peripheral, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C peripheral.
+    /// * `config`: The gyroscope configuration.
+    pub fn new_with_config(
+        mut i2c: I2c,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<Error>> {
+        i2c.set_slave_address(crate::Chip::Lsm6dsl.i2c_address())?;
+        Gyroscope::from_bus(I2cInterface(i2c), config)
+    }
+}
+
+/// A reader for both the accelerometer and gyroscope, which share the
+/// LSM6DSL chip.
+pub type Lsm6dsl = lsm6dsl::Lsm6dsl<I2cInterface>;
+
+impl Lsm6dsl {
+    /// Creates a new LSM6DSL reader from an I2C peripheral.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C peripheral.
+    pub fn new(i2c: I2c) -> Result<Self, crate::Error<Error>> {
+        Lsm6dsl::new_with_config(
+            i2c,
+            crate::AccelConfig::default(),
+            crate::GyroConfig::default(),
+        )
+    }
+
+    /// Creates a new LSM6DSL reader from an I2C peripheral, with the given
+    /// accelerometer and gyroscope configurations.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C peripheral.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    pub fn new_with_config(
+        mut i2c: I2c,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<Error>> {
+        i2c.set_slave_address(crate::Chip::Lsm6dsl.i2c_address())?;
+        Lsm6dsl::from_bus(I2cInterface(i2c), accel_config, gyro_config)
+    }
+}
+
+/// A magnetometer reader.
+pub type Magnetometer = lis3mdl::Magnetometer<I2cInterface>;
+
+impl Magnetometer {
+    /// Creates a new magnetometer reader from an I2C peripheral.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C peripheral.
+    pub fn new(i2c: I2c) -> Result<Self, crate::Error<Error>> {
+        Magnetometer::new_with_config(i2c, crate::MagConfig::default())
+    }
+
+    /// Creates a new magnetometer reader from an I2C peripheral, with the
+    /// given configuration.
+    ///
+    /// # Arguments
+    /// * `i2c`: The I2C peripheral.
+    /// * `config`: The magnetometer configuration.
+    pub fn new_with_config(
+        mut i2c: I2c,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<Error>> {
+        i2c.set_slave_address(crate::Chip::Lis3mdl.i2c_address())?;
+        Magnetometer::from_bus(I2cInterface(i2c), config)
+    }
+}
@@ -0,0 +1,151 @@
+//! Readers for Raspberry Pi SPI peripherals, via `rppal`.
+
+use crate::bus::RegisterAccess;
+use crate::lis3mdl;
+use crate::lsm6dsl;
+use ::rppal::spi::{Error, Segment, Spi};
+
+/// Register access to a chip through a Raspberry Pi SPI peripheral.
+pub struct SpiInterface {
+    spi: Spi,
+    chip: crate::Chip,
+}
+
+impl RegisterAccess for SpiInterface {
+    type Error = Error;
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<Error>> {
+        let mut buf = [0];
+        self.read_registers(reg, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<Error>> {
+        self.spi
+            .write(&[reg, value])
+            .map_err(crate::Error::write(reg))?;
+        Ok(())
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), crate::Error<Error>> {
+        let command = [self.chip.spi_read_command(reg, buf.len())];
+        self.spi
+            .transfer_segments(&[Segment::with_write(&command), Segment::with_read(buf)])
+            .map_err(crate::Error::read(reg))
+    }
+}
+
+/// An accelerometer reader.
+pub type Accelerometer = lsm6dsl::Accelerometer<SpiInterface>;
+
+impl Accelerometer {
+    /// Creates a new accelerometer reader from a SPI peripheral.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI peripheral.
+    pub fn new(spi: Spi) -> Result<Self, crate::Error<Error>> {
+        Accelerometer::new_with_config(spi, crate::AccelConfig::default())
+    }
+
+    /// Creates a new accelerometer reader from a SPI peripheral, with the
+    /// given configuration.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI peripheral.
+    /// * `config`: The accelerometer configuration.
+    pub fn new_with_config(
+        spi: Spi,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<Error>> {
+        let chip = crate::Chip::Lsm6dsl;
+        Accelerometer::from_bus(SpiInterface { spi, chip }, config)
+    }
+}
+
+/// A gyroscope reader.
+pub type Gyroscope = lsm6dsl::Gyroscope<SpiInterface>;
+
+impl Gyroscope {
+    /// Creates a new gyroscope reader from a SPI peripheral.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI peripheral.
+    pub fn new(spi: Spi) -> Result<Self, crate::Error<Error>> {
+        Gyroscope::new_with_config(spi, crate::GyroConfig::default())
+    }
+
+    /// Creates a new gyroscope reader from a SPI peripheral, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI peripheral.
+    /// * `config`: The gyroscope configuration.
+    pub fn new_with_config(
+        spi: Spi,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<Error>> {
+        let chip = crate::Chip::Lsm6dsl;
+        Gyroscope::from_bus(SpiInterface { spi, chip }, config)
+    }
+}
+
+/// A reader for both the accelerometer and gyroscope, which share the
+/// LSM6DSL chip.
+pub type Lsm6dsl = lsm6dsl::Lsm6dsl<SpiInterface>;
+
+impl Lsm6dsl {
+    /// Creates a new LSM6DSL reader from a SPI peripheral.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI peripheral.
+    pub fn new(spi: Spi) -> Result<Self, crate::Error<Error>> {
+        Lsm6dsl::new_with_config(
+            spi,
+            crate::AccelConfig::default(),
+            crate::GyroConfig::default(),
+        )
+    }
+
+    /// Creates a new LSM6DSL reader from a SPI peripheral, with the given
+    /// accelerometer and gyroscope configurations.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI peripheral.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    pub fn new_with_config(
+        spi: Spi,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<Error>> {
+        let chip = crate::Chip::Lsm6dsl;
+        Lsm6dsl::from_bus(SpiInterface { spi, chip }, accel_config, gyro_config)
+    }
+}
+
+/// A magnetometer reader.
+pub type Magnetometer = lis3mdl::Magnetometer<SpiInterface>;
+
+impl Magnetometer {
+    /// Creates a new magnetometer reader from a SPI peripheral.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI peripheral.
+    pub fn new(spi: Spi) -> Result<Self, crate::Error<Error>> {
+        Magnetometer::new_with_config(spi, crate::MagConfig::default())
+    }
+
+    /// Creates a new magnetometer reader from a SPI peripheral, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI peripheral.
+    /// * `config`: The magnetometer configuration.
+    pub fn new_with_config(
+        spi: Spi,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<Error>> {
+        let chip = crate::Chip::Lis3mdl;
+        Magnetometer::from_bus(SpiInterface { spi, chip }, config)
+    }
+}
@@ -0,0 +1,125 @@
+//! Traits implemented by the readers for every version of the board.
+
+use crate::bus::RegisterAccess;
+use crate::lis3mdl;
+use crate::lsm6dsl;
+use crate::lsm9ds0;
+use crate::lsm9ds1;
+
+/// An accelerometer on any version of the board, so that downstream code can
+/// be written against either.
+pub trait AccelSensor {
+    /// The error type of the underlying device.
+    type Error;
+
+    /// Read the raw accelerometer values.
+    fn read_accel(&mut self) -> Result<crate::AccelReading, crate::Error<Self::Error>>;
+}
+
+/// A gyroscope on any version of the board, so that downstream code can be
+/// written against either.
+pub trait GyroSensor {
+    /// The error type of the underlying device.
+    type Error;
+
+    /// Read the raw gyroscope values.
+    fn read_gyro(&mut self) -> Result<crate::GyroReading, crate::Error<Self::Error>>;
+}
+
+/// A magnetometer on any version of the board, so that downstream code can
+/// be written against either.
+pub trait MagSensor {
+    /// The error type of the underlying device.
+    type Error;
+
+    /// Read the raw magnetometer values.
+    fn read_mag(&mut self) -> Result<crate::MagReading, crate::Error<Self::Error>>;
+}
+
+impl<B: RegisterAccess> AccelSensor for lsm6dsl::Accelerometer<B> {
+    type Error = B::Error;
+
+    fn read_accel(&mut self) -> Result<crate::AccelReading, crate::Error<B::Error>> {
+        self.read()
+    }
+}
+
+impl<B: RegisterAccess> AccelSensor for lsm6dsl::Lsm6dsl<B> {
+    type Error = B::Error;
+
+    fn read_accel(&mut self) -> Result<crate::AccelReading, crate::Error<B::Error>> {
+        lsm6dsl::Lsm6dsl::read_accel(self)
+    }
+}
+
+impl<B: RegisterAccess> AccelSensor for lsm9ds0::Accelerometer<B> {
+    type Error = B::Error;
+
+    fn read_accel(&mut self) -> Result<crate::AccelReading, crate::Error<B::Error>> {
+        self.read()
+    }
+}
+
+impl<B: RegisterAccess> AccelSensor for lsm9ds1::Accelerometer<B> {
+    type Error = B::Error;
+
+    fn read_accel(&mut self) -> Result<crate::AccelReading, crate::Error<B::Error>> {
+        self.read()
+    }
+}
+
+impl<B: RegisterAccess> GyroSensor for lsm6dsl::Gyroscope<B> {
+    type Error = B::Error;
+
+    fn read_gyro(&mut self) -> Result<crate::GyroReading, crate::Error<B::Error>> {
+        self.read()
+    }
+}
+
+impl<B: RegisterAccess> GyroSensor for lsm6dsl::Lsm6dsl<B> {
+    type Error = B::Error;
+
+    fn read_gyro(&mut self) -> Result<crate::GyroReading, crate::Error<B::Error>> {
+        lsm6dsl::Lsm6dsl::read_gyro(self)
+    }
+}
+
+impl<B: RegisterAccess> GyroSensor for lsm9ds0::Gyroscope<B> {
+    type Error = B::Error;
+
+    fn read_gyro(&mut self) -> Result<crate::GyroReading, crate::Error<B::Error>> {
+        self.read()
+    }
+}
+
+impl<B: RegisterAccess> GyroSensor for lsm9ds1::Gyroscope<B> {
+    type Error = B::Error;
+
+    fn read_gyro(&mut self) -> Result<crate::GyroReading, crate::Error<B::Error>> {
+        self.read()
+    }
+}
+
+impl<B: RegisterAccess> MagSensor for lis3mdl::Magnetometer<B> {
+    type Error = B::Error;
+
+    fn read_mag(&mut self) -> Result<crate::MagReading, crate::Error<B::Error>> {
+        self.read()
+    }
+}
+
+impl<B: RegisterAccess> MagSensor for lsm9ds0::Magnetometer<B> {
+    type Error = B::Error;
+
+    fn read_mag(&mut self) -> Result<crate::MagReading, crate::Error<B::Error>> {
+        self.read()
+    }
+}
+
+impl<B: RegisterAccess> MagSensor for lsm9ds1::Magnetometer<B> {
+    type Error = B::Error;
+
+    fn read_mag(&mut self) -> Result<crate::MagReading, crate::Error<B::Error>> {
+        self.read()
+    }
+}
@@ -1,68 +1,224 @@
+use crate::bus::{self, RegisterAccess};
+use crate::imu;
+use crate::lis3mdl;
+use crate::lsm6dsl;
 use spidev::{SpiModeFlags, Spidev, SpidevOptions, SpidevTransfer};
 use std::io;
 use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
 
-fn device_from_address<P: AsRef<Path>>(addr: P) -> io::Result<Spidev> {
+/// SPI clock mode. Both chips sample on the rising edge of the clock, so
+/// only modes 0 and 3 are supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpiMode {
+    /// Clock idles low.
+    Mode0,
+    /// Clock idles high.
+    Mode3,
+}
+
+/// SPI bus options.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpiConfig {
+    /// The clock speed, in Hz. Both chips support up to 10 MHz.
+    pub max_speed_hz: u32,
+    pub mode: SpiMode,
+    /// The word size, which must be 8.
+    pub bits_per_word: u8,
+    /// Whether to disable the LSM6DSL's I2C interface, as ST recommends when
+    /// it's accessed over SPI, since activity on floating I2C pins can
+    /// otherwise glitch it. This should only be turned off if the chip is
+    /// also accessed over I2C. Readers created from an already opened device
+    /// always disable it.
+    pub disable_i2c: bool,
+}
+
+impl SpiConfig {
+    /// Applies these options to an open SPI device, e.g. to lower the clock
+    /// speed of a device passed to `new`.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    pub fn apply(&self, dev: &mut Spidev) -> Result<(), crate::Error<io::Error>> {
+        if self.max_speed_hz == 0 || self.max_speed_hz > 10_000_000 {
+            return Err(crate::Error::InvalidConfig(
+                "the SPI clock speed must be between 1 Hz and 10 MHz",
+            ));
+        }
+        if self.bits_per_word != 8 {
+            return Err(crate::Error::InvalidConfig(
+                "the SPI word size must be 8 bits",
+            ));
+        }
+        let mode = match self.mode {
+            SpiMode::Mode0 => SpiModeFlags::SPI_MODE_0,
+            SpiMode::Mode3 => SpiModeFlags::SPI_MODE_3,
+        };
+        let options = SpidevOptions::new()
+            .bits_per_word(self.bits_per_word)
+            .max_speed_hz(self.max_speed_hz)
+            .mode(mode)
+            .build();
+        dev.configure(&options)?;
+        Ok(())
+    }
+}
+
+impl Default for SpiConfig {
+    /// 10 MHz, mode 0, 8 bits per word, I2C disabled
+    fn default() -> Self {
+        Self {
+            max_speed_hz: 10_000_000,
+            mode: SpiMode::Mode0,
+            bits_per_word: 8,
+            disable_i2c: true,
+        }
+    }
+}
+
+fn device_from_address<P: AsRef<Path>>(
+    addr: P,
+    config: &SpiConfig,
+) -> Result<Spidev, crate::Error<io::Error>> {
     let mut dev = Spidev::open(addr)?;
-    let options = SpidevOptions::new()
-        .bits_per_word(8)
-        .max_speed_hz(10_000_000)
-        .mode(SpiModeFlags::SPI_MODE_0)
-        .build();
-    dev.configure(&options)?;
+    config.apply(&mut dev)?;
     Ok(dev)
 }
 
-fn read_reg(dev: &mut Spidev, reg_address: u8) -> io::Result<u8> {
-    // "write" transfers are also reads at the same time with the read having
-    // the same length as the write.
-    let tx_buf = [reg_address | 0x80, 0x00];
-    let mut rx_buf = [0; 2];
-    {
-        let mut transfer = SpidevTransfer::read_write(&tx_buf, &mut rx_buf);
-        dev.transfer(&mut transfer)?;
-    }
-    Ok(rx_buf[1])
+/// A chip select line that's driven manually, e.g. through a GPIO, so that
+/// more chips can share a bus than there are hardware chip selects. It's
+/// called with `true` to select the chip before each transfer, and `false`
+/// to deselect it afterwards.
+pub type ChipSelect = Box<dyn FnMut(bool) -> io::Result<()> + Send>;
+
+/// A SPI device, along with its manually driven chip select, if any.
+struct Device {
+    spidev: Spidev,
+    chip_select: Option<ChipSelect>,
 }
 
-fn write_reg(dev: &mut Spidev, reg_address: u8, data: u8) -> io::Result<[u8; 2]> {
-    // "write" transfers are also reads at the same time with the read having
-    // the same length as the write.
-    let tx_buf = [reg_address, data];
-    let mut rx_buf = [0; 2];
-    {
-        let mut transfer = SpidevTransfer::read_write(&tx_buf, &mut rx_buf);
-        dev.transfer(&mut transfer)?;
+impl Device {
+    fn transfer(&mut self, transfers: &mut [SpidevTransfer]) -> io::Result<()> {
+        match self.chip_select {
+            Some(ref mut chip_select) => {
+                chip_select(true)?;
+                let result = self.spidev.transfer_multiple(transfers);
+                chip_select(false)?;
+                result
+            }
+            None => self.spidev.transfer_multiple(transfers),
+        }
     }
-    Ok(rx_buf)
 }
 
-fn read_axis(dev: &mut Spidev, l_reg_address: u8, h_reg_address: u8) -> io::Result<i32> {
-    let acc_l = read_reg(dev, l_reg_address)?;
-    let acc_h = read_reg(dev, h_reg_address)?;
-    let acc_combined: u16 = (acc_l as u16) | ((acc_h as u16) << 8);
-    if acc_combined < 32768 {
-        Ok(acc_combined as i32)
-    } else {
-        Ok((acc_combined as i32) - 65536)
+/// Register access to a chip through a SPI device.
+pub struct SpiInterface {
+    dev: Arc<Mutex<Device>>,
+    chip: crate::Chip,
+    read_timeout: Option<Duration>,
+    verify_writes: bool,
+}
+
+impl SpiInterface {
+    fn new(
+        spidev: Spidev,
+        chip: crate::Chip,
+        chip_select: Option<ChipSelect>,
+        options: crate::SensorOptions,
+    ) -> Self {
+        Self {
+            dev: Arc::new(Mutex::new(Device {
+                spidev,
+                chip_select,
+            })),
+            chip,
+            read_timeout: options.read_timeout,
+            verify_writes: options.verify_writes,
+        }
     }
 }
 
-fn init(
-    dev: &mut Spidev,
-    who_am_i: u8,
-    expected_response: u8,
-) -> Result<(), crate::Error<io::Error>> {
-    let who_am_i_response = read_reg(dev, who_am_i)?;
-    if who_am_i_response == expected_response {
+impl RegisterAccess for SpiInterface {
+    type Error = io::Error;
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<io::Error>> {
+        let mut buf = [0];
+        self.read_registers(reg, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<io::Error>> {
+        bus::with_timeout(&self.dev, reg, self.read_timeout, move |dev| {
+            dev.transfer(&mut [SpidevTransfer::write(&[reg, value])])
+                .map_err(crate::Error::write(reg))
+        })
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), crate::Error<io::Error>> {
+        // Chip select stays asserted between the transfers of a message, so
+        // this is a single burst read, without the garbage byte received
+        // while the register address is being sent.
+        let command = [self.chip.spi_read_command(reg, buf.len())];
+        match self.read_timeout {
+            None => {
+                let mut dev = self.dev.lock().unwrap_or_else(PoisonError::into_inner);
+                dev.transfer(&mut [SpidevTransfer::write(&command), SpidevTransfer::read(buf)])
+                    .map_err(crate::Error::read(reg))?;
+            }
+            Some(timeout) => {
+                // The transfer may outlive this call, so it reads into its
+                // own buffer.
+                let len = buf.len();
+                let block = bus::with_timeout(&self.dev, reg, Some(timeout), move |dev| {
+                    let mut block = vec![0; len];
+                    let mut transfers = [
+                        SpidevTransfer::write(&command),
+                        SpidevTransfer::read(&mut block),
+                    ];
+                    dev.transfer(&mut transfers)
+                        .map_err(crate::Error::read(reg))?;
+                    Ok(block)
+                })?;
+                buf.copy_from_slice(&block);
+            }
+        }
         Ok(())
-    } else {
-        Err(crate::Error::Init)
+    }
+
+    fn verify_writes(&self) -> bool {
+        self.verify_writes
     }
 }
 
+/// Creates an interface to the LSM6DSL.
+fn lsm6dsl_interface(
+    dev: Spidev,
+    chip_select: Option<ChipSelect>,
+    options: crate::SensorOptions,
+    disable_i2c: bool,
+) -> Result<SpiInterface, crate::Error<io::Error>> {
+    let mut interface = SpiInterface::new(dev, crate::Chip::Lsm6dsl, chip_select, options);
+    if disable_i2c {
+        disable_lsm6dsl_i2c(&mut interface)?;
+    }
+    Ok(interface)
+}
+
+/// Disables the LSM6DSL's I2C interface, once the chip's been found. It's
+/// only written if it isn't already disabled, e.g. by another reader sharing
+/// the chip.
+fn disable_lsm6dsl_i2c<B: RegisterAccess>(bus: &mut B) -> Result<(), crate::Error<B::Error>> {
+    const I2C_DISABLE: u8 = 0b100;
+    bus::init(bus, crate::Chip::Lsm6dsl)?;
+    if bus.read_register(crate::LSM6DSL_CTRL4_C)? & I2C_DISABLE == 0 {
+        bus::modify(bus, crate::LSM6DSL_CTRL4_C, I2C_DISABLE, I2C_DISABLE)?;
+    }
+    Ok(())
+}
+
 /// An accelerometer reader.
-pub struct Accelerometer(Spidev);
+pub type Accelerometer = lsm6dsl::Accelerometer<SpiInterface>;
 
 impl Accelerometer {
     /// Creates a new accelerometer reader from an address.
@@ -70,44 +226,87 @@ impl Accelerometer {
     /// # Arguments
     /// * `addr`: The SPI device address, e.g. `/dev/spidev0.0`.
     pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<io::Error>> {
-        Accelerometer::new(device_from_address(addr)?)
+        Accelerometer::new_from_address_with_config(addr, SpiConfig::default())
+    }
+
+    /// Creates a new accelerometer reader from an address, with the given SPI bus
+    /// options.
+    ///
+    /// # Arguments
+    /// * `addr`: The SPI device address, e.g. `/dev/spidev0.0`.
+    /// * `spi_config`: The SPI bus options.
+    pub fn new_from_address_with_config<P: AsRef<Path>>(
+        addr: P,
+        spi_config: SpiConfig,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        let dev = device_from_address(addr, &spi_config)?;
+        let options = crate::SensorOptions::default();
+        let interface = lsm6dsl_interface(dev, None, options, spi_config.disable_i2c)?;
+        Accelerometer::from_bus(interface, crate::AccelConfig::default())
     }
 
     /// Creates a new accelerometer reader from a SPI device.
     ///
     /// # Arguments
     /// * `dev`: The SPI device.
-    pub fn new(mut dev: Spidev) -> Result<Self, crate::Error<io::Error>> {
-        init(&mut dev, crate::LSM6DSL_WHO_AM_I, 0x6A)?;
-        write_reg(&mut dev, crate::LSM6DSL_CTRL1_XL, 0b10011111)?; // ODR 3.33 kHz, +/- 8g , BW = 400hz
-        write_reg(&mut dev, crate::LSM6DSL_CTRL8_XL, 0b11001000)?; // Low pass filter enabled, BW9, composite filter
-        write_reg(&mut dev, crate::LSM6DSL_CTRL3_C, 0b01000100)?; // Enable Block Data update, increment during multi byte read
-        Ok(Self(dev))
+    pub fn new(dev: Spidev) -> Result<Self, crate::Error<io::Error>> {
+        Accelerometer::new_with_config(dev, crate::AccelConfig::default())
     }
 
-    /// Read the raw accelerometer values.
-    pub fn read(&mut self) -> Result<(i32, i32, i32), crate::Error<io::Error>> {
-        let x = read_axis(
-            &mut self.0,
-            crate::LSM6DSL_OUTX_L_XL,
-            crate::LSM6DSL_OUTX_H_XL,
-        )?;
-        let y = read_axis(
-            &mut self.0,
-            crate::LSM6DSL_OUTY_L_XL,
-            crate::LSM6DSL_OUTY_H_XL,
-        )?;
-        let z = read_axis(
-            &mut self.0,
-            crate::LSM6DSL_OUTZ_L_XL,
-            crate::LSM6DSL_OUTZ_H_XL,
-        )?;
-        Ok((x, y, z))
+    /// Creates a new accelerometer reader from a SPI device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `config`: The accelerometer configuration.
+    pub fn new_with_config(
+        dev: Spidev,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        Accelerometer::new_with_options(dev, config, crate::SensorOptions::default())
+    }
+
+    /// Creates a new accelerometer reader from a SPI device, with the given
+    /// configuration and access options.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `config`: The accelerometer configuration.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: Spidev,
+        config: crate::AccelConfig,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        let interface = lsm6dsl_interface(dev, None, options, true)?;
+        Accelerometer::from_bus(interface, config)
+    }
+
+    /// Creates a new accelerometer reader from a SPI device whose chip select is
+    /// driven manually. The device should be configured not to drive a
+    /// hardware chip select, e.g. with `SpiModeFlags::SPI_NO_CS`.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `chip_select`: Selects or deselects the chip.
+    /// * `config`: The accelerometer configuration.
+    pub fn new_with_chip_select<F>(
+        dev: Spidev,
+        chip_select: F,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<io::Error>>
+    where
+        F: FnMut(bool) -> io::Result<()> + Send + 'static,
+    {
+        let chip_select: ChipSelect = Box::new(chip_select);
+        let options = crate::SensorOptions::default();
+        let interface = lsm6dsl_interface(dev, Some(chip_select), options, true)?;
+        Accelerometer::from_bus(interface, config)
     }
 }
 
 /// A gyroscope reader.
-pub struct Gyroscope(Spidev);
+pub type Gyroscope = lsm6dsl::Gyroscope<SpiInterface>;
 
 impl Gyroscope {
     /// Creates a new gyroscope reader from an address.
@@ -115,37 +314,383 @@ impl Gyroscope {
     /// # Arguments
     /// * `addr`: The SPI device address, e.g. `/dev/spidev0.0`.
     pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<io::Error>> {
-        Gyroscope::new(device_from_address(addr)?)
+        Gyroscope::new_from_address_with_config(addr, SpiConfig::default())
+    }
+
+    /// Creates a new gyroscope reader from an address, with the given SPI bus
+    /// options.
+    ///
+    /// # Arguments
+    /// * `addr`: The SPI device address, e.g. `/dev/spidev0.0`.
+    /// * `spi_config`: The SPI bus options.
+    pub fn new_from_address_with_config<P: AsRef<Path>>(
+        addr: P,
+        spi_config: SpiConfig,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        let dev = device_from_address(addr, &spi_config)?;
+        let options = crate::SensorOptions::default();
+        let interface = lsm6dsl_interface(dev, None, options, spi_config.disable_i2c)?;
+        Gyroscope::from_bus(interface, crate::GyroConfig::default())
     }
 
     /// Creates a new gyroscope reader from a SPI device.
     ///
     /// # Arguments
     /// * `dev`: The SPI device.
-    pub fn new(mut dev: Spidev) -> Result<Self, crate::Error<io::Error>> {
-        init(&mut dev, crate::LIS3MDL_WHO_AM_I, 0x6A)?;
-        // Enable the gyroscope
-        write_reg(&mut dev, crate::LSM6DSL_CTRL2_G, 0b10011100)?; // ODR 3.3 kHz, 2000 dps
-        Ok(Self(dev))
-    }
-
-    /// Read the raw gyroscope values.
-    pub fn read(&mut self) -> Result<(i32, i32, i32), crate::Error<io::Error>> {
-        let x = read_axis(
-            &mut self.0,
-            crate::LSM6DSL_OUTX_L_G,
-            crate::LSM6DSL_OUTX_H_G,
-        )?;
-        let y = read_axis(
-            &mut self.0,
-            crate::LSM6DSL_OUTY_L_G,
-            crate::LSM6DSL_OUTY_H_G,
-        )?;
-        let z = read_axis(
-            &mut self.0,
-            crate::LSM6DSL_OUTZ_L_G,
-            crate::LSM6DSL_OUTZ_H_G,
-        )?;
-        Ok((x, y, z))
+    pub fn new(dev: Spidev) -> Result<Self, crate::Error<io::Error>> {
+        Gyroscope::new_with_config(dev, crate::GyroConfig::default())
+    }
+
+    /// Creates a new gyroscope reader from a SPI device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `config`: The gyroscope configuration.
+    pub fn new_with_config(
+        dev: Spidev,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        Gyroscope::new_with_options(dev, config, crate::SensorOptions::default())
+    }
+
+    /// Creates a new gyroscope reader from a SPI device, with the given
+    /// configuration and access options.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `config`: The gyroscope configuration.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: Spidev,
+        config: crate::GyroConfig,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        let interface = lsm6dsl_interface(dev, None, options, true)?;
+        Gyroscope::from_bus(interface, config)
+    }
+
+    /// Creates a new gyroscope reader from a SPI device whose chip select is
+    /// driven manually. The device should be configured not to drive a
+    /// hardware chip select, e.g. with `SpiModeFlags::SPI_NO_CS`.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `chip_select`: Selects or deselects the chip.
+    /// * `config`: The gyroscope configuration.
+    pub fn new_with_chip_select<F>(
+        dev: Spidev,
+        chip_select: F,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<io::Error>>
+    where
+        F: FnMut(bool) -> io::Result<()> + Send + 'static,
+    {
+        let chip_select: ChipSelect = Box::new(chip_select);
+        let options = crate::SensorOptions::default();
+        let interface = lsm6dsl_interface(dev, Some(chip_select), options, true)?;
+        Gyroscope::from_bus(interface, config)
+    }
+}
+
+/// A reader for both the accelerometer and gyroscope, which share the
+/// LSM6DSL chip.
+pub type Lsm6dsl = lsm6dsl::Lsm6dsl<SpiInterface>;
+
+impl Lsm6dsl {
+    /// Creates a new LSM6DSL reader from an address.
+    ///
+    /// # Arguments
+    /// * `addr`: The SPI device address, e.g. `/dev/spidev0.0`.
+    pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<io::Error>> {
+        Lsm6dsl::new_from_address_with_config(addr, SpiConfig::default())
+    }
+
+    /// Creates a new LSM6DSL reader from an address, with the given SPI bus
+    /// options.
+    ///
+    /// # Arguments
+    /// * `addr`: The SPI device address, e.g. `/dev/spidev0.0`.
+    /// * `spi_config`: The SPI bus options.
+    pub fn new_from_address_with_config<P: AsRef<Path>>(
+        addr: P,
+        spi_config: SpiConfig,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        let dev = device_from_address(addr, &spi_config)?;
+        let options = crate::SensorOptions::default();
+        let interface = lsm6dsl_interface(dev, None, options, spi_config.disable_i2c)?;
+        let accel_config = crate::AccelConfig::default();
+        let gyro_config = crate::GyroConfig::default();
+        Lsm6dsl::from_bus(interface, accel_config, gyro_config)
+    }
+
+    /// Creates a new LSM6DSL reader from a SPI device.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    pub fn new(dev: Spidev) -> Result<Self, crate::Error<io::Error>> {
+        Lsm6dsl::new_with_config(
+            dev,
+            crate::AccelConfig::default(),
+            crate::GyroConfig::default(),
+        )
+    }
+
+    /// Creates a new LSM6DSL reader from a SPI device, with the given
+    /// accelerometer and gyroscope configurations.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    pub fn new_with_config(
+        dev: Spidev,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        Lsm6dsl::new_with_options(
+            dev,
+            accel_config,
+            gyro_config,
+            crate::SensorOptions::default(),
+        )
+    }
+
+    /// Creates a new LSM6DSL reader from a SPI device, with the given
+    /// accelerometer and gyroscope configurations and access options.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: Spidev,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        let interface = lsm6dsl_interface(dev, None, options, true)?;
+        Lsm6dsl::from_bus(interface, accel_config, gyro_config)
+    }
+
+    /// Creates a new LSM6DSL reader from a SPI device whose chip select is
+    /// driven manually. The device should be configured not to drive a
+    /// hardware chip select, e.g. with `SpiModeFlags::SPI_NO_CS`.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `chip_select`: Selects or deselects the chip.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    pub fn new_with_chip_select<F>(
+        dev: Spidev,
+        chip_select: F,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<io::Error>>
+    where
+        F: FnMut(bool) -> io::Result<()> + Send + 'static,
+    {
+        let chip_select: ChipSelect = Box::new(chip_select);
+        let options = crate::SensorOptions::default();
+        let interface = lsm6dsl_interface(dev, Some(chip_select), options, true)?;
+        Lsm6dsl::from_bus(interface, accel_config, gyro_config)
+    }
+}
+
+/// A magnetometer reader.
+pub type Magnetometer = lis3mdl::Magnetometer<SpiInterface>;
+
+impl Magnetometer {
+    /// Creates a new magnetometer reader from an address.
+    ///
+    /// # Arguments
+    /// * `addr`: The SPI device address, e.g. `/dev/spidev0.1`.
+    pub fn new_from_address<P: AsRef<Path>>(addr: P) -> Result<Self, crate::Error<io::Error>> {
+        Magnetometer::new_from_address_with_config(addr, SpiConfig::default())
+    }
+
+    /// Creates a new magnetometer reader from an address, with the given SPI bus
+    /// options.
+    ///
+    /// # Arguments
+    /// * `addr`: The SPI device address, e.g. `/dev/spidev0.1`.
+    /// * `spi_config`: The SPI bus options.
+    pub fn new_from_address_with_config<P: AsRef<Path>>(
+        addr: P,
+        spi_config: SpiConfig,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        Magnetometer::new(device_from_address(addr, &spi_config)?)
+    }
+
+    /// Creates a new magnetometer reader from a SPI device.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    pub fn new(dev: Spidev) -> Result<Self, crate::Error<io::Error>> {
+        Magnetometer::new_with_config(dev, crate::MagConfig::default())
+    }
+
+    /// Creates a new magnetometer reader from a SPI device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `config`: The magnetometer configuration.
+    pub fn new_with_config(
+        dev: Spidev,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        Magnetometer::new_with_options(dev, config, crate::SensorOptions::default())
+    }
+
+    /// Creates a new magnetometer reader from a SPI device, with the given
+    /// configuration and access options.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `config`: The magnetometer configuration.
+    /// * `options`: The access options, e.g. the read timeout.
+    pub fn new_with_options(
+        dev: Spidev,
+        config: crate::MagConfig,
+        options: crate::SensorOptions,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        let interface = SpiInterface::new(dev, crate::Chip::Lis3mdl, None, options);
+        Magnetometer::from_bus(interface, config)
+    }
+
+    /// Creates a new magnetometer reader from a SPI device whose chip select is
+    /// driven manually. The device should be configured not to drive a
+    /// hardware chip select, e.g. with `SpiModeFlags::SPI_NO_CS`.
+    ///
+    /// # Arguments
+    /// * `dev`: The SPI device.
+    /// * `chip_select`: Selects or deselects the chip.
+    /// * `config`: The magnetometer configuration.
+    pub fn new_with_chip_select<F>(
+        dev: Spidev,
+        chip_select: F,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<io::Error>>
+    where
+        F: FnMut(bool) -> io::Result<()> + Send + 'static,
+    {
+        let chip_select: ChipSelect = Box::new(chip_select);
+        let options = crate::SensorOptions::default();
+        let interface = SpiInterface::new(dev, crate::Chip::Lis3mdl, Some(chip_select), options);
+        Magnetometer::from_bus(interface, config)
+    }
+}
+
+/// A reader for all sensors.
+pub type Imu = imu::Imu<SpiInterface>;
+
+impl Imu {
+    /// Creates a new IMU reader from the address of each chip's device.
+    ///
+    /// # Arguments
+    /// * `lsm6dsl_addr`: The SPI device address of the accelerometer &
+    ///   gyroscope, e.g. `/dev/spidev0.0`.
+    /// * `lis3mdl_addr`: The SPI device address of the magnetometer, e.g.
+    ///   `/dev/spidev0.1`.
+    pub fn new_from_address<P: AsRef<Path>, Q: AsRef<Path>>(
+        lsm6dsl_addr: P,
+        lis3mdl_addr: Q,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        Imu::new_from_address_with_config(lsm6dsl_addr, lis3mdl_addr, SpiConfig::default())
+    }
+
+    /// Creates a new IMU reader from the address of each chip's device, with
+    /// the given SPI bus options.
+    ///
+    /// # Arguments
+    /// * `lsm6dsl_addr`: The SPI device address of the accelerometer &
+    ///   gyroscope, e.g. `/dev/spidev0.0`.
+    /// * `lis3mdl_addr`: The SPI device address of the magnetometer, e.g.
+    ///   `/dev/spidev0.1`.
+    /// * `spi_config`: The SPI bus options, used for both devices.
+    pub fn new_from_address_with_config<P: AsRef<Path>, Q: AsRef<Path>>(
+        lsm6dsl_addr: P,
+        lis3mdl_addr: Q,
+        spi_config: SpiConfig,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        Ok(Imu::from_readers(
+            Lsm6dsl::new_from_address_with_config(lsm6dsl_addr, spi_config)?,
+            Magnetometer::new_from_address_with_config(lis3mdl_addr, spi_config)?,
+        ))
+    }
+
+    /// Creates a new IMU reader from a SPI device for each chip.
+    ///
+    /// # Arguments
+    /// * `lsm6dsl_dev`: The SPI device for the accelerometer & gyroscope.
+    /// * `lis3mdl_dev`: The SPI device for the magnetometer.
+    pub fn new(lsm6dsl_dev: Spidev, lis3mdl_dev: Spidev) -> Result<Self, crate::Error<io::Error>> {
+        Ok(Imu::from_readers(
+            Lsm6dsl::new(lsm6dsl_dev)?,
+            Magnetometer::new(lis3mdl_dev)?,
+        ))
+    }
+
+    /// Creates a new IMU reader from a SPI device for each chip, configuring
+    /// the sensors with a preset profile.
+    ///
+    /// # Arguments
+    /// * `lsm6dsl_dev`: The SPI device for the accelerometer & gyroscope.
+    /// * `lis3mdl_dev`: The SPI device for the magnetometer.
+    /// * `profile`: The preset configuration of all sensors.
+    pub fn new_with_profile(
+        lsm6dsl_dev: Spidev,
+        lis3mdl_dev: Spidev,
+        profile: crate::Profile,
+    ) -> Result<Self, crate::Error<io::Error>> {
+        Ok(Imu::from_readers(
+            Lsm6dsl::new_with_config(lsm6dsl_dev, profile.accel_config(), profile.gyro_config())?,
+            Magnetometer::new_with_config(lis3mdl_dev, profile.mag_config())?,
+        ))
+    }
+}
+
+/// An accelerometer reader that runs on its own thread, for use with tokio.
+/// Create it with `AsyncAccelerometer::new(accelerometer)`.
+#[cfg(feature = "tokio")]
+pub type AsyncAccelerometer = crate::worker::Worker<Accelerometer>;
+
+/// A gyroscope reader that runs on its own thread, for use with tokio.
+/// Create it with `AsyncGyroscope::new(gyroscope)`.
+#[cfg(feature = "tokio")]
+pub type AsyncGyroscope = crate::worker::Worker<Gyroscope>;
+
+/// A magnetometer reader that runs on its own thread, for use with tokio.
+/// Create it with `AsyncMagnetometer::new(magnetometer)`.
+#[cfg(feature = "tokio")]
+pub type AsyncMagnetometer = crate::worker::Worker<Magnetometer>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeBus;
+
+    #[test]
+    fn disable_i2c_once() {
+        let bus = FakeBus::lsm6dsl();
+        // Other bits in CTRL4_C are kept
+        bus.set(crate::LSM6DSL_CTRL4_C, 0b10);
+        for _ in 0..3 {
+            disable_lsm6dsl_i2c(&mut bus.clone()).unwrap();
+        }
+        assert_eq!(bus.writes_to(crate::LSM6DSL_CTRL4_C), [0b110]);
+    }
+
+    #[test]
+    fn disable_i2c_checks_who_am_i() {
+        let bus = FakeBus::new();
+        assert!(matches!(
+            disable_lsm6dsl_i2c(&mut bus.clone()),
+            Err(crate::Error::Init { actual: 0, .. })
+        ));
+        assert_eq!(bus.writes_to(crate::LSM6DSL_CTRL4_C), []);
     }
 }
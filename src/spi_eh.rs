@@ -0,0 +1,260 @@
+//! Readers for any SPI device implementing embedded-hal 1.0's `SpiDevice`
+//! trait, for platforms that don't provide `spidev`.
+
+use crate::bus::RegisterAccess;
+use crate::lis3mdl;
+use crate::lsm6dsl;
+use embedded_hal::spi::{Operation, SpiDevice};
+
+/// Register access to a chip through an embedded-hal SPI device.
+pub struct SpiInterface<S: SpiDevice> {
+    spi: S,
+    chip: crate::Chip,
+}
+
+impl<S: SpiDevice> RegisterAccess for SpiInterface<S> {
+    type Error = S::Error;
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<S::Error>> {
+        let mut buf = [0];
+        self.read_registers(reg, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<S::Error>> {
+        self.spi
+            .write(&[reg, value])
+            .map_err(crate::Error::write(reg))
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), crate::Error<S::Error>> {
+        let command = [self.chip.spi_read_command(reg, buf.len())];
+        self.spi
+            .transaction(&mut [Operation::Write(&command), Operation::Read(buf)])
+            .map_err(crate::Error::read(reg))
+    }
+}
+
+/// An accelerometer reader.
+pub type Accelerometer<S> = lsm6dsl::Accelerometer<SpiInterface<S>>;
+
+impl<S: SpiDevice> Accelerometer<S> {
+    /// Creates a new accelerometer reader from a SPI device.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    pub fn new(spi: S) -> Result<Self, crate::Error<S::Error>> {
+        Accelerometer::new_with_config(spi, crate::AccelConfig::default())
+    }
+
+    /// Creates a new accelerometer reader from a SPI device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    /// * `config`: The accelerometer configuration.
+    pub fn new_with_config(
+        spi: S,
+        config: crate::AccelConfig,
+    ) -> Result<Self, crate::Error<S::Error>> {
+        let chip = crate::Chip::Lsm6dsl;
+        Accelerometer::from_bus(SpiInterface { spi, chip }, config)
+    }
+}
+
+/// A gyroscope reader.
+pub type Gyroscope<S> = lsm6dsl::Gyroscope<SpiInterface<S>>;
+
+impl<S: SpiDevice> Gyroscope<S> {
+    /// Creates a new gyroscope reader from a SPI device.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    pub fn new(spi: S) -> Result<Self, crate::Error<S::Error>> {
+        Gyroscope::new_with_config(spi, crate::GyroConfig::default())
+    }
+
+    /// Creates a new gyroscope reader from a SPI device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    /// * `config`: The gyroscope configuration.
+    pub fn new_with_config(
+        spi: S,
+        config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<S::Error>> {
+        let chip = crate::Chip::Lsm6dsl;
+        Gyroscope::from_bus(SpiInterface { spi, chip }, config)
+    }
+}
+
+/// A reader for both the accelerometer and gyroscope, which share the
+/// LSM6DSL chip.
+pub type Lsm6dsl<S> = lsm6dsl::Lsm6dsl<SpiInterface<S>>;
+
+impl<S: SpiDevice> Lsm6dsl<S> {
+    /// Creates a new LSM6DSL reader from a SPI device.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    pub fn new(spi: S) -> Result<Self, crate::Error<S::Error>> {
+        Lsm6dsl::new_with_config(
+            spi,
+            crate::AccelConfig::default(),
+            crate::GyroConfig::default(),
+        )
+    }
+
+    /// Creates a new LSM6DSL reader from a SPI device, with the given
+    /// accelerometer and gyroscope configurations.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    /// * `accel_config`: The accelerometer configuration.
+    /// * `gyro_config`: The gyroscope configuration.
+    pub fn new_with_config(
+        spi: S,
+        accel_config: crate::AccelConfig,
+        gyro_config: crate::GyroConfig,
+    ) -> Result<Self, crate::Error<S::Error>> {
+        let chip = crate::Chip::Lsm6dsl;
+        Lsm6dsl::from_bus(SpiInterface { spi, chip }, accel_config, gyro_config)
+    }
+}
+
+/// A magnetometer reader.
+pub type Magnetometer<S> = lis3mdl::Magnetometer<SpiInterface<S>>;
+
+impl<S: SpiDevice> Magnetometer<S> {
+    /// Creates a new magnetometer reader from a SPI device.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    pub fn new(spi: S) -> Result<Self, crate::Error<S::Error>> {
+        Magnetometer::new_with_config(spi, crate::MagConfig::default())
+    }
+
+    /// Creates a new magnetometer reader from a SPI device, with the given
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `spi`: The SPI device.
+    /// * `config`: The magnetometer configuration.
+    pub fn new_with_config(
+        spi: S,
+        config: crate::MagConfig,
+    ) -> Result<Self, crate::Error<S::Error>> {
+        let chip = crate::Chip::Lis3mdl;
+        Magnetometer::from_bus(SpiInterface { spi, chip }, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal_mock::eh1::spi::{Mock, Transaction};
+
+    fn read(command: u8, response: &[u8]) -> [Transaction<u8>; 4] {
+        [
+            Transaction::transaction_start(),
+            Transaction::write_vec(vec![command]),
+            Transaction::read_vec(response.to_vec()),
+            Transaction::transaction_end(),
+        ]
+    }
+
+    fn write(reg: u8, value: u8) -> [Transaction<u8>; 3] {
+        [
+            Transaction::transaction_start(),
+            Transaction::write_vec(vec![reg, value]),
+            Transaction::transaction_end(),
+        ]
+    }
+
+    fn expect(transactions: &[&[Transaction<u8>]]) -> Mock<u8> {
+        Mock::new(&transactions.concat())
+    }
+
+    #[test]
+    fn accelerometer_new() {
+        let mut spi = expect(&[
+            // WHO_AM_I
+            &read(0x8F, &[0x6A]),
+            // The gyroscope's ODR in CTRL2_G, which is powered down
+            &read(0x91, &[0x00]),
+            // CTRL1_XL, CTRL8_XL
+            &write(0x10, 0x9F),
+            &write(0x17, 0xC8),
+            // BDU, IF_INC and BLE in CTRL3_C
+            &read(0x92, &[0x04]),
+            &write(0x12, 0x44),
+            // XL_HM_MODE in CTRL6_C, keeping the gyroscope's FTYPE
+            &read(0x95, &[0x01]),
+            &write(0x15, 0x01),
+            // OUTX_L_XL
+            &read(0xA8, &[1, 0, 2, 0, 3, 0]),
+        ]);
+        let mut accel = Accelerometer::new(spi.clone()).unwrap();
+        assert_eq!(accel.read().unwrap(), (1, 2, 3).into());
+        drop(accel);
+        spi.done();
+    }
+
+    #[test]
+    fn gyroscope_new() {
+        let mut spi = expect(&[
+            // WHO_AM_I
+            &read(0x8F, &[0x6A]),
+            // The accelerometer's ODR in CTRL1_XL, which is powered down
+            &read(0x90, &[0x00]),
+            // CTRL2_G, CTRL7_G
+            &write(0x11, 0x9C),
+            &write(0x16, 0x00),
+            // BLE in CTRL3_C
+            &read(0x92, &[0x44]),
+            &write(0x12, 0x44),
+            // LPF1_SEL_G in CTRL4_C, and FTYPE in CTRL6_C
+            &read(0x93, &[0x00]),
+            &write(0x13, 0x00),
+            &read(0x95, &[0x00]),
+            &write(0x15, 0x00),
+            // OUTX_L_G
+            &read(0xA2, &[1, 0, 2, 0, 3, 0]),
+        ]);
+        let mut gyro = Gyroscope::new(spi.clone()).unwrap();
+        assert_eq!(gyro.read().unwrap(), (1, 2, 3).into());
+        drop(gyro);
+        spi.done();
+    }
+
+    #[test]
+    fn magnetometer_new() {
+        let mut spi = expect(&[
+            // WHO_AM_I
+            &read(0x8F, &[0x3D]),
+            // CTRL_REG1 to CTRL_REG5
+            &write(0x20, 0xDC),
+            &write(0x21, 0x20),
+            &write(0x22, 0x00),
+            &write(0x23, 0x08),
+            &write(0x24, 0x40),
+            // OUT_X_L, with the MS bit set to auto-increment
+            &read(0xE8, &[1, 0, 2, 0, 3, 0]),
+        ]);
+        let mut mag = Magnetometer::new(spi.clone()).unwrap();
+        assert_eq!(mag.read().unwrap(), (1, 2, 3).into());
+        drop(mag);
+        spi.done();
+    }
+
+    #[test]
+    fn wrong_who_am_i() {
+        let mut spi = expect(&[&read(0x8F, &[0x00])]);
+        assert!(matches!(
+            Magnetometer::new(spi.clone()),
+            Err(crate::Error::Init { actual: 0x00, .. })
+        ));
+        spi.done();
+    }
+}
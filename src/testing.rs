@@ -0,0 +1,126 @@
+//! A fake bus for unit tests, which logs the writes made through it.
+
+use crate::bus::RegisterAccess;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::vec::Vec;
+
+struct State {
+    registers: [u8; 256],
+    writes: Vec<(u8, u8)>,
+    fail_reads: bool,
+    fail_writes: bool,
+    verify_writes: bool,
+    // A register, and how many more writes to it are lost
+    lost_writes: Option<(u8, usize)>,
+}
+
+/// A register map that's shared between its clones, so that it can be
+/// inspected after it's been moved into a reader.
+#[derive(Clone)]
+pub(crate) struct FakeBus(Rc<RefCell<State>>);
+
+impl FakeBus {
+    pub(crate) fn new() -> Self {
+        Self(Rc::new(RefCell::new(State {
+            registers: [0; 256],
+            writes: Vec::new(),
+            fail_reads: false,
+            fail_writes: false,
+            verify_writes: false,
+            lost_writes: None,
+        })))
+    }
+
+    /// A bus to an LSM6DSL, with its WHO_AM_I register set.
+    pub(crate) fn lsm6dsl() -> Self {
+        let bus = Self::new();
+        bus.set(crate::LSM6DSL_WHO_AM_I, 0x6A);
+        bus
+    }
+
+    /// A bus to a LIS3MDL, with its WHO_AM_I register set.
+    pub(crate) fn lis3mdl() -> Self {
+        let bus = Self::new();
+        bus.set(crate::LIS3MDL_WHO_AM_I, 0x3D);
+        bus
+    }
+
+    pub(crate) fn get(&self, reg: u8) -> u8 {
+        self.0.borrow().registers[reg as usize]
+    }
+
+    pub(crate) fn set(&self, reg: u8, value: u8) {
+        self.0.borrow_mut().registers[reg as usize] = value;
+    }
+
+    /// Makes every subsequent read fail, as if the device stopped responding.
+    pub(crate) fn fail_reads(&self) {
+        self.0.borrow_mut().fail_reads = true;
+    }
+
+    /// Makes every subsequent write fail, without logging it.
+    pub(crate) fn fail_writes(&self) {
+        self.0.borrow_mut().fail_writes = true;
+    }
+
+    /// Makes configuration writes be read back, as with
+    /// `SensorOptions::verify_writes`.
+    pub(crate) fn verify(&self) {
+        self.0.borrow_mut().verify_writes = true;
+    }
+
+    /// Makes the next `count` writes to a register succeed without taking
+    /// effect, as on a marginal bus. They're still logged.
+    pub(crate) fn lose_writes_to(&self, reg: u8, count: usize) {
+        self.0.borrow_mut().lost_writes = Some((reg, count));
+    }
+
+    /// The writes made so far to a register, in order.
+    pub(crate) fn writes_to(&self, reg: u8) -> Vec<u8> {
+        let state = self.0.borrow();
+        state
+            .writes
+            .iter()
+            .filter(|&&(r, _)| r == reg)
+            .map(|&(_, value)| value)
+            .collect()
+    }
+}
+
+impl RegisterAccess for FakeBus {
+    type Error = ();
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, crate::Error<()>> {
+        if self.0.borrow().fail_reads {
+            return Err(crate::Error::Read { reg, cause: None });
+        }
+        Ok(self.get(reg))
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), crate::Error<()>> {
+        let mut state = self.0.borrow_mut();
+        if state.fail_writes {
+            return Err(crate::Error::Write { reg, cause: None });
+        }
+        state.writes.push((reg, value));
+        match &mut state.lost_writes {
+            Some((lost, count)) if *lost == reg && *count > 0 => *count -= 1,
+            _ => state.registers[reg as usize] = value,
+        }
+        Ok(())
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), crate::Error<()>> {
+        if self.0.borrow().fail_reads {
+            return Err(crate::Error::Read { reg, cause: None });
+        }
+        let start = reg as usize;
+        buf.copy_from_slice(&self.0.borrow().registers[start..start + buf.len()]);
+        Ok(())
+    }
+
+    fn verify_writes(&self) -> bool {
+        self.0.borrow().verify_writes
+    }
+}
@@ -0,0 +1,187 @@
+//! Async wrappers for the blocking readers, for use with tokio. Each wrapper
+//! owns a dedicated thread that runs the reader's bus transactions, so they
+//! don't stall the reactor. The thread is stopped when the wrapper is
+//! dropped.
+
+use crate::bus::RegisterAccess;
+use crate::lis3mdl;
+use crate::lsm6dsl;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::{self, Interval};
+
+type Job<R> = Box<dyn FnOnce(&mut R) + Send>;
+
+/// Runs a blocking reader on a dedicated thread.
+pub struct Worker<R> {
+    jobs: Option<mpsc::Sender<Job<R>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<R: Send + 'static> Worker<R> {
+    /// Creates a new worker, moving the reader onto its thread.
+    ///
+    /// # Arguments
+    /// * `reader`: The blocking reader.
+    pub fn new(mut reader: R) -> Self {
+        let (jobs, rx) = mpsc::channel::<Job<R>>();
+        let thread = thread::spawn(move || {
+            for job in rx {
+                job(&mut reader);
+            }
+        });
+        Self {
+            jobs: Some(jobs),
+            thread: Some(thread),
+        }
+    }
+
+    /// Runs a function against the reader on the worker thread.
+    ///
+    /// # Arguments
+    /// * `f`: The function to run.
+    ///
+    /// # Panics
+    /// If a previous function panicked, taking down the worker thread.
+    pub async fn run<T, F>(&self, f: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut R) -> T + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let job: Job<R> = Box::new(move |reader| {
+            // The caller may have stopped waiting
+            let _ = tx.send(f(reader));
+        });
+        self.jobs
+            .as_ref()
+            .and_then(|jobs| jobs.send(job).ok())
+            .expect("worker thread exited");
+        rx.await.expect("worker thread exited")
+    }
+}
+
+impl<R> Drop for Worker<R> {
+    fn drop(&mut self) {
+        // Closing the channel ends the thread's loop
+        self.jobs.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Readings taken at a fixed interval.
+pub struct ReadStream<'a, R, T> {
+    worker: &'a Worker<R>,
+    interval: Interval,
+    read: fn(&mut R) -> T,
+}
+
+impl<'a, R: Send + 'static, T: Send + 'static> ReadStream<'a, R, T> {
+    fn new(worker: &'a Worker<R>, period: Duration, read: fn(&mut R) -> T) -> Self {
+        Self {
+            worker,
+            interval: time::interval(period),
+            read,
+        }
+    }
+
+    /// Waits for the next interval, then takes a reading.
+    pub async fn next(&mut self) -> T {
+        self.interval.tick().await;
+        self.worker.run(self.read).await
+    }
+}
+
+impl<B> Worker<lsm6dsl::Accelerometer<B>>
+where
+    B: RegisterAccess + Send + 'static,
+    B::Error: Send,
+{
+    /// Read the raw accelerometer values.
+    pub async fn read(&self) -> Result<crate::AccelReading, crate::Error<B::Error>> {
+        self.run(|accel| accel.read()).await
+    }
+
+    /// Read the accelerometer values, in g.
+    pub async fn read_scaled(&self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        self.run(|accel| accel.read_scaled()).await
+    }
+
+    /// Read the raw accelerometer values at a fixed interval.
+    ///
+    /// # Arguments
+    /// * `period`: The interval between readings.
+    #[allow(clippy::type_complexity)]
+    pub fn read_stream(
+        &self,
+        period: Duration,
+    ) -> ReadStream<
+        '_,
+        lsm6dsl::Accelerometer<B>,
+        Result<crate::AccelReading, crate::Error<B::Error>>,
+    > {
+        ReadStream::new(self, period, |accel| accel.read())
+    }
+}
+
+impl<B> Worker<lsm6dsl::Gyroscope<B>>
+where
+    B: RegisterAccess + Send + 'static,
+    B::Error: Send,
+{
+    /// Read the raw gyroscope values.
+    pub async fn read(&self) -> Result<crate::GyroReading, crate::Error<B::Error>> {
+        self.run(|gyro| gyro.read()).await
+    }
+
+    /// Read the gyroscope values, in degrees per second.
+    pub async fn read_scaled(&self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        self.run(|gyro| gyro.read_scaled()).await
+    }
+
+    /// Read the raw gyroscope values at a fixed interval.
+    ///
+    /// # Arguments
+    /// * `period`: The interval between readings.
+    #[allow(clippy::type_complexity)]
+    pub fn read_stream(
+        &self,
+        period: Duration,
+    ) -> ReadStream<'_, lsm6dsl::Gyroscope<B>, Result<crate::GyroReading, crate::Error<B::Error>>>
+    {
+        ReadStream::new(self, period, |gyro| gyro.read())
+    }
+}
+
+impl<B> Worker<lis3mdl::Magnetometer<B>>
+where
+    B: RegisterAccess + Send + 'static,
+    B::Error: Send,
+{
+    /// Read the raw magnetometer values.
+    pub async fn read(&self) -> Result<crate::MagReading, crate::Error<B::Error>> {
+        self.run(|mag| mag.read()).await
+    }
+
+    /// Read the magnetometer values, in gauss.
+    pub async fn read_scaled(&self) -> Result<(f32, f32, f32), crate::Error<B::Error>> {
+        self.run(|mag| mag.read_scaled()).await
+    }
+
+    /// Read the raw magnetometer values at a fixed interval.
+    ///
+    /// # Arguments
+    /// * `period`: The interval between readings.
+    #[allow(clippy::type_complexity)]
+    pub fn read_stream(
+        &self,
+        period: Duration,
+    ) -> ReadStream<'_, lis3mdl::Magnetometer<B>, Result<crate::MagReading, crate::Error<B::Error>>>
+    {
+        ReadStream::new(self, period, |mag| mag.read())
+    }
+}
@@ -0,0 +1,47 @@
+use berryimu::mock::{Accelerometer, Board, Magnetometer};
+use berryimu::worker::Worker;
+use berryimu::{AccelConfig, AccelReading, MagReading};
+use std::time::Duration;
+
+#[tokio::test]
+async fn read() {
+    let board = Board::new();
+    let accel = Worker::new(Accelerometer::new(&board).unwrap());
+    let mag = Worker::new(Magnetometer::new(&board).unwrap());
+    board.set_accel(AccelReading { x: 1, y: 2, z: 3 });
+    board.set_mag(MagReading { x: 4, y: 5, z: 6 });
+    assert_eq!(
+        accel.read().await.unwrap(),
+        AccelReading { x: 1, y: 2, z: 3 }
+    );
+    assert_eq!(mag.read().await.unwrap(), MagReading { x: 4, y: 5, z: 6 });
+}
+
+#[tokio::test(start_paused = true)]
+async fn read_stream() {
+    let board = Board::new();
+    let accel = Worker::new(Accelerometer::new(&board).unwrap());
+    let mut stream = accel.read_stream(Duration::from_millis(10));
+    for x in 0..3 {
+        board.set_accel(AccelReading { x, y: 0, z: 0 });
+        let start = tokio::time::Instant::now();
+        assert_eq!(stream.next().await.unwrap().x, x);
+        // The first reading is taken immediately
+        if x > 0 {
+            assert_eq!(start.elapsed(), Duration::from_millis(10));
+        }
+    }
+}
+
+#[tokio::test]
+async fn drop_stops_the_worker() {
+    let board = Board::new();
+    let config = AccelConfig::builder().power_down_on_drop(true).build();
+    let accel = Worker::new(Accelerometer::new_with_config(&board, config).unwrap());
+    accel.read().await.unwrap();
+    assert_ne!(board.lsm6dsl_register(0x10) & 0xF0, 0);
+    // Dropping the worker waits for its thread, which drops the reader, so
+    // it's powered down by the time the drop returns.
+    drop(accel);
+    assert_eq!(board.lsm6dsl_register(0x10) & 0xF0, 0);
+}